@@ -6,8 +6,36 @@ use std::{
 pub mod overlay;
 mod types;
 pub use overlay::Overlay;
+use thiserror::Error;
 pub use types::*;
 
+/// Why a block, qc or vote was rejected by [`Carnot`]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum Error<Id> {
+    #[error("Parent block: {0:?} is not known to this node")]
+    ParentMissing(Id),
+    #[error("Block: {0:?} is not known to this node")]
+    BlockMissing(Id),
+    #[error("Block: {0:?} was already received")]
+    DuplicateBlock(Id),
+    #[error("View {view} is not after the current view {current_view}")]
+    OldView { view: View, current_view: View },
+    #[error("View {view} is ahead of the view following the current view {current_view}")]
+    FutureView { view: View, current_view: View },
+    #[error("Block was proposed by {0:?}, which is not the leader of its view")]
+    UnexpectedLeader(NodeId),
+    #[error("Qc for view {0} is not signed by a super majority of the root committee")]
+    InvalidQc(View),
+    #[error("Timeout qc for view {0} was not built by the root committee")]
+    InvalidTimeoutQc(View),
+    #[error("Block: {0:?} does not follow the qc it carries")]
+    UnsafeBlock(Id),
+    #[error("Already voted in view {0}")]
+    AlreadyVoted(View),
+    #[error("New view votes for view {0} do not match the timeout qc")]
+    InvalidNewViews(View),
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Carnot<O: Overlay, Id: Eq + Hash> {
     id: NodeId,
@@ -19,12 +47,13 @@ pub struct Carnot<O: Overlay, Id: Eq + Hash> {
     last_view_timeout_qc: Option<TimeoutQc<Id>>,
     latest_committed_block: Option<Id>,
     overlay: O,
+    verify_qcs: bool,
 }
 
 impl<O, Id> Carnot<O, Id>
 where
     O: Overlay,
-    Id: Copy + Eq + Hash + core::fmt::Debug,
+    Id: Copy + Eq + Hash + core::fmt::Debug + SignableBlockId,
 {
     pub fn from_genesis(id: NodeId, genesis_block: Block<Id>, overlay: O) -> Self {
         Self {
@@ -37,9 +66,75 @@ where
             overlay,
             safe_blocks: [(genesis_block.id, genesis_block.clone())].into(),
             tip: genesis_block.id,
+            verify_qcs: false,
         }
     }
 
+    /// Resume from a checkpoint taken before a restart. Blocks committed before the checkpoint are
+    /// not kept, apart from genesis and the latest committed one.
+    /// `overlay` is expected to be in the state it was when the checkpoint was taken.
+    pub fn from_checkpoint(
+        id: NodeId,
+        genesis_block: Block<Id>,
+        checkpoint: Checkpoint<Id>,
+        overlay: O,
+    ) -> Self {
+        let Checkpoint {
+            current_view,
+            highest_voted_view,
+            high_qc,
+            last_view_timeout_qc,
+            latest_committed_block,
+            pending_blocks,
+        } = checkpoint;
+        let mut engine = Self::from_genesis(id, genesis_block, overlay);
+        if latest_committed_block.view > View(0) {
+            engine.latest_committed_block = Some(latest_committed_block.id);
+        }
+        engine.safe_blocks.extend(
+            std::iter::once(latest_committed_block)
+                .chain(pending_blocks)
+                .map(|block| (block.id, block)),
+        );
+        engine.tip = engine
+            .safe_blocks
+            .values()
+            .max_by_key(|block| block.view)
+            .map(|block| block.id)
+            .unwrap_or(engine.tip);
+        engine.current_view = current_view;
+        engine.highest_voted_view = highest_voted_view;
+        engine.local_high_qc = high_qc;
+        engine.last_view_timeout_qc = last_view_timeout_qc;
+        engine
+    }
+
+    /// Everything needed to resume from the current state with [`Carnot::from_checkpoint`]
+    pub fn checkpoint(&self) -> Checkpoint<Id> {
+        let latest_committed_block = self.latest_committed_block();
+        let mut pending_blocks = self
+            .safe_blocks
+            .values()
+            .filter(|block| block.view > latest_committed_block.view)
+            .cloned()
+            .collect::<Vec<_>>();
+        pending_blocks.sort_by_key(|block| block.view);
+        Checkpoint {
+            current_view: self.current_view,
+            highest_voted_view: self.highest_voted_view,
+            high_qc: self.local_high_qc.clone(),
+            last_view_timeout_qc: self.last_view_timeout_qc.clone(),
+            latest_committed_block,
+            pending_blocks,
+        }
+    }
+
+    /// Only accept blocks whose parent qc is signed by a super majority of the root committee
+    pub fn with_qc_verification(mut self) -> Self {
+        self.verify_qcs = true;
+        self
+    }
+
     pub fn current_view(&self) -> View {
         self.current_view
     }
@@ -65,68 +160,80 @@ where
     ///     success before `receive_block(b)`.
     ///  *  Overlay changes for views < block.view should be made available before trying to process
     ///     a block by calling `receive_timeout_qc`.
-    #[allow(clippy::result_unit_err)]
-    pub fn receive_block(&self, block: Block<Id>) -> Result<Self, ()> {
-        assert!(
-            self.safe_blocks.contains_key(&block.parent()),
-            "out of order view not supported, missing parent block for {block:?}",
-        );
-        // if the block has already been processed, return early
+    ///
+    /// A block whose parent is missing, or which is ahead of the next view, can be retried once the
+    /// missing blocks or timeout qcs have been received.
+    pub fn receive_block(&self, block: Block<Id>) -> Result<Self, Error<Id>> {
+        if !self.safe_blocks.contains_key(&block.parent()) {
+            return Err(Error::ParentMissing(block.parent()));
+        }
         if self.safe_blocks.contains_key(&block.id) {
-            return Ok(self.clone());
+            return Err(Error::DuplicateBlock(block.id));
+        }
+        // This only accepts blocks for current_view + 1
+        if block.view <= self.current_view {
+            //  TODO: Report malicious leader
+            //  TODO: it could be possible that a malicious leader send a block to a node and another one to
+            //  the rest of the network. The node should be able to catch up with the rest of the network after having
+            //  validated that the history of the block is correct and diverged from its fork.
+            //  By rejecting any other blocks except the first one received for a view this code does NOT do that.
+            return Err(Error::OldView {
+                view: block.view,
+                current_view: self.current_view,
+            });
+        }
+        if block.view > self.current_view.next() {
+            return Err(Error::FutureView {
+                view: block.view,
+                current_view: self.current_view,
+            });
         }
 
         match block.leader_proof {
             LeaderProof::LeaderId { leader_id } => {
                 // This only accepts blocks from the leader of current_view + 1
-                if leader_id != self.overlay.next_leader()
-                    || block.view != self.current_view().next()
-                {
-                    return Err(());
+                if leader_id != self.overlay.next_leader() {
+                    return Err(Error::UnexpectedLeader(leader_id));
                 }
             }
         }
 
-        if self.blocks_in_view(block.view).contains(&block)
-            || block.view <= self.latest_committed_view()
-        {
-            //  TODO: Report malicious leader
-            //  TODO: it could be possible that a malicious leader send a block to a node and another one to
-            //  the rest of the network. The node should be able to catch up with the rest of the network after having
-            //  validated that the history of the block is correct and diverged from its fork.
-            //  By rejecting any other blocks except the first one received for a view this code does NOT do that.
-            return Err(());
+        if self.verify_qcs {
+            self.validate_qc(&block.parent_qc)?;
+        }
+        if !self.block_is_safe(block.clone()) {
+            return Err(Error::UnsafeBlock(block.id));
         }
         let mut new_state = self.clone();
-        if new_state.block_is_safe(block.clone()) {
-            new_state.safe_blocks.insert(block.id, block.clone());
-            if block.view > new_state.tip().view {
-                new_state.tip = block.id;
-                new_state.update_latest_committed_block(&block);
-            }
-            new_state.update_high_qc(block.parent_qc);
-        } else {
-            // Non safe block, not necessarily an error
-            return Err(());
+        new_state.safe_blocks.insert(block.id, block.clone());
+        if block.view > new_state.tip().view {
+            new_state.tip = block.id;
+            new_state.update_latest_committed_block(&block);
         }
+        new_state.update_high_qc(block.parent_qc);
         Ok(new_state)
     }
 
     /// Upon reception of a global timeout event
     ///
-    /// Preconditions:
-    pub fn receive_timeout_qc(&self, timeout_qc: TimeoutQc<Id>) -> Self {
-        let mut new_state = self.clone();
-
-        if timeout_qc.view() < new_state.current_view {
-            return new_state;
+    /// Timeout qcs for views before the current one are rejected, as the node already moved on.
+    pub fn receive_timeout_qc(&self, timeout_qc: TimeoutQc<Id>) -> Result<Self, Error<Id>> {
+        if timeout_qc.view() < self.current_view {
+            return Err(Error::OldView {
+                view: timeout_qc.view(),
+                current_view: self.current_view,
+            });
         }
+        if self.verify_qcs {
+            self.validate_timeout_qc(&timeout_qc)?;
+        }
+        let mut new_state = self.clone();
         new_state.update_high_qc(Qc::Standard(timeout_qc.high_qc().clone()));
         new_state.update_timeout_qc(timeout_qc.clone());
 
         new_state.current_view = timeout_qc.view().next();
 
-        new_state
+        Ok(new_state)
     }
 
     /// Upon reception of a supermajority of votes for a safe block from children
@@ -135,17 +242,13 @@ where
     /// Preconditions:
     /// *  `receive_block(b)` must have been called successfully before trying to approve a block b.
     /// *   A node should not attempt to vote for a block in a view earlier than the latest one it actively participated in.
-    pub fn approve_block(&self, block: Block<Id>) -> (Self, Send<Id>) {
-        assert!(
-            self.safe_blocks.contains_key(&block.id),
-            "{:?} not in {:?}",
-            block,
-            self.safe_blocks
-        );
-        assert!(
-            self.highest_voted_view < block.view,
-            "can't vote for a block in the past"
-        );
+    pub fn approve_block(&self, block: Block<Id>) -> Result<(Self, Send<Id>), Error<Id>> {
+        if !self.safe_blocks.contains_key(&block.id) {
+            return Err(Error::BlockMissing(block.id));
+        }
+        if self.highest_voted_view >= block.view {
+            return Err(Error::AlreadyVoted(block.view));
+        }
 
         let mut new_state = self.clone();
 
@@ -159,7 +262,7 @@ where
                 .parent_committee(self.id)
                 .expect("Non root committee members parent should be present")
         };
-        (
+        Ok((
             new_state,
             Send {
                 to,
@@ -168,7 +271,7 @@ where
                     view: block.view,
                 }),
             },
-        )
+        ))
     }
 
     /// Upon reception of a supermajority of votes for a new view from children of the current node.
@@ -182,27 +285,34 @@ where
         &self,
         timeout_qc: TimeoutQc<Id>,
         new_views: HashSet<NewView<Id>>,
-    ) -> (Self, Send<Id>) {
+    ) -> Result<(Self, Send<Id>), Error<Id>> {
         let new_view = timeout_qc.view().next();
-        assert!(
-            new_view
-                > self
-                    .last_view_timeout_qc
-                    .as_ref()
-                    .map(|qc| qc.view())
-                    .unwrap_or(View(0)),
-            "can't vote for a new view not bigger than the last timeout_qc"
-        );
-        assert_eq!(
-            new_views.len(),
-            self.overlay.super_majority_threshold(self.id)
-        );
-        assert!(new_views.iter().all(|nv| self
-            .overlay
-            .is_member_of_child_committee(self.id, nv.sender)));
-        assert!(self.highest_voted_view < new_view);
-        assert!(new_views.iter().all(|nv| nv.view == new_view));
-        assert!(new_views.iter().all(|nv| nv.timeout_qc == timeout_qc));
+        // can't vote for a new view not bigger than the last timeout_qc
+        if new_view
+            <= self
+                .last_view_timeout_qc
+                .as_ref()
+                .map(|qc| qc.view())
+                .unwrap_or(View(0))
+        {
+            return Err(Error::OldView {
+                view: new_view,
+                current_view: self.current_view,
+            });
+        }
+        if self.highest_voted_view >= new_view {
+            return Err(Error::AlreadyVoted(new_view));
+        }
+        if new_views.len() != self.overlay.super_majority_threshold(self.id)
+            || !new_views.iter().all(|nv| {
+                self.overlay
+                    .is_member_of_child_committee(self.id, nv.sender)
+                    && nv.view == new_view
+                    && nv.timeout_qc == timeout_qc
+            })
+        {
+            return Err(Error::InvalidNewViews(new_view));
+        }
 
         let mut new_state = self.clone();
 
@@ -230,13 +340,13 @@ where
                 .parent_committee(new_state.id)
                 .expect("Non root committee members parent should be present")
         };
-        (
+        Ok((
             new_state,
             Send {
                 to,
                 payload: Payload::NewView(new_view_msg),
             },
-        )
+        ))
     }
 
     /// Upon a configurable amount of time has elapsed since the last view change
@@ -269,6 +379,39 @@ where
         (new_state, None)
     }
 
+    /// Check that `qc` is signed by a supermajority of the root committee and its children
+    pub fn validate_qc(&self, qc: &Qc<Id>) -> Result<(), Error<Id>> {
+        let committee = self.root_committee_with_children();
+        let threshold = self.overlay.leader_super_majority_threshold(self.id);
+        let high_qc_is_valid = |high_qc: &StandardQc<Id>| {
+            *high_qc == StandardQc::genesis(self.genesis_block().id)
+                || high_qc.verify(&committee, threshold)
+        };
+        let valid = match qc {
+            Qc::Standard(qc) => high_qc_is_valid(qc),
+            Qc::Aggregated(qc) => qc.verify(&committee, threshold) && high_qc_is_valid(&qc.high_qc),
+        };
+        if !valid {
+            return Err(Error::InvalidQc(qc.view()));
+        }
+        Ok(())
+    }
+
+    /// Check that `timeout_qc` was built by a member of the root committee from the timeouts of a
+    /// supermajority of the root committee and its children, on top of a valid high qc
+    pub fn validate_timeout_qc(&self, timeout_qc: &TimeoutQc<Id>) -> Result<(), Error<Id>> {
+        let committee = self.root_committee_with_children();
+        let threshold = self.overlay.leader_super_majority_threshold(self.id);
+        // timeout qcs received from the network did not go through `TimeoutQc::new`
+        if timeout_qc.view() < timeout_qc.high_qc().view
+            || !self.overlay.root_committee().contains(&timeout_qc.sender())
+            || !timeout_qc.verify(&committee, threshold)
+        {
+            return Err(Error::InvalidTimeoutQc(timeout_qc.view()));
+        }
+        self.validate_qc(&Qc::Standard(timeout_qc.high_qc().clone()))
+    }
+
     fn block_is_safe(&self, block: Block<Id>) -> bool {
         block.view >= self.current_view && block.view == block.parent_qc.view().next()
     }
@@ -408,6 +551,26 @@ where
         self.overlay.is_member_of_root_committee(self.id)
     }
 
+    pub fn is_child_of_root_committee(&self) -> bool {
+        self.overlay.is_child_of_root_committee(self.id)
+    }
+
+    /// Nodes whose votes are collected by the leader: the members of the root committee
+    /// and of its child committees
+    pub fn root_committee_with_children(&self) -> Committee {
+        let root_committee = self.overlay.root_committee();
+        let children = root_committee
+            .iter()
+            .next()
+            .map(|member| self.overlay.child_committees(*member))
+            .unwrap_or_default();
+        children
+            .into_iter()
+            .flatten()
+            .chain(root_committee)
+            .collect()
+    }
+
     pub fn overlay(&self) -> &O {
         &self.overlay
     }
@@ -441,10 +604,19 @@ where
 mod test {
     use std::convert::Infallible;
 
-    use crate::overlay::{FlatOverlay, FlatOverlaySettings, FreezeMembership, RoundRobin};
+    use crate::overlay::{
+        FlatOverlay, FlatOverlaySettings, FreezeMembership, RoundRobin, TreeOverlay,
+        TreeOverlaySettings,
+    };
 
     use super::*;
 
+    impl SignableBlockId for usize {
+        fn signable_bytes(&self) -> Vec<u8> {
+            self.to_be_bytes().to_vec()
+        }
+    }
+
     fn init(nodes: Vec<NodeId>) -> Carnot<FlatOverlay<RoundRobin, FreezeMembership>, usize> {
         assert!(!nodes.is_empty());
 
@@ -478,6 +650,7 @@ mod test {
             parent_qc: Qc::Standard(StandardQc {
                 view: block.view,
                 id: block.id,
+                signature: QcSignature::default(),
             }),
             leader_proof: LeaderProof::LeaderId {
                 leader_id: engine.overlay().next_leader(),
@@ -499,6 +672,210 @@ mod test {
             .unwrap()
     }
 
+    fn sign_qc(
+        committee: &Committee,
+        keys: &[bls_signatures::PrivateKey],
+        qc: &mut StandardQc<usize>,
+    ) {
+        use bls_signatures::Serialize;
+        let signatures = keys
+            .iter()
+            .map(|key| {
+                let voter = NodeId::from_public_key(&key.public_key());
+                let signature = key.sign(vote_message(voter, qc.view, &qc.id));
+                (voter, key.public_key().as_bytes(), signature.as_bytes())
+            })
+            .collect::<Vec<_>>();
+        qc.signature = QcSignature::aggregate(
+            committee,
+            signatures
+                .iter()
+                .map(|(voter, public_key, signature)| (*voter, &public_key[..], &signature[..])),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn receive_block_with_qc_verification() {
+        let keys = (1..=4)
+            .map(|i| bls_signatures::PrivateKey::new([i; 32]))
+            .collect::<Vec<_>>();
+        let nodes = keys
+            .iter()
+            .map(|key| NodeId::from_public_key(&key.public_key()))
+            .collect::<Vec<_>>();
+        let engine = init(nodes).with_qc_verification();
+        let (committee, threshold) = (
+            engine.root_committee_with_children(),
+            engine.leader_super_majority_threshold(),
+        );
+        let mut block1 = next_block(&engine, &engine.genesis_block());
+
+        // unsigned qc
+        assert_eq!(
+            engine.receive_block(block1.clone()),
+            Err(Error::InvalidQc(View(0)))
+        );
+        // not enough signatures
+        let Qc::Standard(qc) = &mut block1.parent_qc else {
+            unreachable!()
+        };
+        sign_qc(&committee, &keys[..threshold - 1], qc);
+        assert_eq!(
+            engine.receive_block(block1.clone()),
+            Err(Error::InvalidQc(View(0)))
+        );
+
+        let Qc::Standard(qc) = &mut block1.parent_qc else {
+            unreachable!()
+        };
+        sign_qc(&committee, &keys[..threshold], qc);
+        let engine = engine.receive_block(block1.clone()).unwrap();
+        assert_eq!(engine.current_view(), View(1));
+        assert!(engine.safe_blocks().contains_key(&block1.id));
+    }
+
+    fn sign_timeouts(
+        committee: &Committee,
+        keys: &[bls_signatures::PrivateKey],
+        view: View,
+    ) -> QcSignature {
+        use bls_signatures::Serialize;
+        let signatures = keys
+            .iter()
+            .map(|key| {
+                let sender = NodeId::from_public_key(&key.public_key());
+                let signature = key.sign(timeout_message(sender, view));
+                (sender, key.public_key().as_bytes(), signature.as_bytes())
+            })
+            .collect::<Vec<_>>();
+        QcSignature::aggregate(
+            committee,
+            signatures
+                .iter()
+                .map(|(sender, public_key, signature)| (*sender, &public_key[..], &signature[..])),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn timeout_qc_validity() {
+        let keys = (1..=4)
+            .map(|i| bls_signatures::PrivateKey::new([i; 32]))
+            .collect::<Vec<_>>();
+        let nodes = keys
+            .iter()
+            .map(|key| NodeId::from_public_key(&key.public_key()))
+            .collect::<Vec<_>>();
+        let engine = init(nodes.clone());
+        let (committee, threshold) = (
+            engine.root_committee_with_children(),
+            engine.leader_super_majority_threshold(),
+        );
+        let genesis_qc = StandardQc::genesis(0);
+        let timeouts = sign_timeouts(&committee, &keys[..threshold], View(3));
+        assert_eq!(
+            engine.validate_timeout_qc(&TimeoutQc::new(
+                View(3),
+                genesis_qc.clone(),
+                nodes[1],
+                timeouts.clone()
+            )),
+            Ok(())
+        );
+        // not built by the root committee
+        assert_eq!(
+            engine.validate_timeout_qc(&TimeoutQc::new(
+                View(3),
+                genesis_qc.clone(),
+                NodeId::new([0; 32]),
+                timeouts.clone()
+            )),
+            Err(Error::InvalidTimeoutQc(View(3)))
+        );
+        // unsigned, or without enough timeouts
+        assert_eq!(
+            engine.validate_timeout_qc(&TimeoutQc::new(
+                View(3),
+                genesis_qc.clone(),
+                nodes[1],
+                QcSignature::default()
+            )),
+            Err(Error::InvalidTimeoutQc(View(3)))
+        );
+        assert_eq!(
+            engine.validate_timeout_qc(&TimeoutQc::new(
+                View(3),
+                genesis_qc.clone(),
+                nodes[1],
+                sign_timeouts(&committee, &keys[..threshold - 1], View(3))
+            )),
+            Err(Error::InvalidTimeoutQc(View(3)))
+        );
+        // the timeouts are for another view
+        assert_eq!(
+            engine.validate_timeout_qc(&TimeoutQc::new(
+                View(4),
+                genesis_qc,
+                nodes[1],
+                timeouts.clone()
+            )),
+            Err(Error::InvalidTimeoutQc(View(4)))
+        );
+
+        let mut high_qc = StandardQc {
+            view: View(1),
+            id: 1,
+            signature: QcSignature::default(),
+        };
+        assert_eq!(
+            engine.validate_timeout_qc(&TimeoutQc::new(
+                View(3),
+                high_qc.clone(),
+                nodes[1],
+                timeouts.clone()
+            )),
+            Err(Error::InvalidQc(View(1)))
+        );
+        sign_qc(&committee, &keys[..threshold], &mut high_qc);
+        assert_eq!(
+            engine.validate_timeout_qc(&TimeoutQc::new(View(3), high_qc, nodes[1], timeouts)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn root_committee_with_children_in_tree_overlay() {
+        let nodes = (0..10).map(|i| NodeId::new([i; 32])).collect::<Vec<_>>();
+        let overlay = TreeOverlay::new(TreeOverlaySettings {
+            nodes: nodes.clone(),
+            current_leader: nodes[0],
+            number_of_committees: 3,
+            leader: RoundRobin::default(),
+            committee_membership: FreezeMembership,
+            super_majority_threshold: None,
+        });
+        let genesis = Block {
+            view: View(0),
+            id: 0,
+            parent_qc: Qc::Standard(StandardQc::genesis(0)),
+            leader_proof: LeaderProof::LeaderId {
+                leader_id: nodes[0],
+            },
+        };
+        let engine: Carnot<_, usize> = Carnot::from_genesis(nodes[0], genesis, overlay);
+
+        let voters = engine.root_committee_with_children();
+        assert_eq!(voters.len(), nodes.len());
+        assert!(engine.root_committee().iter().all(|id| voters.contains(id)));
+        assert!(engine
+            .child_committees()
+            .iter()
+            .flatten()
+            .all(|id| voters.contains(id)));
+        assert_eq!(engine.leader_super_majority_threshold(), 7);
+    }
+
     #[test]
     // Ensure that all states are initialized correctly with the genesis block.
     fn from_genesis() {
@@ -526,7 +903,7 @@ mod test {
     }
 
     #[test]
-    // Ensure that receive_block() returns early if the same block ID has already been received.
+    // Ensure that receive_block() fails if the same block ID has already been received.
     fn receive_duplicate_block_id() {
         let mut engine = init(vec![NodeId::new([0; 32])]);
 
@@ -536,12 +913,14 @@ mod test {
 
         let mut block2 = next_block(&engine, &block1);
         block2.id = block1.id;
-        engine = engine.receive_block(block2).unwrap();
+        assert_eq!(
+            engine.receive_block(block2),
+            Err(Error::DuplicateBlock(block1.id))
+        );
         assert_eq!(engine.blocks_in_view(View(1)), vec![block1]);
     }
 
     #[test]
-    #[should_panic(expected = "out of order view not supported, missing parent block")]
     // Ensure that receive_block() fails if the parent block has never been received.
     fn receive_block_with_unknown_parent() {
         let engine = init(vec![NodeId::new([0; 32])]);
@@ -552,13 +931,17 @@ mod test {
             parent_qc: Qc::Standard(StandardQc {
                 view: engine.current_view(),
                 id: parent_block_id,
+                signature: QcSignature::default(),
             }),
             leader_proof: LeaderProof::LeaderId {
                 leader_id: NodeId::new([0; 32]),
             },
         };
 
-        let _ = engine.receive_block(block);
+        assert_eq!(
+            engine.receive_block(block),
+            Err(Error::ParentMissing(parent_block_id))
+        );
     }
 
     #[test]
@@ -576,11 +959,21 @@ mod test {
 
         let mut unsafe_block = next_block(&engine, &block);
         unsafe_block.view = engine.current_view().prev(); // UNSAFE: view < engine.current_view
-        assert!(engine.receive_block(unsafe_block).is_err());
+        assert_eq!(
+            engine.receive_block(unsafe_block),
+            Err(Error::OldView {
+                view: View(1),
+                current_view: View(2)
+            })
+        );
 
-        let mut unsafe_block = next_block(&engine, &block);
-        unsafe_block.view = unsafe_block.parent_qc.view() + View(2); // UNSAFE: view != parent_qc.view + 1
-        assert!(engine.receive_block(unsafe_block).is_err());
+        let mut unsafe_block = next_block(&engine, &engine.genesis_block());
+        unsafe_block.id = 10;
+        unsafe_block.view = engine.current_view().next(); // UNSAFE: view != parent_qc.view + 1
+        assert_eq!(
+            engine.receive_block(unsafe_block),
+            Err(Error::UnsafeBlock(10))
+        );
     }
 
     #[test]
@@ -617,6 +1010,49 @@ mod test {
         );
     }
 
+    #[test]
+    // Ensure that a node resumed from a checkpoint keeps its votes and can follow the chain
+    fn resume_from_checkpoint() {
+        let mut engine = init(vec![NodeId::new([0; 32])]);
+        let mut block = engine.genesis_block();
+        for _ in 0..4 {
+            block = next_block(&engine, &block);
+            engine = engine.receive_block(block.clone()).unwrap();
+            engine = update_leader_selection(&engine);
+        }
+        let (engine, _) = engine.approve_block(block.clone()).unwrap();
+        let checkpoint = engine.checkpoint();
+        assert_eq!(checkpoint.latest_committed_block.view, View(2));
+        assert_eq!(
+            checkpoint
+                .pending_blocks
+                .iter()
+                .map(|block| block.view)
+                .collect::<Vec<_>>(),
+            vec![View(3), View(4)]
+        );
+
+        let resumed = Carnot::from_checkpoint(
+            engine.id(),
+            engine.genesis_block(),
+            checkpoint.clone(),
+            engine.overlay().clone(),
+        );
+        assert_eq!(resumed.checkpoint(), checkpoint);
+        assert_eq!(resumed.tip(), engine.tip());
+        assert_eq!(resumed.highest_voted_view(), View(4));
+        // blocks committed before the checkpoint are gone
+        assert_eq!(
+            resumed.latest_committed_blocks(None),
+            vec![engine.latest_committed_block().id]
+        );
+
+        let block5 = next_block(&resumed, &block);
+        let resumed = resumed.receive_block(block5).unwrap();
+        assert_eq!(resumed.current_view(), View(5));
+        assert_eq!(resumed.latest_committed_view(), View(3));
+    }
+
     #[test]
     // Ensure that the leader check in receive_block fails
     // if the block is proposed by an unexpected leader.
@@ -632,7 +1068,10 @@ mod test {
         block.leader_proof = LeaderProof::LeaderId {
             leader_id: NodeId::new([0; 32]), // unexpected leader
         };
-        assert!(engine.receive_block(block).is_err());
+        assert_eq!(
+            engine.receive_block(block),
+            Err(Error::UnexpectedLeader(NodeId::new([0; 32])))
+        );
     }
 
     #[test]
@@ -656,18 +1095,32 @@ mod test {
                     // a known parent block
                     id: block1.id,
                     view: block1.view,
+                    signature: QcSignature::default(),
                 },
+                signature: QcSignature::default(),
             }),
             leader_proof: LeaderProof::LeaderId {
                 leader_id: engine.overlay().next_leader(),
             },
         };
-        assert!(engine.receive_block(future_block).is_err());
+        assert_eq!(
+            engine.receive_block(future_block),
+            Err(Error::FutureView {
+                view: View(11),
+                current_view: View(1)
+            })
+        );
 
         // a past block should be also rejected
         let mut past_block = block1; // with the same view as block1
         past_block.id = 10;
-        assert!(engine.receive_block(past_block).is_err());
+        assert_eq!(
+            engine.receive_block(past_block),
+            Err(Error::OldView {
+                view: View(1),
+                current_view: View(1)
+            })
+        );
     }
 
     #[test]
@@ -683,7 +1136,7 @@ mod test {
         engine = engine.receive_block(block.clone()).unwrap();
         engine = update_leader_selection(&engine);
 
-        let (engine, send) = engine.approve_block(block.clone());
+        let (engine, send) = engine.approve_block(block.clone()).unwrap();
         assert_eq!(engine.highest_voted_view, block.view);
         assert_eq!(
             send.to,
@@ -699,17 +1152,18 @@ mod test {
     }
 
     #[test]
-    #[should_panic(expected = "not in")]
     // Ensure that approve_block cannot accept not-received blocks.
     fn approve_block_not_received() {
         let engine = init(vec![NodeId::new([0; 32])]);
 
         let block = next_block(&engine, &engine.genesis_block());
-        let _ = engine.approve_block(block);
+        assert_eq!(
+            engine.approve_block(block.clone()),
+            Err(Error::BlockMissing(block.id))
+        );
     }
 
     #[test]
-    #[should_panic(expected = "can't vote for a block in the past")]
     // Ensure that approve_block cannot vote blocks in the past.
     fn approve_block_in_the_past() {
         let mut engine = init(vec![NodeId::new([0; 32])]);
@@ -718,10 +1172,13 @@ mod test {
         engine = engine.receive_block(block.clone()).unwrap();
         engine = update_leader_selection(&engine);
 
-        let (engine, _) = engine.approve_block(block.clone());
+        let (engine, _) = engine.approve_block(block.clone()).unwrap();
 
         // trying to approve the block again that was already voted.
-        let _ = engine.approve_block(block);
+        assert_eq!(
+            engine.approve_block(block),
+            Err(Error::AlreadyVoted(View(1)))
+        );
     }
 
     #[test]
@@ -744,6 +1201,7 @@ mod test {
                     high_qc: StandardQc {
                         view: View(0), // genesis
                         id: 0,
+                        signature: QcSignature::default(),
                     },
                     timeout_qc: None
                 }),
@@ -767,10 +1225,12 @@ mod test {
             StandardQc {
                 view: View::new(0), // genesis
                 id: 0,
+                signature: QcSignature::default(),
             },
             NodeId::new([0; 32]),
+            QcSignature::default(),
         );
-        engine = engine.receive_timeout_qc(timeout_qc.clone());
+        engine = engine.receive_timeout_qc(timeout_qc.clone()).unwrap();
         assert_eq!(&engine.local_high_qc, timeout_qc.high_qc());
         assert_eq!(engine.last_view_timeout_qc, Some(timeout_qc));
         assert_eq!(engine.current_view(), View(2));
@@ -792,10 +1252,12 @@ mod test {
             StandardQc {
                 view: View(0), // genesis
                 id: 0,
+                signature: QcSignature::default(),
             },
             NodeId::new([0; 32]),
+            QcSignature::default(),
         );
-        engine = engine.receive_timeout_qc(timeout_qc.clone());
+        engine = engine.receive_timeout_qc(timeout_qc.clone()).unwrap();
         assert_eq!(&engine.local_high_qc, timeout_qc.high_qc());
         assert_eq!(engine.last_view_timeout_qc, Some(timeout_qc));
         assert_eq!(engine.current_view(), View(2));
@@ -819,17 +1281,21 @@ mod test {
             StandardQc {
                 view: View(0), // genesis
                 id: 0,
+                signature: QcSignature::default(),
             },
             NodeId::new([0; 32]),
+            QcSignature::default(),
         );
-        engine = engine.receive_timeout_qc(timeout_qc.clone());
+        engine = engine.receive_timeout_qc(timeout_qc.clone()).unwrap();
         assert_eq!(&engine.local_high_qc, timeout_qc.high_qc());
         assert_eq!(engine.last_view_timeout_qc, Some(timeout_qc.clone()));
         assert_eq!(engine.current_view(), View(2));
         assert_eq!(engine.highest_voted_view, View(-1)); // didn't vote on anything yet
         engine = update_leader_selection(&engine);
 
-        let (engine, send) = engine.approve_new_view(timeout_qc.clone(), HashSet::new());
+        let (engine, send) = engine
+            .approve_new_view(timeout_qc.clone(), HashSet::new())
+            .unwrap();
         assert_eq!(&engine.high_qc(), timeout_qc.high_qc());
         assert_eq!(engine.current_view(), View(2)); // not changed
         assert_eq!(engine.highest_voted_view, View(2));
@@ -848,7 +1314,6 @@ mod test {
     }
 
     #[test]
-    #[should_panic(expected = "can't vote for a new view not bigger than the last timeout_qc")]
     fn approve_new_view_not_bigger_than_timeout_qc() {
         let mut engine = init(vec![NodeId::new([0; 32])]);
         let block = next_block(&engine, &engine.genesis_block());
@@ -861,10 +1326,12 @@ mod test {
             StandardQc {
                 view: View(0), // genesis
                 id: 0,
+                signature: QcSignature::default(),
             },
             NodeId::new([0; 32]),
+            QcSignature::default(),
         );
-        engine = engine.receive_timeout_qc(timeout_qc1.clone());
+        engine = engine.receive_timeout_qc(timeout_qc1.clone()).unwrap();
         assert_eq!(engine.last_view_timeout_qc, Some(timeout_qc1.clone()));
         engine = update_leader_selection(&engine);
 
@@ -874,14 +1341,22 @@ mod test {
             StandardQc {
                 view: View(0), // genesis
                 id: 0,
+                signature: QcSignature::default(),
             },
             NodeId::new([0; 32]),
+            QcSignature::default(),
         );
-        engine = engine.receive_timeout_qc(timeout_qc2.clone());
+        engine = engine.receive_timeout_qc(timeout_qc2.clone()).unwrap();
         assert_eq!(engine.last_view_timeout_qc, Some(timeout_qc2));
         engine = update_leader_selection(&engine);
 
         // we expect new_view(timeout_qc2), but...
-        let _ = engine.approve_new_view(timeout_qc1, HashSet::new());
+        assert_eq!(
+            engine.approve_new_view(timeout_qc1, HashSet::new()),
+            Err(Error::OldView {
+                view: View(2),
+                current_view: View(3)
+            })
+        );
     }
 }
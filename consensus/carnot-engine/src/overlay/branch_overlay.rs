@@ -123,8 +123,14 @@ where
         (committee_size * 2 / 3) + 1
     }
 
+    // The leader collects votes from the root committee and from its child committee
     fn leader_super_majority_threshold(&self, _id: NodeId) -> usize {
-        let committee_size = self.root_committee().len();
+        let children_size = if self.number_of_committees > 1 {
+            self.membership_committees[&1].len()
+        } else {
+            0
+        };
+        let committee_size = self.root_committee().len() + children_size;
         (committee_size * 2 / 3) + 1
     }
 
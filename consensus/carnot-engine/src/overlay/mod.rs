@@ -49,6 +49,11 @@ pub trait LeaderSelection: Clone {
 
 pub trait CommitteeMembership: Clone {
     fn reshape_committees(&self, nodes: &mut [NodeId]);
+    /// Nodes to build the committees from, for memberships keeping track of them.
+    /// They take precedence over the nodes in the overlay settings.
+    fn nodes(&self) -> Option<&[NodeId]> {
+        None
+    }
 }
 
 #[cfg(test)]
@@ -53,6 +53,9 @@ where
             super_majority_threshold,
         } = settings;
 
+        if let Some(members) = committee_membership.nodes() {
+            nodes = members.to_vec();
+        }
         committee_membership.reshape_committees(&mut nodes);
         let carnot_tree = Tree::new(&nodes, number_of_committees);
 
@@ -152,25 +155,17 @@ where
             .expect("node is not part of any committee")
     }
 
-    // TODO: Carnot node in sim does not send votes to the next leader from the child committee of
-    // root committee yet. *For now* leader super majority threshold should be calculated only from
-    // the number of root committee nodes. The code will be reverted once vote sending from
-    // child committee of root committee is added to Carnot node.
+    // The leader collects votes from the root committee and from the children of the root committee
     fn leader_super_majority_threshold(&self, _id: NodeId) -> usize {
-        // let root_committee = &self.carnot_tree.inner_committees[0];
-        // let children = self.carnot_tree.child_committees(root_committee);
-        // let children_size = children.0.map_or(0, |c| {
-        //     self.carnot_tree
-        //         .committee_by_committee_id(c)
-        //         .map_or(0, |c| c.len())
-        // }) + children.1.map_or(0, |c| {
-        //     self.carnot_tree
-        //         .committee_by_committee_id(c)
-        //         .map_or(0, |c| c.len())
-        // });
-        // let root_size = self.root_committee().len();
-        // let committee_size = root_size + children_size;
-        apply_threshold(self.root_committee().len(), self.threshold)
+        let root_committee = &self.carnot_tree.inner_committees[0];
+        let (left, right) = self.carnot_tree.child_committees(root_committee);
+        let children_size: usize = [left, right]
+            .into_iter()
+            .flatten()
+            .filter_map(|c| self.carnot_tree.committee_by_committee_id(c))
+            .map(Committee::len)
+            .sum();
+        apply_threshold(self.root_committee().len() + children_size, self.threshold)
     }
 
     fn update_leader_selection<F, E>(&self, f: F) -> Result<Self, E>
@@ -364,9 +359,46 @@ mod tests {
             super_majority_threshold: None,
         });
 
+        // 4 root committee members and 3 members in each of its two children
         assert_eq!(
             overlay.leader_super_majority_threshold(NodeId::new([0; 32])),
-            3
+            7
         );
     }
+
+    #[derive(Clone)]
+    struct FixedNodes(Vec<NodeId>);
+
+    impl CommitteeMembership for FixedNodes {
+        fn reshape_committees(&self, _nodes: &mut [NodeId]) {}
+
+        fn nodes(&self) -> Option<&[NodeId]> {
+            Some(&self.0)
+        }
+    }
+
+    #[test]
+    fn test_update_committees_with_membership_nodes() {
+        let nodes: Vec<_> = (0..10).map(|i| NodeId::new([i as u8; 32])).collect();
+        let overlay = TreeOverlay::new(TreeOverlaySettings {
+            nodes: nodes.clone(),
+            current_leader: nodes[0],
+            number_of_committees: 3,
+            leader: RoundRobin::new(),
+            committee_membership: FixedNodes(nodes.clone()),
+            super_majority_threshold: None,
+        });
+        assert_eq!(overlay.nodes, nodes);
+
+        // two nodes leave and three new ones join
+        let new_nodes: Vec<_> = (2..13).map(|i| NodeId::new([i as u8; 32])).collect();
+        let overlay = overlay
+            .update_committees(|_| Ok::<_, ()>(FixedNodes(new_nodes.clone())))
+            .unwrap();
+
+        assert_eq!(overlay.nodes, new_nodes);
+        assert!(!overlay.is_member_of_root_committee(nodes[0]));
+        assert!(overlay.is_member_of_root_committee(new_nodes[0]));
+        assert_eq!(overlay.leader_super_majority_threshold(new_nodes[0]), 8);
+    }
 }
@@ -136,7 +136,6 @@ impl Tree {
             .and_then(|idx| self.committee_by_committee_idx(idx))
     }
 
-    #[allow(dead_code)]
     pub(super) fn committee_by_committee_id(
         &self,
         committee_id: &CommitteeId,
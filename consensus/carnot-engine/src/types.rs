@@ -6,8 +6,15 @@ use serde::{Deserialize, Serialize};
 
 mod committee;
 pub use committee::{Committee, CommitteeId};
+mod equivocation;
+pub use equivocation::{Equivocation, EquivocationKind, SignedBlock};
 mod node_id;
 pub use node_id::NodeId;
+mod qc_signature;
+pub use qc_signature::{
+    new_view_message, proposal_message, timeout_message, vote_message, QcSignature,
+    QcSignatureError, SignableBlockId,
+};
 mod view;
 pub use view::View;
 
@@ -66,10 +73,17 @@ pub struct TimeoutQc<Id> {
     view: View,
     high_qc: StandardQc<Id>,
     sender: NodeId,
+    /// Signatures of the timeouts, see [`timeout_message`]
+    signature: QcSignature,
 }
 
 impl<Id> TimeoutQc<Id> {
-    pub fn new(view: View, high_qc: StandardQc<Id>, sender: NodeId) -> Self {
+    pub fn new(
+        view: View,
+        high_qc: StandardQc<Id>,
+        sender: NodeId,
+        signature: QcSignature,
+    ) -> Self {
         assert!(
             view >= high_qc.view,
             "timeout_qc.view:{} shouldn't be lower than timeout_qc.high_qc.view:{}",
@@ -81,6 +95,7 @@ impl<Id> TimeoutQc<Id> {
             view,
             high_qc,
             sender,
+            signature,
         }
     }
 
@@ -95,6 +110,18 @@ impl<Id> TimeoutQc<Id> {
     pub fn sender(&self) -> NodeId {
         self.sender
     }
+
+    pub fn signature(&self) -> &QcSignature {
+        &self.signature
+    }
+
+    /// Whether at least `threshold` members of `committee` timed out in the view.
+    /// The high qc carries its own signatures and is not checked here.
+    pub fn verify(&self, committee: &Committee, threshold: usize) -> bool {
+        self.signature.verify(committee, threshold, |sender| {
+            timeout_message(sender, self.view)
+        })
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -131,6 +158,20 @@ impl<Id: Copy> Block<Id> {
     }
 }
 
+/// What a node needs to resume consensus after a restart without going back on its votes,
+/// see [`crate::Carnot::checkpoint`]
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Checkpoint<Id> {
+    pub current_view: View,
+    pub highest_voted_view: View,
+    pub high_qc: StandardQc<Id>,
+    pub last_view_timeout_qc: Option<TimeoutQc<Id>>,
+    pub latest_committed_block: Block<Id>,
+    /// Safe blocks after the latest committed one, in view order
+    pub pending_blocks: Vec<Block<Id>>,
+}
+
 /// Possible output events.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Send<Id> {
@@ -144,11 +185,27 @@ pub struct Send<Id> {
 pub struct StandardQc<Id> {
     pub view: View,
     pub id: Id,
+    /// Signatures of the votes for the block, see [`vote_message`]
+    pub signature: QcSignature,
 }
 
 impl<Id> StandardQc<Id> {
+    /// The genesis block is not voted for, so its qc has no signatures
     pub fn genesis(id: Id) -> Self {
-        Self { view: View(-1), id }
+        Self {
+            view: View(-1),
+            id,
+            signature: QcSignature::default(),
+        }
+    }
+}
+
+impl<Id: SignableBlockId> StandardQc<Id> {
+    /// Whether at least `threshold` members of `committee` voted for the block
+    pub fn verify(&self, committee: &Committee, threshold: usize) -> bool {
+        self.signature.verify(committee, threshold, |voter| {
+            vote_message(voter, self.view, &self.id)
+        })
     }
 }
 
@@ -158,6 +215,18 @@ impl<Id> StandardQc<Id> {
 pub struct AggregateQc<Id> {
     pub high_qc: StandardQc<Id>,
     pub view: View,
+    /// Signatures of the new view votes, see [`new_view_message`]
+    pub signature: QcSignature,
+}
+
+impl<Id> AggregateQc<Id> {
+    /// Whether at least `threshold` members of `committee` voted to move to the view.
+    /// The high qc carries its own signatures and is not checked here.
+    pub fn verify(&self, committee: &Committee, threshold: usize) -> bool {
+        self.signature.verify(committee, threshold, |voter| {
+            new_view_message(voter, self.view)
+        })
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -203,6 +272,7 @@ mod test {
         let standard_qc = StandardQc {
             view: View(10),
             id: 0,
+            signature: QcSignature::default(),
         };
         let qc = Qc::Standard(standard_qc.clone());
         assert_eq!(qc.view(), View(10));
@@ -217,7 +287,9 @@ mod test {
             high_qc: StandardQc {
                 view: View(10),
                 id: 0,
+                signature: QcSignature::default(),
             },
+            signature: QcSignature::default(),
         };
         let qc = Qc::Aggregated(aggregated_qc.clone());
         assert_eq!(qc.view(), View(20));
@@ -232,8 +304,10 @@ mod test {
             StandardQc {
                 view: View(1),
                 id: 0,
+                signature: QcSignature::default(),
             },
             NodeId::new([0; 32]),
+            QcSignature::default(),
         );
         assert_eq!(timeout_qc.view(), View(2));
         assert_eq!(timeout_qc.high_qc().view, View(1));
@@ -245,8 +319,10 @@ mod test {
             StandardQc {
                 view: View(2),
                 id: 0,
+                signature: QcSignature::default(),
             },
             NodeId::new([0; 32]),
+            QcSignature::default(),
         );
         assert_eq!(timeout_qc.view(), View(2));
         assert_eq!(timeout_qc.high_qc().view, View(2));
@@ -264,8 +340,10 @@ mod test {
             StandardQc {
                 view: View(2),
                 id: 0,
+                signature: QcSignature::default(),
             },
             NodeId::new([0; 32]),
+            QcSignature::default(),
         );
     }
 }
@@ -0,0 +1,140 @@
+// std
+// crates
+use bls_signatures::{PublicKey, Serialize as _, Signature};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+// internal
+use crate::{proposal_message, vote_message, NodeId, SignableBlockId, View};
+
+/// Kind of message signed twice by an equivocating node
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum EquivocationKind {
+    /// The leader of a view proposed two different blocks
+    Proposal,
+    /// A node voted for two different blocks in the same view
+    Vote,
+}
+
+/// A block signed by a node, either as a proposal or as a vote depending on the
+/// [`EquivocationKind`]. Keys and signatures are kept in their compressed form.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SignedBlock<Id> {
+    pub block: Id,
+    pub public_key: Box<[u8]>,
+    pub signature: Box<[u8]>,
+}
+
+/// Evidence that `offender` signed two conflicting messages in `view`.
+/// It can be checked by anyone, without trusting whoever reported it.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Equivocation<Id> {
+    pub kind: EquivocationKind,
+    pub offender: NodeId,
+    pub view: View,
+    pub first: SignedBlock<Id>,
+    pub second: SignedBlock<Id>,
+}
+
+impl<Id: SignableBlockId + Eq> Equivocation<Id> {
+    /// Whether both messages were signed by the offender and are for different blocks
+    pub fn verify(&self) -> bool {
+        self.first.block != self.second.block
+            && [&self.first, &self.second]
+                .into_iter()
+                .all(|signed| self.verify_signed(signed))
+    }
+
+    fn verify_signed(&self, signed: &SignedBlock<Id>) -> bool {
+        let Ok(public_key) = PublicKey::from_bytes(&signed.public_key) else {
+            return false;
+        };
+        let Ok(signature) = Signature::from_bytes(&signed.signature) else {
+            return false;
+        };
+        let message = match self.kind {
+            EquivocationKind::Proposal => proposal_message(self.offender, self.view, &signed.block),
+            EquivocationKind::Vote => vote_message(self.offender, self.view, &signed.block),
+        };
+        NodeId::from_public_key(&public_key) == self.offender
+            && public_key.verify(signature, message)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bls_signatures::PrivateKey;
+
+    fn sign(
+        key: &PrivateKey,
+        kind: EquivocationKind,
+        view: View,
+        block: [u8; 32],
+    ) -> SignedBlock<[u8; 32]> {
+        let signer = NodeId::from_public_key(&key.public_key());
+        let message = match kind {
+            EquivocationKind::Proposal => proposal_message(signer, view, &block),
+            EquivocationKind::Vote => vote_message(signer, view, &block),
+        };
+        SignedBlock {
+            block,
+            public_key: key.public_key().as_bytes().into(),
+            signature: key.sign(message).as_bytes().into(),
+        }
+    }
+
+    fn equivocation(
+        key: &PrivateKey,
+        kind: EquivocationKind,
+        blocks: [[u8; 32]; 2],
+    ) -> Equivocation<[u8; 32]> {
+        let view = View(3);
+        Equivocation {
+            kind,
+            offender: NodeId::from_public_key(&key.public_key()),
+            view,
+            first: sign(key, kind, view, blocks[0]),
+            second: sign(key, kind, view, blocks[1]),
+        }
+    }
+
+    #[test]
+    fn conflicting_messages() {
+        let key = PrivateKey::new([1; 32]);
+        for kind in [EquivocationKind::Proposal, EquivocationKind::Vote] {
+            assert!(equivocation(&key, kind, [[1; 32], [2; 32]]).verify());
+            // the same block twice is not an offence
+            assert!(!equivocation(&key, kind, [[1; 32], [1; 32]]).verify());
+        }
+    }
+
+    #[test]
+    fn evidence_must_match_the_offender_and_the_message() {
+        let key = PrivateKey::new([1; 32]);
+        let other = PrivateKey::new([2; 32]);
+
+        let mut evidence = equivocation(&key, EquivocationKind::Vote, [[1; 32], [2; 32]]);
+        evidence.offender = NodeId::from_public_key(&other.public_key());
+        assert!(!evidence.verify());
+
+        let mut evidence = equivocation(&key, EquivocationKind::Vote, [[1; 32], [2; 32]]);
+        evidence.view = View(4);
+        assert!(!evidence.verify());
+
+        // a vote is not a proposal
+        let mut evidence = equivocation(&key, EquivocationKind::Vote, [[1; 32], [2; 32]]);
+        evidence.kind = EquivocationKind::Proposal;
+        assert!(!evidence.verify());
+
+        // both messages must be signed by the offender
+        let mut evidence = equivocation(&key, EquivocationKind::Vote, [[1; 32], [2; 32]]);
+        evidence.second = sign(&other, EquivocationKind::Vote, View(3), [2; 32]);
+        assert!(!evidence.verify());
+    }
+}
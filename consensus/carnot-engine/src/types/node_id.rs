@@ -1,3 +1,8 @@
+use blake2::{digest::typenum::U32, Blake2b, Digest};
+use bls_signatures::{PublicKey, Serialize as _};
+
+const NODE_ID_TAG: &[u8] = b"carnot-node-id";
+
 #[derive(Clone, Copy, Default, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
 #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct NodeId(pub(crate) [u8; 32]);
@@ -24,6 +29,17 @@ impl NodeId {
         Self(val)
     }
 
+    /// Id of the node owning the given consensus key, so that messages signed with the key can
+    /// be attributed to the node without any additional registry.
+    pub fn from_public_key(public_key: &PublicKey) -> Self {
+        Self(
+            Blake2b::<U32>::new_with_prefix(NODE_ID_TAG)
+                .chain_update(public_key.as_bytes())
+                .finalize()
+                .into(),
+        )
+    }
+
     /// Returns a random node id
     #[cfg(any(test, feature = "simulation"))]
     pub fn random<R: rand::Rng>(rng: &mut R) -> Self {
@@ -0,0 +1,267 @@
+// std
+use std::collections::BTreeMap;
+// crates
+use bls_signatures::{PublicKey, Serialize as _, Signature};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+// internal
+use crate::{Committee, NodeId, View};
+
+const VOTE_TAG: &[u8] = b"carnot-vote";
+const NEW_VIEW_TAG: &[u8] = b"carnot-new-view";
+const PROPOSAL_TAG: &[u8] = b"carnot-proposal";
+const TIMEOUT_TAG: &[u8] = b"carnot-timeout";
+
+/// Block ids that can be part of the messages signed by the nodes
+pub trait SignableBlockId {
+    fn signable_bytes(&self) -> Vec<u8>;
+}
+
+impl SignableBlockId for [u8; 32] {
+    fn signable_bytes(&self) -> Vec<u8> {
+        self.to_vec()
+    }
+}
+
+/// Message signed by `voter` when voting for `block` in `view`.
+/// Every voter signs a different message, as required to safely aggregate the signatures.
+pub fn vote_message<Id: SignableBlockId>(voter: NodeId, view: View, block: &Id) -> Vec<u8> {
+    [
+        VOTE_TAG,
+        &voter.0,
+        &view.0.to_be_bytes(),
+        &block.signable_bytes(),
+    ]
+    .concat()
+}
+
+/// Message signed by `leader` when proposing `block` in `view`
+pub fn proposal_message<Id: SignableBlockId>(leader: NodeId, view: View, block: &Id) -> Vec<u8> {
+    [
+        PROPOSAL_TAG,
+        &leader.0,
+        &view.0.to_be_bytes(),
+        &block.signable_bytes(),
+    ]
+    .concat()
+}
+
+/// Message signed by `voter` when voting to move to `view` after a timeout
+pub fn new_view_message(voter: NodeId, view: View) -> Vec<u8> {
+    [NEW_VIEW_TAG, &voter.0, &view.0.to_be_bytes()].concat()
+}
+
+/// Message signed by `sender` when timing out in `view`.
+/// Signatures are aggregated into the timeout qc, which only keeps the highest of the high qcs
+/// of the senders, so they don't cover them. High qcs carry their own signatures.
+pub fn timeout_message(sender: NodeId, view: View) -> Vec<u8> {
+    [TIMEOUT_TAG, &sender.0, &view.0.to_be_bytes()].concat()
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum QcSignatureError {
+    #[error("{0} is not a member of the committee")]
+    NotACommitteeMember(NodeId),
+    #[error("Invalid signature by {0}")]
+    InvalidSignature(NodeId),
+    #[error("No signatures to aggregate")]
+    NoSignatures,
+}
+
+/// Aggregated BLS signature of the members of a committee who voted for a QC.
+///
+/// Signers are identified by a bitmap over the members of the committee, in the order of their
+/// ids, and their public keys are included so that the signature can be checked by anyone
+/// knowing the committee. All fields are kept in their compressed form.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct QcSignature {
+    signers: Box<[u8]>,
+    public_keys: Box<[Box<[u8]>]>,
+    signature: Box<[u8]>,
+}
+
+impl QcSignature {
+    /// Aggregate the signatures of the members of `committee`, given as
+    /// `(signer, compressed public key, compressed signature)`.
+    /// Public keys are only checked when verifying the aggregated signature.
+    pub fn aggregate<'a>(
+        committee: &Committee,
+        signatures: impl IntoIterator<Item = (NodeId, &'a [u8], &'a [u8])>,
+    ) -> Result<Self, QcSignatureError> {
+        let mut signatures = signatures
+            .into_iter()
+            .map(|(signer, public_key, signature)| (signer, (public_key, signature)))
+            .collect::<BTreeMap<_, _>>();
+        let mut signers = vec![0u8; committee.len().div_ceil(8)];
+        let mut public_keys = Vec::new();
+        let mut aggregated = Vec::new();
+        for (i, member) in committee.iter().enumerate() {
+            let Some((public_key, signature)) = signatures.remove(member) else {
+                continue;
+            };
+            let signature = Signature::from_bytes(signature)
+                .map_err(|_| QcSignatureError::InvalidSignature(*member))?;
+            signers[i / 8] |= 1 << (i % 8);
+            public_keys.push(public_key.into());
+            aggregated.push(signature);
+        }
+        if let Some(signer) = signatures.into_keys().next() {
+            return Err(QcSignatureError::NotACommitteeMember(signer));
+        }
+        let signature =
+            bls_signatures::aggregate(&aggregated).map_err(|_| QcSignatureError::NoSignatures)?;
+        Ok(Self {
+            signers: signers.into(),
+            public_keys: public_keys.into(),
+            signature: signature.as_bytes().into(),
+        })
+    }
+
+    /// Members of `committee` who signed, or `None` if the bitmap does not match the committee
+    pub fn signers(&self, committee: &Committee) -> Option<Vec<NodeId>> {
+        if self.signers.len() != committee.len().div_ceil(8) {
+            return None;
+        }
+        let signers = committee
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.signers[i / 8] & (1 << (i % 8)) != 0)
+            .map(|(_, member)| *member)
+            .collect::<Vec<_>>();
+        // bits past the end of the committee must not be set
+        let set_bits: usize = self.signers.iter().map(|b| b.count_ones() as usize).sum();
+        (signers.len() == set_bits).then_some(signers)
+    }
+
+    /// Whether at least `threshold` members of `committee` signed, each one the message
+    /// returned by `message` for it
+    pub fn verify(
+        &self,
+        committee: &Committee,
+        threshold: usize,
+        message: impl Fn(NodeId) -> Vec<u8>,
+    ) -> bool {
+        let Some(signers) = self.signers(committee) else {
+            return false;
+        };
+        if signers.len() < threshold || signers.len() != self.public_keys.len() {
+            return false;
+        }
+        let Ok(public_keys) = self
+            .public_keys
+            .iter()
+            .map(|public_key| PublicKey::from_bytes(public_key))
+            .collect::<Result<Vec<_>, _>>()
+        else {
+            return false;
+        };
+        if signers
+            .iter()
+            .zip(&public_keys)
+            .any(|(signer, public_key)| NodeId::from_public_key(public_key) != *signer)
+        {
+            return false;
+        }
+        let Ok(signature) = Signature::from_bytes(&self.signature) else {
+            return false;
+        };
+        let messages = signers.into_iter().map(message).collect::<Vec<_>>();
+        let messages = messages.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        bls_signatures::verify_messages(&signature, &messages, &public_keys)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bls_signatures::PrivateKey;
+
+    fn keys(n: u8) -> Vec<PrivateKey> {
+        (1..=n).map(|i| PrivateKey::new([i; 32])).collect()
+    }
+
+    fn committee(keys: &[PrivateKey]) -> Committee {
+        keys.iter()
+            .map(|key| NodeId::from_public_key(&key.public_key()))
+            .collect()
+    }
+
+    fn sign(keys: &[PrivateKey], view: View, block: [u8; 32]) -> Vec<(NodeId, Vec<u8>, Vec<u8>)> {
+        keys.iter()
+            .map(|key| {
+                let voter = NodeId::from_public_key(&key.public_key());
+                let signature = key.sign(vote_message(voter, view, &block));
+                (voter, key.public_key().as_bytes(), signature.as_bytes())
+            })
+            .collect()
+    }
+
+    fn aggregate(
+        committee: &Committee,
+        signatures: &[(NodeId, Vec<u8>, Vec<u8>)],
+    ) -> Result<QcSignature, QcSignatureError> {
+        QcSignature::aggregate(
+            committee,
+            signatures
+                .iter()
+                .map(|(voter, public_key, signature)| (*voter, &public_key[..], &signature[..])),
+        )
+    }
+
+    #[test]
+    fn aggregated_signature() {
+        let keys = keys(10);
+        let committee = committee(&keys);
+        let (view, block) = (View(3), [7; 32]);
+        let signature = aggregate(&committee, &sign(&keys[..7], view, block)).unwrap();
+        let message = |voter| vote_message(voter, view, &block);
+
+        assert_eq!(signature.signers(&committee).unwrap().len(), 7);
+        assert!(signature.verify(&committee, 7, message));
+        assert!(!signature.verify(&committee, 8, message));
+        assert!(!signature.verify(&committee, 7, |voter| vote_message(voter, View(4), &block)));
+        assert!(!signature.verify(&committee, 7, |voter| vote_message(voter, view, &[8; 32])));
+        // the bitmap is over another committee
+        assert!(!signature.verify(&self::committee(&keys[..7]), 7, message));
+        assert!(!QcSignature::default().verify(&committee, 0, message));
+    }
+
+    #[test]
+    fn signers_must_match_public_keys() {
+        let keys = keys(4);
+        let committee = committee(&keys);
+        let (view, block) = (View(1), [1; 32]);
+        let mut signatures = sign(&keys[..3], view, block);
+        // a valid signature, attributed to another member
+        signatures[0].0 = NodeId::from_public_key(&keys[3].public_key());
+        let signature = aggregate(&committee, &signatures).unwrap();
+
+        assert!(!signature.verify(&committee, 3, |voter| vote_message(voter, view, &block)));
+    }
+
+    #[test]
+    fn aggregate_errors() {
+        let keys = keys(4);
+        let committee = committee(&keys);
+        let outsider = PrivateKey::new([42; 32]);
+
+        assert_eq!(
+            aggregate(&committee, &[]),
+            Err(QcSignatureError::NoSignatures)
+        );
+        let signatures = sign(&[outsider], View(1), [1; 32]);
+        assert_eq!(
+            aggregate(&committee, &signatures),
+            Err(QcSignatureError::NotACommitteeMember(signatures[0].0))
+        );
+        let mut signatures = sign(&keys, View(1), [1; 32]);
+        signatures[2].2 = vec![0; 3];
+        assert_eq!(
+            aggregate(&committee, &signatures),
+            Err(QcSignatureError::InvalidSignature(signatures[2].0))
+        );
+    }
+}
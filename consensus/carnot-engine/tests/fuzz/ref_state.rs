@@ -1,6 +1,6 @@
 use std::collections::{BTreeMap, HashSet};
 
-use carnot_engine::{LeaderProof, NodeId, View};
+use carnot_engine::{LeaderProof, NodeId, QcSignature, View};
 use proptest::prelude::*;
 use proptest::strategy::BoxedStrategy;
 use proptest_state_machine::ReferenceStateMachine;
@@ -271,8 +271,10 @@ impl RefState {
                                 StandardQc {
                                     view: block.view,
                                     id: block.id,
+                                    signature: QcSignature::default(),
                                 },
                                 SENDER,
+                                QcSignature::default(),
                             ))
                         })
                 })
@@ -298,6 +300,7 @@ impl RefState {
                         view,
                         entry.high_qc().unwrap(),
                         SENDER,
+                        QcSignature::default(),
                     ))
                 })
                 .boxed()
@@ -337,6 +340,7 @@ impl RefState {
             parent_qc: Qc::Aggregated(AggregateQc {
                 high_qc: self.high_qc(),
                 view: current_view,
+                signature: QcSignature::default(),
             }),
             leader_proof: LEADER_PROOF.clone(),
         }))
@@ -406,6 +410,7 @@ impl RefState {
             parent_qc: Qc::Standard(StandardQc {
                 view: parent.view,
                 id: parent.id,
+                signature: QcSignature::default(),
             }),
             leader_proof: LEADER_PROOF.clone(),
         }
@@ -1,4 +1,4 @@
-use std::{collections::HashSet, panic};
+use std::collections::HashSet;
 
 use carnot_engine::overlay::FreezeMembership;
 use carnot_engine::{
@@ -76,20 +76,28 @@ impl StateMachineTest for ConsensusEngineTest {
                 ConsensusEngineTest { engine }
             }
             Transition::ReceiveUnsafeBlock(block) => {
-                let result = panic::catch_unwind(|| state.engine.receive_block(block));
-                assert!(result.is_err() || result.unwrap().is_err());
+                let current_view = state.engine.current_view();
+                assert_eq!(
+                    state.engine.receive_block(block.clone()),
+                    Err(Error::OldView {
+                        view: block.view,
+                        current_view
+                    })
+                );
 
                 state
             }
             Transition::ApproveBlock(block) => {
-                let (engine, _) = state.engine.approve_block(block.clone());
+                let (engine, _) = state.engine.approve_block(block.clone()).unwrap();
                 assert_eq!(engine.highest_voted_view(), block.view);
 
                 ConsensusEngineTest { engine }
             }
             Transition::ApprovePastBlock(block) => {
-                let result = panic::catch_unwind(|| state.engine.approve_block(block));
-                assert!(result.is_err());
+                assert_eq!(
+                    state.engine.approve_block(block.clone()),
+                    Err(Error::AlreadyVoted(block.view))
+                );
 
                 state
             }
@@ -100,23 +108,28 @@ impl StateMachineTest for ConsensusEngineTest {
                 ConsensusEngineTest { engine }
             }
             Transition::ReceiveTimeoutQcForRecentView(timeout_qc) => {
-                let engine = state.engine.receive_timeout_qc(timeout_qc.clone());
+                let engine = state.engine.receive_timeout_qc(timeout_qc.clone()).unwrap();
                 assert_eq!(engine.current_view(), RefState::new_view_from(&timeout_qc));
 
                 ConsensusEngineTest { engine }
             }
             Transition::ReceiveTimeoutQcForOldView(timeout_qc) => {
-                #[allow(clippy::redundant_clone)]
-                let prev_engine = state.engine.clone();
-                let engine = state.engine.receive_timeout_qc(timeout_qc);
-
-                // Check that the engine state didn't change.
-                assert_eq!(engine, prev_engine);
+                let current_view = state.engine.current_view();
+                assert_eq!(
+                    state.engine.receive_timeout_qc(timeout_qc.clone()),
+                    Err(Error::OldView {
+                        view: timeout_qc.view(),
+                        current_view
+                    })
+                );
 
-                ConsensusEngineTest { engine }
+                state
             }
             Transition::ApproveNewViewWithLatestTimeoutQc(timeout_qc, new_views) => {
-                let (engine, _) = state.engine.approve_new_view(timeout_qc.clone(), new_views);
+                let (engine, _) = state
+                    .engine
+                    .approve_new_view(timeout_qc.clone(), new_views)
+                    .unwrap();
                 assert_eq!(
                     engine.highest_voted_view(),
                     RefState::new_view_from(&timeout_qc)
@@ -19,9 +19,13 @@ pub struct Cryptarchia<Id> {
 pub struct Branches<Id> {
     branches: HashMap<Id, Branch<Id>>,
     tips: HashSet<Id>,
+    // block of the local chain forks are not allowed to diverge before, either because they
+    // have been pruned or because its ancestors are not tracked
+    root: Branch<Id>,
 }
 
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Branch<Id> {
     id: Id,
     parent: Id,
@@ -30,23 +34,46 @@ pub struct Branch<Id> {
     length: u64,
 }
 
+impl<Id: Copy> Branch<Id> {
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    pub fn parent(&self) -> Id {
+        self.parent
+    }
+
+    pub fn slot(&self) -> Slot {
+        self.slot
+    }
+
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+}
+
 impl<Id> Branches<Id>
 where
     Id: Eq + std::hash::Hash + Copy,
 {
     pub fn from_genesis(genesis: Id) -> Self {
-        let mut branches = HashMap::new();
-        branches.insert(
-            genesis,
-            Branch {
-                id: genesis,
-                parent: genesis,
-                slot: 0.into(),
-                length: 0,
-            },
-        );
-        let tips = HashSet::from([genesis]);
-        Self { branches, tips }
+        Self::from_root(Branch {
+            id: genesis,
+            parent: genesis,
+            slot: 0.into(),
+            length: 0,
+        })
+    }
+
+    // Start from an arbitrary block, whose ancestors are not tracked
+    fn from_root(root: Branch<Id>) -> Self {
+        let tips = HashSet::from([root.id]);
+        let branches = HashMap::from([(root.id, root.clone())]);
+        Self {
+            branches,
+            tips,
+            root,
+        }
     }
 
     #[must_use = "this returns the result of the operation, without modifying the original"]
@@ -55,11 +82,17 @@ where
         let mut tips = self.tips.clone();
         // if the parent was the head of a branch, remove it as it has been superseded by the new header
         tips.remove(&parent);
-        let length = branches
-            .get(&parent)
-            .ok_or(Error::ParentMissing(parent))?
-            .length
-            + 1;
+        let length = match branches.get(&parent) {
+            // the parent is part of the local chain, but the header would start a fork
+            // diverging before the root
+            Some(parent) if parent.length < self.root.length => {
+                return Err(Error::ParentPruned(parent.id))
+            }
+            Some(parent) => parent.length + 1,
+            // the parent comes before the root, so it was pruned or is not tracked
+            None if slot <= self.root.slot => return Err(Error::ParentPruned(parent)),
+            None => return Err(Error::ParentMissing(parent)),
+        };
         tips.insert(header);
         branches.insert(
             header,
@@ -71,7 +104,11 @@ where
             },
         );
 
-        Ok(Self { branches, tips })
+        Ok(Self {
+            branches,
+            tips,
+            root: self.root.clone(),
+        })
     }
 
     pub fn branches(&self) -> Vec<Branch<Id>> {
@@ -105,6 +142,54 @@ where
         self.branches.get(id)
     }
 
+    pub fn len(&self) -> usize {
+        self.branches.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.branches.is_empty()
+    }
+
+    // Remove all the forks that diverged from `chain` more than `depth` blocks ago,
+    // returning the ids of the pruned blocks. Blocks in `chain` are never removed.
+    // Headers starting new forks that deep are rejected from now on.
+    fn prune_forks(&mut self, chain: &Branch<Id>, depth: u64) -> Vec<Id> {
+        let Some(cutoff) = chain.length.checked_sub(depth) else {
+            return vec![];
+        };
+        if cutoff > self.root.length {
+            let mut root = chain;
+            while root.length > cutoff {
+                root = &self.branches[&root.parent];
+            }
+            self.root = root.clone();
+        }
+        // compute all the common ancestors upfront, as forks might share a common prefix
+        // that will be removed while pruning another fork
+        let stale_forks = self
+            .tips
+            .iter()
+            .filter(|tip| **tip != chain.id)
+            .map(|tip| (*tip, self.lca(&self.branches[tip], chain).id))
+            .filter(|(_, lca)| self.branches[lca].length < cutoff)
+            .collect::<Vec<_>>();
+
+        let mut pruned = Vec::new();
+        for (tip, lca) in stale_forks {
+            self.tips.remove(&tip);
+            let mut current = tip;
+            while current != lca {
+                let Some(branch) = self.branches.remove(&current) else {
+                    // already removed as part of another fork
+                    break;
+                };
+                pruned.push(current);
+                current = branch.parent;
+            }
+        }
+        pruned
+    }
+
     // Walk back the chain until the target slot
     fn walk_back_before(&self, branch: &Branch<Id>, slot: Slot) -> Branch<Id> {
         let mut current = branch;
@@ -119,6 +204,8 @@ where
 pub enum Error<Id> {
     #[error("Parent block: {0:?} is not know to this node")]
     ParentMissing(Id),
+    #[error("Parent block: {0:?} was pruned or precedes the oldest block known to this node")]
+    ParentPruned(Id),
     #[error("Orphan proof has was not found in the ledger: {0:?}, can't import it")]
     OrphanMissing(Id),
 }
@@ -141,6 +228,18 @@ where
         }
     }
 
+    // Restore the engine from a block that was immutable when it was persisted, e.g. after a restart.
+    // The block becomes the root of the tree of known blocks, and blocks building on its ancestors
+    // will be rejected as they are deeper than k.
+    pub fn from_lib(lib: Branch<Id>, genesis: Id, config: Config) -> Self {
+        Self {
+            branches: Branches::from_root(lib.clone()),
+            local_chain: lib,
+            config,
+            genesis,
+        }
+    }
+
     #[must_use = "this returns the result of the operation, without modifying the original"]
     pub fn receive_block(&self, id: Id, parent: Id, slot: Slot) -> Result<Self, Error<Id>> {
         let mut new: Self = self.clone();
@@ -161,15 +260,58 @@ where
     }
 
     // prune all states deeper than 'depth' with regard to the current
-    // local chain except for states belonging to the local chain
-    pub fn prune_forks(&mut self, _depth: u64) {
-        todo!()
+    // local chain except for states belonging to the local chain.
+    // Returns the ids of the pruned blocks so that dependent state (e.g. the ledger)
+    // can be pruned as well
+    pub fn prune_forks(&mut self, depth: u64) -> Vec<Id> {
+        let local_chain = self.local_chain.clone();
+        self.branches.prune_forks(&local_chain, depth)
+    }
+
+    // The last immutable block (LIB) of the local chain, i.e. the block 'k' blocks deep.
+    // Forks diverging before this block will never be selected by the fork choice rule
+    // once the node is synced and can be safely pruned.
+    pub fn lib(&self) -> Id {
+        self.lib_branch().id
+    }
+
+    pub fn lib_branch(&self) -> Branch<Id> {
+        let k = self.config.security_param as u64;
+        let mut current = &self.local_chain;
+        while self.local_chain.length - current.length < k && current.id != self.genesis {
+            // the engine might have been restored from a block other than genesis
+            let Some(parent) = self.branches.get(&current.parent) else {
+                break;
+            };
+            current = parent;
+        }
+        current.clone()
     }
 
     pub fn genesis(&self) -> Id {
         self.genesis
     }
 
+    // Blocks that are not part of the local chain and have not been pruned yet,
+    // sorted by slot. Leader proofs in these blocks can be imported in new blocks.
+    pub fn abandoned_blocks(&self) -> Vec<Branch<Id>> {
+        let mut blocks = HashMap::new();
+        for tip in self.branches.branches() {
+            let lca = self.branches.lca(&tip, &self.local_chain);
+            let mut current = &tip;
+            while current.id != lca.id {
+                // forks might share a common prefix
+                if blocks.insert(current.id, current.clone()).is_some() {
+                    break;
+                }
+                current = &self.branches.branches[&current.parent];
+            }
+        }
+        let mut blocks = blocks.into_values().collect::<Vec<_>>();
+        blocks.sort_by_key(|block| block.slot);
+        blocks
+    }
+
     pub fn branches(&self) -> &Branches<Id> {
         &self.branches
     }
@@ -297,6 +439,199 @@ pub mod tests {
         assert_eq!(engine.tip(), parent);
     }
 
+    #[test]
+    fn test_lib() {
+        let mut engine = Cryptarchia::from_genesis([0; 32], config());
+        engine.config.security_param = 5;
+        let mut parent = engine.genesis();
+        for i in 1..=5 {
+            let new_block = hash(&i);
+            engine = engine.receive_block(new_block, parent, i.into()).unwrap();
+            parent = new_block;
+            // the chain is still shorter than k, genesis is the only immutable block
+            assert_eq!(engine.lib(), engine.genesis());
+        }
+        for i in 6..20 {
+            let new_block = hash(&i);
+            engine = engine.receive_block(new_block, parent, i.into()).unwrap();
+            parent = new_block;
+            assert_eq!(engine.lib(), hash(&(i - 5)));
+            assert_eq!(engine.lib_branch().length(), i - 5);
+        }
+    }
+
+    #[test]
+    fn test_abandoned_blocks() {
+        let mut engine = Cryptarchia::from_genesis([0; 32], config());
+        engine.config.security_param = 10;
+        let mut parent = engine.genesis();
+        for i in 1..10 {
+            let new_block = hash(&i);
+            engine = engine.receive_block(new_block, parent, i.into()).unwrap();
+            parent = new_block;
+        }
+        assert!(engine.abandoned_blocks().is_empty());
+
+        // two forks sharing a common prefix, diverging from block 5
+        let fork_1 = hash(&"fork-1");
+        let fork_2 = hash(&"fork-2");
+        let fork_3 = hash(&"fork-3");
+        engine = engine
+            .receive_block(fork_1, hash(&5u64), 6.into())
+            .unwrap()
+            .receive_block(fork_2, fork_1, 7.into())
+            .unwrap()
+            .receive_block(fork_3, fork_1, 8.into())
+            .unwrap();
+        assert_eq!(engine.tip(), parent);
+        assert_eq!(
+            engine
+                .abandoned_blocks()
+                .iter()
+                .map(|block| block.id)
+                .collect::<Vec<_>>(),
+            vec![fork_1, fork_2, fork_3]
+        );
+    }
+
+    #[test]
+    fn test_prune_forks() {
+        let mut engine = Cryptarchia::from_genesis([0; 32], config());
+        engine.config.security_param = 3;
+        let k = engine.config.security_param as u64;
+
+        let mut parent = engine.genesis();
+        for i in 1..=10 {
+            let new_block = hash(&i);
+            engine = engine.receive_block(new_block, parent, i.into()).unwrap();
+            parent = new_block;
+        }
+        // fork from block 2 (8 blocks deep) and from block 8 (2 blocks deep)
+        let deep_fork = hash(&"deep-1");
+        engine = engine
+            .receive_block(deep_fork, hash(&2u64), 3.into())
+            .unwrap();
+        let deep_fork_2 = hash(&"deep-2");
+        engine = engine
+            .receive_block(deep_fork_2, deep_fork, 4.into())
+            .unwrap();
+        // a fork of the deep fork
+        let deep_fork_fork = hash(&"deep-fork-1");
+        engine = engine
+            .receive_block(deep_fork_fork, deep_fork, 5.into())
+            .unwrap();
+        let shallow_fork = hash(&"shallow");
+        engine = engine
+            .receive_block(shallow_fork, hash(&8u64), 9.into())
+            .unwrap();
+        assert_eq!(engine.tip(), parent);
+        assert_eq!(engine.branches().branches().len(), 4);
+
+        let mut pruned = engine.prune_forks(k);
+        pruned.sort();
+        let mut expected = vec![deep_fork, deep_fork_2, deep_fork_fork];
+        expected.sort();
+        assert_eq!(pruned, expected);
+
+        // the local chain and the shallow fork are preserved
+        assert_eq!(engine.tip(), parent);
+        assert_eq!(engine.branches().len(), 11 + 1);
+        assert!(engine.branches().get(&shallow_fork).is_some());
+        assert_eq!(engine.branches().branches().len(), 2);
+        for i in 1..=10u64 {
+            assert!(engine.branches().get(&hash(&i)).is_some());
+        }
+
+        // blocks building on pruned forks are now rejected
+        assert!(matches!(
+            engine.receive_block(hash(&"deep-3"), deep_fork_2, 6.into()),
+            Err(crate::Error::ParentPruned(_))
+        ));
+        // and so are the pruned blocks themselves, even if their parent is still known
+        assert!(matches!(
+            engine.receive_block(deep_fork, hash(&2u64), 3.into()),
+            Err(crate::Error::ParentPruned(_))
+        ));
+        // forks diverging after the pruned ones are still accepted
+        assert!(engine
+            .receive_block(hash(&"fork-7"), hash(&7u64), 8.into())
+            .is_ok());
+        // unknown parents which could be recent are still reported as missing
+        assert!(matches!(
+            engine.receive_block(hash(&"deep-4"), hash(&"unknown"), 11.into()),
+            Err(crate::Error::ParentMissing(_))
+        ));
+    }
+
+    #[test]
+    fn test_pruning_bounds_memory() {
+        let mut engine = Cryptarchia::from_genesis([0; 32], config());
+        engine.config.security_param = 5;
+        let k = engine.config.security_param as u64;
+
+        let mut parent = engine.genesis();
+        for i in 1..1000u64 {
+            let new_block = hash(&i);
+            engine = engine.receive_block(new_block, parent, i.into()).unwrap();
+            // every block also gets a competing sibling which is never extended
+            engine = engine
+                .receive_block(hash(&format!("fork-{i}")), parent, i.into())
+                .unwrap();
+            parent = new_block;
+            engine.prune_forks(k);
+
+            // only forks diverging in the last k blocks are kept
+            assert!(engine.branches().branches().len() as u64 <= k + 1);
+            assert!(engine.branches().len() as u64 <= i + 1 + k);
+        }
+        assert_eq!(engine.tip(), parent);
+    }
+
+    #[test]
+    fn test_restore_from_lib() {
+        let mut engine = Cryptarchia::from_genesis([0; 32], config());
+        engine.config.security_param = 5;
+        let mut parent = engine.genesis();
+        for i in 1..20u64 {
+            let new_block = hash(&i);
+            engine = engine.receive_block(new_block, parent, i.into()).unwrap();
+            parent = new_block;
+        }
+
+        let lib = engine.lib_branch();
+        let mut restored =
+            Cryptarchia::from_lib(lib.clone(), engine.genesis(), engine.config.clone());
+        assert_eq!(restored.tip(), lib.id());
+        assert_eq!(restored.lib(), lib.id());
+        assert_eq!(restored.genesis(), engine.genesis());
+
+        // replay the blocks after the lib
+        let mut to_replay = Vec::new();
+        let mut current = engine.tip();
+        while current != lib.id() {
+            let branch = engine.branches().get(&current).unwrap();
+            to_replay.push(branch.clone());
+            current = branch.parent();
+        }
+        for branch in to_replay.into_iter().rev() {
+            restored = restored
+                .receive_block(branch.id(), branch.parent(), branch.slot())
+                .unwrap();
+        }
+        assert_eq!(restored.tip(), engine.tip());
+        assert_eq!(restored.lib(), engine.lib());
+        assert_eq!(
+            restored.branches().get(&restored.tip()).unwrap().length(),
+            engine.branches().get(&engine.tip()).unwrap().length()
+        );
+
+        // blocks building before the restored root are not accepted
+        assert!(matches!(
+            restored.receive_block(hash(&"fork"), hash(&1u64), 2.into()),
+            Err(crate::Error::ParentPruned(_))
+        ));
+    }
+
     fn hash<T: Hash>(t: &T) -> [u8; 32] {
         let mut s = DefaultHasher::new();
         t.hash(&mut s);
@@ -0,0 +1,25 @@
+pub mod ref_state;
+pub mod sut;
+mod transition;
+
+use cryptarchia_engine::Config;
+
+type Id = [u8; 32];
+
+// A low k and a short density window, so that deep forks and the density rule
+// are exercised with small block trees
+const CONFIG: Config = Config {
+    security_param: 3,
+    active_slot_coeff: 0.5,
+};
+
+// Blocks are identified by their position in the reference block tree
+pub fn id(index: usize) -> Id {
+    let mut id = [0; 32];
+    id[..8].copy_from_slice(&(index as u64).to_be_bytes());
+    id
+}
+
+pub fn index(id: &Id) -> usize {
+    u64::from_be_bytes(id[..8].try_into().unwrap()) as usize
+}
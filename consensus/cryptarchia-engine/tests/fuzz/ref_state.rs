@@ -0,0 +1,297 @@
+use std::collections::HashSet;
+
+use proptest::prelude::*;
+use proptest::strategy::BoxedStrategy;
+use proptest_state_machine::ReferenceStateMachine;
+
+use crate::fuzz::transition::Transition;
+use crate::fuzz::CONFIG;
+
+// Forks are only created while there are fewer tips than this, so that all the orders in which
+// the fork choice rule can consider the tips can be enumerated
+const MAX_TIPS: usize = 6;
+const MAX_SLOT_GAP: u64 = 6;
+
+// A reference state machine (RefState) is used to generated state transitions.
+// It keeps the whole block tree, and implements the Ouroboros Genesis fork choice rule
+// as literally as possible, without any of the bookkeeping done by cryptarchia-engine,
+// so that it can be used to check the engine.
+#[derive(Clone, Debug)]
+pub struct RefState {
+    // blocks in the order they were received, indexed by their id
+    pub blocks: Vec<RefBlock>,
+    // one of the chains the engine can select, used to generate transitions
+    pub tip: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RefBlock {
+    pub index: usize,
+    pub parent: usize,
+    pub slot: u64,
+    pub length: u64,
+}
+
+impl ReferenceStateMachine for RefState {
+    type State = Self;
+
+    type Transition = Transition;
+
+    // Initialize the reference state machine with the genesis block
+    fn init_state() -> BoxedStrategy<Self::State> {
+        Just(RefState::genesis()).boxed()
+    }
+
+    // Generate transitions based on the current reference state machine
+    fn transitions(state: &Self::State) -> BoxedStrategy<Self::Transition> {
+        // Each `state.transition_*` function returns a Nop transition
+        // if it cannot generate the promised transition for the current reference state.
+        prop_oneof![
+            3 => state.transition_extend_local_chain(),
+            2 => state.transition_shallow_fork(),
+            1 => state.transition_deep_fork(),
+        ]
+        .boxed()
+    }
+
+    // Check that the blocks still build on known blocks, as some transitions
+    // might have been removed while shrinking
+    fn preconditions(state: &Self::State, transition: &Self::Transition) -> bool {
+        transition.blocks().iter().enumerate().all(|(i, block)| {
+            let Some(parent) = state
+                .blocks
+                .get(block.parent)
+                .or_else(|| transition.blocks().get(block.parent - state.blocks.len()))
+            else {
+                return false;
+            };
+            block.index == state.blocks.len() + i
+                && block.parent < block.index
+                && block.slot > parent.slot
+                && block.length == parent.length + 1
+        })
+    }
+
+    // Apply the given transition on the reference state machine,
+    // so that it can be used to generate next transitions.
+    fn apply(mut state: Self::State, transition: &Self::Transition) -> Self::State {
+        for block in transition.blocks() {
+            state.blocks.push(block.clone());
+            state.tip = state.fork_choice_in_order(state.tip);
+        }
+        state
+    }
+}
+
+impl RefState {
+    // Generate a Transition::ExtendLocalChain.
+    fn transition_extend_local_chain(&self) -> BoxedStrategy<Transition> {
+        let parent = self.blocks[self.tip].clone();
+        let next = self.blocks.len();
+        (1..=MAX_SLOT_GAP)
+            .prop_map(move |gap| {
+                Transition::ExtendLocalChain(RefBlock {
+                    index: next,
+                    parent: parent.index,
+                    slot: parent.slot + gap,
+                    length: parent.length + 1,
+                })
+            })
+            .boxed()
+    }
+
+    // Generate a Transition::ShallowFork.
+    fn transition_shallow_fork(&self) -> BoxedStrategy<Transition> {
+        let tips = self.tips(self.blocks.len());
+        let parents = if tips.len() < MAX_TIPS {
+            self.blocks.clone()
+        } else {
+            // only extend existing forks
+            tips.iter().map(|tip| self.blocks[*tip].clone()).collect()
+        };
+        let next = self.blocks.len();
+        (
+            proptest::sample::select(parents),
+            proptest::collection::vec(1..=MAX_SLOT_GAP, 1..=3),
+        )
+            .prop_map(move |(parent, gaps)| {
+                Transition::ShallowFork(Self::chain_from(&parent, next, &gaps))
+            })
+            .boxed()
+    }
+
+    // Generate a Transition::DeepFork.
+    fn transition_deep_fork(&self) -> BoxedStrategy<Transition> {
+        let k = CONFIG.security_param as u64;
+        let tip_length = self.blocks[self.tip].length;
+        let deep_blocks = self
+            .chain(self.tip)
+            .filter(|block| tip_length - self.blocks[*block].length >= k)
+            .map(|block| self.blocks[block].clone())
+            .collect::<Vec<_>>();
+
+        if deep_blocks.is_empty() || self.tips(self.blocks.len()).len() >= MAX_TIPS {
+            Just(Transition::Nop).boxed()
+        } else {
+            let next = self.blocks.len();
+            (
+                proptest::sample::select(deep_blocks),
+                // mostly consecutive slots, so that the fork has a chance to be denser
+                proptest::collection::vec(
+                    prop_oneof![4 => Just(1), 1 => 2..=MAX_SLOT_GAP],
+                    k as usize + 1..=2 * k as usize + 2,
+                ),
+            )
+                .prop_map(move |(parent, gaps)| {
+                    Transition::DeepFork(Self::chain_from(&parent, next, &gaps))
+                })
+                .boxed()
+        }
+    }
+
+    fn chain_from(parent: &RefBlock, next: usize, gaps: &[u64]) -> Vec<RefBlock> {
+        let mut blocks: Vec<RefBlock> = Vec::with_capacity(gaps.len());
+        for (i, gap) in gaps.iter().enumerate() {
+            let parent = blocks.last().unwrap_or(parent);
+            blocks.push(RefBlock {
+                index: next + i,
+                parent: parent.index,
+                slot: parent.slot + gap,
+                length: parent.length + 1,
+            });
+        }
+        blocks
+    }
+
+    // A random block tree, not limited in the number of forks
+    pub fn arbitrary_tree() -> impl Strategy<Value = Self> {
+        proptest::collection::vec((any::<prop::sample::Index>(), 1..=MAX_SLOT_GAP), 1..50).prop_map(
+            |blocks| {
+                let mut state = Self::genesis();
+                for (parent, gap) in blocks {
+                    let parent = state.blocks[parent.index(state.blocks.len())].clone();
+                    let next = state.blocks.len();
+                    state.blocks.extend(Self::chain_from(&parent, next, &[gap]));
+                    state.tip = state.fork_choice_in_order(state.tip);
+                }
+                state
+            },
+        )
+    }
+
+    fn genesis() -> Self {
+        RefState {
+            blocks: vec![RefBlock {
+                index: 0,
+                parent: 0,
+                slot: 0,
+                length: 0,
+            }],
+            tip: 0,
+        }
+    }
+
+    // Blocks from `tip` back to genesis
+    pub fn chain(&self, tip: usize) -> impl Iterator<Item = usize> + '_ {
+        let mut current = Some(tip);
+        std::iter::from_fn(move || {
+            let block = current?;
+            let parent = self.blocks[block].parent;
+            current = (block != parent).then_some(parent);
+            Some(block)
+        })
+    }
+
+    // Blocks without children among the first `n` blocks
+    pub fn tips(&self, n: usize) -> Vec<usize> {
+        let parents = self.blocks[1..n]
+            .iter()
+            .map(|block| block.parent)
+            .collect::<HashSet<_>>();
+        (0..n).filter(|block| !parents.contains(block)).collect()
+    }
+
+    pub fn lca(&self, a: usize, b: usize) -> usize {
+        let ancestors = self.chain(a).collect::<HashSet<_>>();
+        self.chain(b)
+            .find(|block| ancestors.contains(block))
+            .expect("all blocks descend from genesis")
+    }
+
+    // Number of blocks in the chain ending with `tip` produced in the `s` slots after `lca`
+    fn density(&self, tip: usize, lca: usize) -> usize {
+        let window_end = self.blocks[lca].slot + CONFIG.s();
+        self.chain(tip)
+            .take_while(|block| *block != lca)
+            .filter(|block| self.blocks[*block].slot <= window_end)
+            .count()
+    }
+
+    // Whether the Ouroboros Genesis rule (maxvalid-bg) prefers `candidate` over `current`:
+    // forks within k blocks follow the longest chain rule, deeper forks the densest chain
+    // in the slots right after the fork
+    pub fn prefers(&self, candidate: usize, current: usize) -> bool {
+        let k = CONFIG.security_param as u64;
+        let lca = self.lca(candidate, current);
+        if self.blocks[current].length - self.blocks[lca].length <= k {
+            self.blocks[candidate].length > self.blocks[current].length
+        } else {
+            self.density(candidate, lca) > self.density(current, lca)
+        }
+    }
+
+    // The fork choice rule compares the local chain with every tip in turn, in no particular
+    // order. Since the rule is not transitive, different orders can select different chains:
+    // return all the chains that can be selected with the first `n` blocks.
+    pub fn fork_choice_outcomes(&self, local: usize, n: usize) -> HashSet<usize> {
+        let tips = self.tips(n);
+        assert!(tips.len() <= MAX_TIPS, "too many tips to enumerate");
+        let all_considered = (1u64 << tips.len()) - 1;
+
+        let mut outcomes = HashSet::new();
+        let mut visited = HashSet::new();
+        let mut to_visit = vec![(local, 0u64)];
+        while let Some((selected, considered)) = to_visit.pop() {
+            if !visited.insert((selected, considered)) {
+                continue;
+            }
+            if considered == all_considered {
+                outcomes.insert(selected);
+                continue;
+            }
+            for (i, tip) in tips.iter().enumerate() {
+                if considered & (1 << i) == 0 {
+                    let next = if self.prefers(*tip, selected) {
+                        *tip
+                    } else {
+                        selected
+                    };
+                    to_visit.push((next, considered | (1 << i)));
+                }
+            }
+        }
+        outcomes
+    }
+
+    fn fork_choice_in_order(&self, local: usize) -> usize {
+        self.tips(self.blocks.len())
+            .into_iter()
+            .fold(local, |selected, tip| {
+                if self.prefers(tip, selected) {
+                    tip
+                } else {
+                    selected
+                }
+            })
+    }
+
+    // A tip preferred over any other block, which the fork choice rule selects no matter
+    // which chain was selected before or in which order blocks were received
+    pub fn dominant_tip(&self) -> Option<usize> {
+        self.tips(self.blocks.len()).into_iter().find(|tip| {
+            (0..self.blocks.len())
+                .filter(|block| block != tip)
+                .all(|block| self.prefers(*tip, block) && !self.prefers(block, *tip))
+        })
+    }
+}
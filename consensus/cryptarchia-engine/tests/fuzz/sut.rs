@@ -0,0 +1,117 @@
+use std::collections::HashSet;
+
+use cryptarchia_engine::Cryptarchia;
+use proptest_state_machine::{ReferenceStateMachine, StateMachineTest};
+
+use crate::fuzz::ref_state::{RefBlock, RefState};
+use crate::fuzz::{id, index, Id, CONFIG};
+
+// CryptarchiaEngineTest defines a state that we want to test.
+// This is called as SUT (System Under Test).
+#[derive(Clone, Debug)]
+pub struct CryptarchiaEngineTest {
+    pub engine: Cryptarchia<Id>,
+}
+
+impl CryptarchiaEngineTest {
+    pub fn new() -> Self {
+        CryptarchiaEngineTest {
+            engine: Cryptarchia::from_genesis(id(0), CONFIG),
+        }
+    }
+
+    pub fn receive_block(&self, block: &RefBlock) -> Self {
+        let engine = self
+            .engine
+            .receive_block(id(block.index), id(block.parent), block.slot.into())
+            .unwrap();
+        CryptarchiaEngineTest { engine }
+    }
+
+    // Receive the blocks in the given order, buffering the ones received before their parent
+    // as a node would do
+    pub fn receive_in_order(&self, ref_state: &RefState, order: &[usize]) -> Self {
+        let mut sut = self.clone();
+        let mut pending = Vec::new();
+        for block in order {
+            pending.push(&ref_state.blocks[*block]);
+            while let Some(position) = pending
+                .iter()
+                .position(|block| sut.engine.branches().get(&id(block.parent)).is_some())
+            {
+                sut = sut.receive_block(pending.remove(position));
+            }
+        }
+        assert!(pending.is_empty());
+        sut
+    }
+}
+
+// StateMachineTest defines how transitions are applied to the real state machine
+// and what checks should be performed.
+impl StateMachineTest for CryptarchiaEngineTest {
+    // SUT is the real state machine that we want to test.
+    type SystemUnderTest = Self;
+
+    // A reference state machine that should be compared against the SUT.
+    type Reference = RefState;
+
+    // Initialize the SUT state
+    fn init_test(
+        _ref_state: &<Self::Reference as ReferenceStateMachine>::State,
+    ) -> Self::SystemUnderTest {
+        CryptarchiaEngineTest::new()
+    }
+
+    // Apply the transition on the SUT state and check post-conditions
+    fn apply(
+        mut state: Self::SystemUnderTest,
+        ref_state: &<Self::Reference as ReferenceStateMachine>::State,
+        transition: <Self::Reference as ReferenceStateMachine>::Transition,
+    ) -> Self::SystemUnderTest {
+        println!("{transition:?}");
+
+        for block in transition.blocks() {
+            let local_chain = index(&state.engine.tip());
+            state = state.receive_block(block);
+            // the fork choice rule is applied on every block, starting from the local chain
+            let outcomes = ref_state.fork_choice_outcomes(local_chain, block.index + 1);
+            assert!(
+                outcomes.contains(&index(&state.engine.tip())),
+                "selected {} from {local_chain}, expected one of {outcomes:?}",
+                index(&state.engine.tip()),
+            );
+        }
+        state
+    }
+
+    // Check invariants after every transition.
+    fn check_invariants(
+        state: &Self::SystemUnderTest,
+        ref_state: &<Self::Reference as ReferenceStateMachine>::State,
+    ) {
+        assert_same_tree(&state.engine, ref_state);
+    }
+}
+
+pub fn assert_same_tree(engine: &Cryptarchia<Id>, ref_state: &RefState) {
+    let tips = engine
+        .branches()
+        .branches()
+        .iter()
+        .map(|branch| index(&branch.id()))
+        .collect::<HashSet<_>>();
+    let ref_tips = ref_state
+        .tips(ref_state.blocks.len())
+        .into_iter()
+        .collect::<HashSet<_>>();
+    assert_eq!(tips, ref_tips);
+
+    assert_eq!(engine.branches().len(), ref_state.blocks.len());
+    for block in &ref_state.blocks {
+        let branch = engine.branches().get(&id(block.index)).unwrap();
+        assert_eq!(index(&branch.parent()), block.parent);
+        assert_eq!(u64::from(branch.slot()), block.slot);
+        assert_eq!(branch.length(), block.length);
+    }
+}
@@ -0,0 +1,24 @@
+use crate::fuzz::ref_state::RefBlock;
+
+// State transtitions that will be picked randomly
+#[derive(Clone, Debug)]
+pub enum Transition {
+    Nop,
+    // A block building on the current tip of the local chain
+    ExtendLocalChain(RefBlock),
+    // A few blocks building on any known block
+    ShallowFork(Vec<RefBlock>),
+    // More than k blocks building on a block at least k blocks deep in the local chain,
+    // mostly in consecutive slots so that it can be denser than the local chain after the fork
+    DeepFork(Vec<RefBlock>),
+}
+
+impl Transition {
+    pub fn blocks(&self) -> &[RefBlock] {
+        match self {
+            Transition::Nop => &[],
+            Transition::ExtendLocalChain(block) => std::slice::from_ref(block),
+            Transition::ShallowFork(blocks) | Transition::DeepFork(blocks) => blocks,
+        }
+    }
+}
@@ -0,0 +1,48 @@
+mod fuzz;
+
+use proptest::prelude::*;
+use proptest::test_runner::Config;
+use proptest_state_machine::{prop_state_machine, ReferenceStateMachine, StateMachineTest};
+
+use fuzz::ref_state::RefState;
+use fuzz::sut::{assert_same_tree, CryptarchiaEngineTest};
+
+prop_state_machine! {
+    #![proptest_config(Config {
+        // Only run 50 cases by default to avoid taking too long to finish.
+        cases: 50,
+        .. Config::default()
+    })]
+
+    #[test]
+    fn fork_choice_test(sequential 1..40 => CryptarchiaEngineTest);
+}
+
+proptest! {
+    #![proptest_config(Config {
+        cases: 100,
+        .. Config::default()
+    })]
+
+    #[test]
+    fn receive_block_is_order_independent(
+        (ref_state, order) in RefState::arbitrary_tree().prop_flat_map(|ref_state| {
+            let blocks = (1..ref_state.blocks.len()).collect::<Vec<_>>();
+            (Just(ref_state), Just(blocks).prop_shuffle())
+        })
+    ) {
+        let in_order = (1..ref_state.blocks.len()).collect::<Vec<_>>();
+        let sut = CryptarchiaEngineTest::new();
+        let expected = sut.receive_in_order(&ref_state, &in_order);
+        let shuffled = sut.receive_in_order(&ref_state, &order);
+
+        assert_same_tree(&expected.engine, &ref_state);
+        assert_same_tree(&shuffled.engine, &ref_state);
+        // the selected chain depends on the order blocks are received in only if no chain
+        // is unambiguously better than all the others
+        if let Some(tip) = ref_state.dominant_tip() {
+            prop_assert_eq!(fuzz::index(&expected.engine.tip()), tip);
+            prop_assert_eq!(fuzz::index(&shuffled.engine.tip()), tip);
+        }
+    }
+}
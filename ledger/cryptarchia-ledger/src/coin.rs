@@ -1,10 +1,13 @@
-use crate::{crypto::Blake2b, Commitment, EpochState, LeaderProof, Nonce, Nullifier};
+use crate::{
+    crypto::Blake2b,
+    threshold,
+    vrf::{self, PublicKey, SecretKey},
+    Commitment, EpochState, LeaderProof, Nonce, Note, Nullifier,
+};
 use blake2::digest::Digest;
 use cryptarchia_engine::config::Config;
 use cryptarchia_engine::Slot;
-
-type SecretKey = [u8; 32];
-type PublicKey = [u8; 32];
+use num_bigint::BigUint;
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Debug, Eq, PartialEq, Copy, Hash)]
@@ -16,10 +19,18 @@ impl From<u32> for Value {
     }
 }
 
+impl From<Value> for u32 {
+    fn from(value: Value) -> Self {
+        value.0
+    }
+}
+
 // This implementatio is only a stub
 // see https://github.com/logos-co/nomos-specs/blob/master/cryptarchia/cryptarchia.py for a spec
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
 pub struct Coin {
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::bytes_array"))]
     sk: SecretKey,
     nonce: Nonce,
     value: Value,
@@ -42,75 +53,104 @@ impl Coin {
         }
     }
 
-    fn value_bytes(&self) -> [u8; 32] {
-        let mut value_bytes = [0; 32];
-        value_bytes[28..].copy_from_slice(&self.value.0.to_be_bytes());
-        value_bytes
+    pub fn pk(&self) -> PublicKey {
+        vrf::public_key(&self.sk)
     }
 
-    pub fn pk(&self) -> &PublicKey {
+    pub(crate) fn sk(&self) -> &SecretKey {
         &self.sk
     }
 
-    pub fn commitment(&self) -> Commitment {
-        let mut h = Blake2b::new();
-        h.update("coin-commitment");
-        h.update(self.nonce);
-        h.update(self.pk());
-        h.update(self.value_bytes());
-        <[u8; 32]>::from(h.finalize()).into()
+    pub fn nonce(&self) -> Nonce {
+        self.nonce
     }
 
-    pub fn nullifier(&self) -> Nullifier {
-        let mut h = Blake2b::new();
-        h.update("coin-nullifier");
-        h.update(self.nonce);
-        h.update(self.pk());
-        h.update(self.value_bytes());
-        <[u8; 32]>::from(h.finalize()).into()
+    pub fn value(&self) -> Value {
+        self.value
     }
 
-    // TODO: better precision
-    fn phi(active_slot_coeff: f64, relative_stake: f64) -> f64 {
-        1.0 - (1.0 - active_slot_coeff).powf(relative_stake)
+    /// Public opening of the coin commitment
+    pub fn note(&self) -> Note {
+        Note::new(self.pk(), self.nonce, self.value)
+    }
+
+    pub fn commitment(&self) -> Commitment {
+        commitment(&self.nonce, &self.pk(), self.value)
+    }
+
+    pub fn nullifier(&self) -> Nullifier {
+        nullifier(&self.nonce, &self.pk(), self.value)
     }
 
     pub fn vrf(&self, epoch_nonce: Nonce, slot: Slot) -> [u8; 32] {
-        let mut h = Blake2b::new();
-        h.update("lead");
-        h.update(epoch_nonce);
-        h.update(u64::from(slot).to_be_bytes());
-        h.update(self.sk);
-        h.update(self.nonce);
-        h.finalize().into()
+        vrf::evaluate(&self.sk, epoch_nonce, slot)
     }
 
     pub fn is_slot_leader(&self, epoch: &EpochState, slot: Slot, config: &Config) -> bool {
         // TODO: check slot and epoch state are consistent
-        let relative_stake = f64::from(self.value.0) / f64::from(epoch.total_stake.0);
-
-        let vrf = self.vrf(epoch.nonce, slot);
-
-        // HACK: we artificially restrict the VRF output so that it fits into a u32
-        // this is not going to be the final implementation anyway and the last 4 bytes
-        // should have a similar (statistical) distribution as the full output
-        let vrf = u32::from_be_bytes(vrf[28..].try_into().unwrap());
-        vrf < (f64::from(u32::MAX) * Self::phi(config.active_slot_coeff, relative_stake)) as u32
+        is_lottery_won(&self.vrf(epoch.nonce, slot), self.value, epoch, config)
     }
 
-    pub fn to_proof(&self, slot: Slot) -> LeaderProof {
+    pub fn to_proof(&self, epoch: &EpochState, slot: Slot) -> LeaderProof {
+        let (vrf_output, vrf_proof) = vrf::prove(&self.sk, epoch.nonce, slot);
         LeaderProof::new(
             self.commitment(),
             self.nullifier(),
             slot,
             self.evolve().commitment(),
+            self.pk(),
+            self.nonce,
+            self.value,
+            vrf_output,
+            vrf_proof,
         )
     }
 }
 
+fn value_bytes(value: Value) -> [u8; 32] {
+    let mut value_bytes = [0; 32];
+    value_bytes[28..].copy_from_slice(&value.0.to_be_bytes());
+    value_bytes
+}
+
+pub(crate) fn commitment(nonce: &Nonce, pk: &PublicKey, value: Value) -> Commitment {
+    let mut h = Blake2b::new();
+    h.update("coin-commitment");
+    h.update(nonce);
+    h.update(pk);
+    h.update(value_bytes(value));
+    <[u8; 32]>::from(h.finalize()).into()
+}
+
+pub(crate) fn nullifier(nonce: &Nonce, pk: &PublicKey, value: Value) -> Nullifier {
+    let mut h = Blake2b::new();
+    h.update("coin-nullifier");
+    h.update(nonce);
+    h.update(pk);
+    h.update(value_bytes(value));
+    <[u8; 32]>::from(h.finalize()).into()
+}
+
+/// Whether a coin with the given value wins the slot lottery with the given VRF output
+pub(crate) fn is_lottery_won(
+    vrf: &[u8; 32],
+    value: Value,
+    epoch: &EpochState,
+    config: &Config,
+) -> bool {
+    let threshold =
+        threshold::leadership_threshold(config.active_slot_coeff, value, epoch.total_stake);
+    BigUint::from_bytes_be(vrf) < threshold
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    fn phi(active_slot_coeff: f64, relative_stake: f64) -> f64 {
+        1.0 - (1.0 - active_slot_coeff).powf(relative_stake)
+    }
+
     #[test]
     fn test_slot_leader_statistics() {
         let epoch = EpochState {
@@ -134,7 +174,7 @@ mod tests {
         // We'll use the Margin of Error equation to decide how many samples we need.
         //  https://en.wikipedia.org/wiki/Margin_of_error
         let margin_of_error = 1e-4;
-        let p = Coin::phi(config.active_slot_coeff, 10.0 / 1000.0);
+        let p = phi(config.active_slot_coeff, 10.0 / 1000.0);
         let std = (p * (1.0 - p)).sqrt();
         let z = 3.0; // we want 3 std from the mean to be within the margin of error
         let n = (z * std / margin_of_error).powi(2).ceil();
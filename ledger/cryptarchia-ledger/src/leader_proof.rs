@@ -1,5 +1,17 @@
-use cryptarchia_engine::Slot;
-
+use crate::{
+    coin,
+    vrf::{self, PublicKey, VrfOutput, VrfProof},
+    EpochState, Nonce, Value,
+};
+use cryptarchia_engine::{Config, Slot};
+
+/// Proof that the leader of a slot won the lottery with a coin committed in the ledger.
+///
+/// The lottery is verifiable but the leader is not hidden: the coin opening (`pk`, `nonce` and
+/// `value`) is sent in the clear so that verifiers can recompute the commitment and nullifier.
+/// This links every block to the coin which produced it and reveals its stake. Replacing the
+/// opening with a zk proof of membership, as in the spec, is left for a separate change, and
+/// leader anonymity must not be assumed until then.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Debug, Eq, PartialEq, Copy, Hash)]
 pub struct LeaderProof {
@@ -7,6 +19,13 @@ pub struct LeaderProof {
     nullifier: Nullifier,
     slot: Slot,
     evolved_commitment: Commitment,
+    // opening of the leader coin, needed to check the VRF key and the
+    // relative stake used for the lottery belong to the committed coin
+    pk: PublicKey,
+    nonce: Nonce,
+    value: Value,
+    vrf_output: VrfOutput,
+    vrf_proof: VrfProof,
 }
 
 impl LeaderProof {
@@ -26,29 +45,69 @@ impl LeaderProof {
         &self.evolved_commitment
     }
 
-    #[cfg(test)]
-    pub fn dummy(slot: Slot) -> Self {
-        Self {
-            commitment: Commitment([0; 32]),
-            nullifier: Nullifier([0; 32]),
-            slot,
-            evolved_commitment: Commitment([0; 32]),
-        }
+    pub fn pk(&self) -> &PublicKey {
+        &self.pk
+    }
+
+    pub fn nonce(&self) -> &Nonce {
+        &self.nonce
+    }
+
+    pub fn value(&self) -> Value {
+        self.value
     }
 
+    pub fn vrf_output(&self) -> &VrfOutput {
+        &self.vrf_output
+    }
+
+    pub fn vrf_proof(&self) -> &VrfProof {
+        &self.vrf_proof
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         commitment: Commitment,
         nullifier: Nullifier,
         slot: Slot,
         evolved_commitment: Commitment,
+        pk: PublicKey,
+        nonce: Nonce,
+        value: Value,
+        vrf_output: VrfOutput,
+        vrf_proof: VrfProof,
     ) -> Self {
         Self {
             commitment,
             nullifier,
             slot,
             evolved_commitment,
+            pk,
+            nonce,
+            value,
+            vrf_output,
+            vrf_proof,
         }
     }
+
+    /// Check that the proof opens the committed coin and that the coin
+    /// won the slot lottery in the given epoch
+    pub fn verify(&self, epoch: &EpochState, config: &Config) -> bool {
+        if coin::commitment(&self.nonce, &self.pk, self.value) != self.commitment
+            || coin::nullifier(&self.nonce, &self.pk, self.value) != self.nullifier
+        {
+            return false;
+        }
+
+        vrf::verify(
+            &self.pk,
+            epoch.nonce,
+            self.slot,
+            &self.vrf_output,
+            &self.vrf_proof,
+        )
+        .is_some_and(|vrf| coin::is_lottery_won(&vrf, self.value, epoch, config))
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Copy, Hash)]
@@ -3,7 +3,10 @@ mod config;
 mod crypto;
 mod leader_proof;
 mod nonce;
+mod threshold;
+mod tx;
 mod utils;
+mod vrf;
 
 use blake2::Digest;
 use cryptarchia_engine::{Epoch, Slot};
@@ -12,22 +15,33 @@ use std::{collections::HashMap, hash::Hash};
 use thiserror::Error;
 
 type HashTrieSet<T> = rpds::HashTrieSetSync<T>;
+type RedBlackTreeMap<K, V> = rpds::RedBlackTreeMapSync<K, V>;
 
 pub use coin::{Coin, Value};
 pub use config::Config;
 pub use leader_proof::*;
 pub use nonce::*;
+pub use tx::{Input, Note, Signature, Tx};
+pub use vrf::{PublicKey, SecretKey, VrfOutput, VrfProof};
 
 #[derive(Clone, Debug, Error)]
 pub enum LedgerError<Id> {
     #[error("Commitment not found in the ledger state")]
     CommitmentNotFound,
+    #[error("Invalid leader proof")]
+    InvalidLeaderProof,
+    #[error("Transaction input is not signed by the owner of the note")]
+    InvalidSignature,
+    #[error("Transaction inputs and outputs have different values")]
+    UnbalancedTransaction,
     #[error("Nullifier already exists in the ledger state")]
     NullifierExists,
     #[error("Commitment already exists in the ledger state")]
     CommitmentExists,
     #[error("Invalid block slot {block:?} for parent slot {parent:?}")]
     InvalidSlot { parent: Slot, block: Slot },
+    #[error("Leader proof for slot {proof:?} in a block for slot {block:?}")]
+    ProofSlotMismatch { block: Slot, proof: Slot },
     #[error("Parent block not found: {0:?}")]
     ParentNotFound(Id),
     #[error("Orphan block missing: {0:?}. Importing leader proofs requires the block to be validated first")]
@@ -57,8 +71,10 @@ impl EpochState {
         };
 
         let stake_snapshot_slot = config.stake_distribution_snapshot(self.epoch);
+        // notes received through transactions are only spendable, but take part in
+        // leader elections once they are part of a snapshot
         let commitments = if ledger.slot < stake_snapshot_slot {
-            ledger.lead_commitments.clone()
+            ledger.spend_commitments.clone()
         } else {
             self.commitments
         };
@@ -70,13 +86,25 @@ impl EpochState {
         }
     }
 
-    fn is_eligible_leader(&self, commitment: &Commitment) -> bool {
+    pub fn is_eligible_leader(&self, commitment: &Commitment) -> bool {
         self.commitments.contains(commitment)
     }
 
     pub fn epoch(&self) -> Epoch {
         self.epoch
     }
+
+    pub fn nonce(&self) -> &Nonce {
+        &self.nonce
+    }
+
+    pub fn commitments(&self) -> impl Iterator<Item = &Commitment> {
+        self.commitments.iter()
+    }
+
+    pub fn total_stake(&self) -> Value {
+        self.total_stake
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -97,7 +125,7 @@ where
     }
 
     #[must_use = "this returns the result of the operation, without modifying the original"]
-    pub fn try_update(
+    pub fn try_update<'a>(
         &self,
         id: Id,
         parent_id: Id,
@@ -105,6 +133,7 @@ where
         proof: &LeaderProof,
         // (update corresponding to the leader proof, leader proof)
         orphan_proofs: impl IntoIterator<Item = (Id, LeaderProof)>,
+        transactions: impl IntoIterator<Item = &'a Tx>,
     ) -> Result<Self, LedgerError<Id>> {
         let parent_state = self
             .states
@@ -124,10 +153,13 @@ where
             }
         }
 
-        let new_state =
-            parent_state
-                .clone()
-                .try_update(slot, proof, &orphan_proofs, &self.config)?;
+        let new_state = parent_state.clone().try_update(
+            slot,
+            proof,
+            &orphan_proofs,
+            transactions,
+            &self.config,
+        )?;
 
         let mut states = self.states.clone();
 
@@ -136,10 +168,58 @@ where
         Ok(Self { states, config })
     }
 
+    /// Select the orphan proofs that a block at `slot` building on `parent_id` can import,
+    /// preserving the order of `candidates`.
+    /// Proofs from blocks the ledger has not validated, or that conflict with the parent state,
+    /// the previously selected proofs or the block leader proof are skipped.
+    pub fn importable_orphan_proofs(
+        &self,
+        parent_id: Id,
+        slot: Slot,
+        proof: &LeaderProof,
+        candidates: impl IntoIterator<Item = (Id, LeaderProof)>,
+    ) -> Vec<Id> {
+        let Some(mut state) = self.states.get(&parent_id).and_then(|state| {
+            state
+                .clone()
+                .update_epoch_state::<Id>(slot, &self.config)
+                .ok()
+        }) else {
+            return vec![];
+        };
+
+        let mut importable = Vec::new();
+        for (id, orphan) in candidates {
+            // the leader coin is spent by the block itself
+            if !self.states.contains_key(&id) || orphan.nullifier() == proof.nullifier() {
+                continue;
+            }
+            if let Ok(new_state) = state.clone().try_apply_proof::<Id>(&orphan, &self.config) {
+                state = new_state;
+                importable.push(id);
+            }
+        }
+        importable
+    }
+
     pub fn state(&self, id: &Id) -> Option<&LedgerState> {
         self.states.get(id)
     }
 
+    /// Remove the state associated with a block, e.g. because it belongs to a fork
+    /// that has been pruned by the consensus engine
+    pub fn prune_state_at(&mut self, id: &Id) {
+        self.states.remove(id);
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
     pub fn config(&self) -> &Config {
         &self.config
     }
@@ -159,19 +239,34 @@ pub struct LedgerState {
     // rolling snapshot of the state for the next epoch, used for epoch transitions
     next_epoch_state: EpochState,
     epoch_state: EpochState,
+    // snapshots of the previous epochs of this branch, used to verify leader proofs in old blocks
+    #[cfg_attr(feature = "serde", serde(default))]
+    epoch_history: RedBlackTreeMap<Epoch, EpochState>,
 }
 
 impl LedgerState {
-    fn try_update<Id>(
+    fn try_update<'a, Id>(
         self,
         slot: Slot,
         proof: &LeaderProof,
         orphan_proofs: &[LeaderProof],
+        transactions: impl IntoIterator<Item = &'a Tx>,
         config: &Config,
     ) -> Result<Self, LedgerError<Id>> {
-        // TODO: import leader proofs
-        self.update_epoch_state(slot, config)?
-            .try_apply_leadership(proof, orphan_proofs, config)
+        // a proof won in one slot can't be used to lead another one
+        if proof.slot() != slot {
+            return Err(LedgerError::ProofSlotMismatch {
+                block: slot,
+                proof: proof.slot(),
+            });
+        }
+        let mut state = self
+            .update_epoch_state(slot, config)?
+            .try_apply_leadership(proof, orphan_proofs, config)?;
+        for tx in transactions {
+            state = state.try_apply_tx(tx)?;
+        }
+        Ok(state)
     }
 
     fn update_epoch_state<Id>(self, slot: Slot, config: &Config) -> Result<Self, LedgerError<Id>> {
@@ -182,7 +277,7 @@ impl LedgerState {
             });
         }
 
-        // TODO: update once supply can change
+        // transactions can only move value between notes, so the total stake is fixed at genesis
         let total_stake = self.epoch_state.total_stake;
         let current_epoch = config.epoch(self.slot);
         let new_epoch = config.epoch(slot);
@@ -207,6 +302,9 @@ impl LedgerState {
             })
         } else if new_epoch == current_epoch + 1 {
             // case 2)
+            let epoch_history = self
+                .epoch_history
+                .insert(self.epoch_state.epoch, self.epoch_state.clone());
             let epoch_state = self.next_epoch_state.clone();
             let next_epoch_state = EpochState {
                 epoch: new_epoch + 1,
@@ -218,10 +316,15 @@ impl LedgerState {
                 slot,
                 next_epoch_state,
                 epoch_state,
+                epoch_history,
                 ..self
             })
         } else {
             // case 3)
+            // no block was produced in the skipped epochs, so there is nothing to record for them
+            let epoch_history = self
+                .epoch_history
+                .insert(self.epoch_state.epoch, self.epoch_state.clone());
             let epoch_state = EpochState {
                 epoch: new_epoch,
                 nonce: self.nonce,
@@ -238,6 +341,7 @@ impl LedgerState {
                 slot,
                 next_epoch_state,
                 epoch_state,
+                epoch_history,
                 ..self
             })
         }
@@ -248,7 +352,10 @@ impl LedgerState {
         proof: &LeaderProof,
         config: &Config,
     ) -> Result<Self, LedgerError<Id>> {
-        assert_eq!(config.epoch(proof.slot()), self.epoch_state.epoch);
+        // imported proofs might be from a different epoch
+        if config.epoch(proof.slot()) != self.epoch_state.epoch {
+            return Err(LedgerError::InvalidLeaderProof);
+        }
         // The leadership coin either has to be in the state snapshot or be derived from
         // a coin that is in the state snapshot (i.e. be in the lead coins commitments)
         if !self.can_lead(proof.commitment())
@@ -265,6 +372,10 @@ impl LedgerState {
             return Err(LedgerError::CommitmentExists);
         }
 
+        if !proof.verify(&self.epoch_state, &config.consensus_config) {
+            return Err(LedgerError::InvalidLeaderProof);
+        }
+
         let lead_commitments = self.lead_commitments.insert(*proof.evolved_commitment());
         let spend_commitments = self.spend_commitments.insert(*proof.evolved_commitment());
         let nullifiers = self.nullifiers.insert(*proof.nullifier());
@@ -292,6 +403,45 @@ impl LedgerState {
         Ok(self)
     }
 
+    /// Spend the transaction inputs and make its outputs available for spending.
+    /// New notes take part in leader elections once they are included in an epoch
+    /// stake distribution snapshot.
+    pub fn try_apply_tx<Id>(self, tx: &Tx) -> Result<Self, LedgerError<Id>> {
+        if !tx.is_balanced() {
+            return Err(LedgerError::UnbalancedTransaction);
+        }
+        if !tx.verify_signatures() {
+            return Err(LedgerError::InvalidSignature);
+        }
+
+        let mut nullifiers = self.nullifiers;
+        for input in tx.inputs() {
+            if !self.spend_commitments.contains(&input.commitment()) {
+                return Err(LedgerError::CommitmentNotFound);
+            }
+            let nullifier = input.nullifier();
+            if nullifiers.contains(&nullifier) {
+                return Err(LedgerError::NullifierExists);
+            }
+            nullifiers = nullifiers.insert(nullifier);
+        }
+
+        let mut spend_commitments = self.spend_commitments;
+        for output in tx.outputs() {
+            let commitment = output.commitment();
+            if spend_commitments.contains(&commitment) {
+                return Err(LedgerError::CommitmentExists);
+            }
+            spend_commitments = spend_commitments.insert(commitment);
+        }
+
+        Ok(Self {
+            nullifiers,
+            spend_commitments,
+            ..self
+        })
+    }
+
     pub fn can_spend(&self, commitment: &Commitment) -> bool {
         self.spend_commitments.contains(commitment)
     }
@@ -323,8 +473,16 @@ impl LedgerState {
         }
     }
 
-    pub fn from_commitments(commitments: impl IntoIterator<Item = Commitment>) -> Self {
-        let commitments = commitments.into_iter().collect::<HashTrieSet<_>>();
+    pub fn from_notes(notes: impl IntoIterator<Item = Note>) -> Self {
+        let notes = notes.into_iter().collect::<Vec<_>>();
+        let total_stake = notes
+            .iter()
+            .try_fold(0u32, |total, note| total.checked_add(note.value().into()))
+            .expect("total stake should fit in a u32");
+        let commitments = notes
+            .iter()
+            .map(Note::commitment)
+            .collect::<HashTrieSet<_>>();
         Self {
             lead_commitments: commitments.clone(),
             spend_commitments: commitments,
@@ -335,14 +493,15 @@ impl LedgerState {
                 epoch: 1.into(),
                 nonce: [0; 32].into(),
                 commitments: Default::default(),
-                total_stake: 1.into(),
+                total_stake: total_stake.into(),
             },
             epoch_state: EpochState {
                 epoch: 0.into(),
                 nonce: [0; 32].into(),
                 commitments: Default::default(),
-                total_stake: 1.into(),
+                total_stake: total_stake.into(),
             },
+            epoch_history: Default::default(),
         }
     }
 
@@ -357,6 +516,18 @@ impl LedgerState {
     pub fn next_epoch_state(&self) -> &EpochState {
         &self.next_epoch_state
     }
+
+    /// Epoch state for any epoch up to the next one, as seen by this branch.
+    /// Returns `None` for epochs in which this branch has no blocks, or that are too far in the future.
+    pub fn epoch_state_for(&self, epoch: Epoch) -> Option<&EpochState> {
+        if epoch == self.epoch_state.epoch {
+            Some(&self.epoch_state)
+        } else if epoch == self.next_epoch_state.epoch {
+            Some(&self.next_epoch_state)
+        } else {
+            self.epoch_history.get(&epoch)
+        }
+    }
 }
 
 impl core::fmt::Debug for LedgerState {
@@ -380,7 +551,7 @@ impl core::fmt::Debug for LedgerState {
 #[cfg(test)]
 pub mod tests {
     use super::{Coin, EpochState, Ledger, LedgerState};
-    use crate::{crypto::Blake2b, Commitment, Config, LedgerError};
+    use crate::{crypto::Blake2b, Commitment, Config, LeaderProof, LedgerError, Note, Tx};
     use blake2::Digest;
     use cryptarchia_engine::Slot;
 
@@ -410,23 +581,60 @@ pub mod tests {
             .into()
     }
 
+    // epoch state a block at `slot` on top of `parent` will be validated against
+    fn epoch_state(ledger: &Ledger<HeaderId>, parent: HeaderId, slot: Slot) -> EpochState {
+        ledger
+            .states
+            .get(&parent)
+            .and_then(|state| {
+                state
+                    .clone()
+                    .update_epoch_state::<HeaderId>(slot, &ledger.config)
+                    .ok()
+            })
+            .map_or_else(|| genesis_state(&[]).epoch_state, |state| state.epoch_state)
+    }
+
     fn update_orphans(
         ledger: &mut Ledger<HeaderId>,
         parent: HeaderId,
         slot: impl Into<Slot>,
         coin: Coin,
         orphans: Vec<(HeaderId, (u64, Coin))>,
+    ) -> Result<HeaderId, LedgerError<HeaderId>> {
+        update_block(ledger, parent, slot, coin, orphans, &[])
+    }
+
+    fn update_txs(
+        ledger: &mut Ledger<HeaderId>,
+        parent: HeaderId,
+        slot: impl Into<Slot>,
+        coin: Coin,
+        txs: &[Tx],
+    ) -> Result<HeaderId, LedgerError<HeaderId>> {
+        update_block(ledger, parent, slot, coin, vec![], txs)
+    }
+
+    fn update_block(
+        ledger: &mut Ledger<HeaderId>,
+        parent: HeaderId,
+        slot: impl Into<Slot>,
+        coin: Coin,
+        orphans: Vec<(HeaderId, (u64, Coin))>,
+        txs: &[Tx],
     ) -> Result<HeaderId, LedgerError<HeaderId>> {
         let slot = slot.into();
         let id = make_id(parent, slot, coin);
+        let epoch_state = epoch_state(ledger, parent, slot);
         *ledger = ledger.try_update(
             id,
             parent,
             slot,
-            &coin.to_proof(slot),
+            &coin.to_proof(&epoch_state, slot),
             orphans
                 .into_iter()
-                .map(|(id, (slot, coin))| (id, coin.to_proof(slot.into()))),
+                .map(|(id, (slot, coin))| (id, coin.to_proof(&epoch_state, slot.into()))),
+            txs,
         )?;
         Ok(id)
     }
@@ -462,6 +670,7 @@ pub mod tests {
                 commitments: commitments.iter().cloned().collect(),
                 total_stake: 1.into(),
             },
+            epoch_history: Default::default(),
         }
     }
 
@@ -514,6 +723,145 @@ pub mod tests {
         ));
     }
 
+    #[test]
+    fn test_invalid_leader_proof() {
+        let coin = coin(0);
+        let (mut ledger, genesis) = ledger(&[coin.commitment()]);
+        let epoch_state = epoch_state(&ledger, genesis, 1.into());
+        let try_update = |ledger: &Ledger<HeaderId>, proof: &LeaderProof| {
+            ledger.try_update(
+                make_id(genesis, 1, coin),
+                genesis,
+                1.into(),
+                proof,
+                vec![],
+                [],
+            )
+        };
+
+        // proof for a different epoch nonce
+        let mut wrong_nonce = epoch_state.clone();
+        wrong_nonce.nonce = [1; 32].into();
+        assert!(matches!(
+            try_update(&ledger, &coin.to_proof(&wrong_nonce, 1.into())),
+            Err(LedgerError::InvalidLeaderProof)
+        ));
+
+        // the claimed value does not match the committed coin
+        let proof = coin.to_proof(&epoch_state, 1.into());
+        let inflated = LeaderProof::new(
+            *proof.commitment(),
+            *proof.nullifier(),
+            proof.slot(),
+            *proof.evolved_commitment(),
+            *proof.pk(),
+            *proof.nonce(),
+            2.into(),
+            *proof.vrf_output(),
+            *proof.vrf_proof(),
+        );
+        assert!(matches!(
+            try_update(&ledger, &inflated),
+            Err(LedgerError::InvalidLeaderProof)
+        ));
+
+        // a valid proof for a coin that did not win the lottery
+        ledger.config.consensus_config.active_slot_coeff = 0.01;
+        assert!(!coin.is_slot_leader(&epoch_state, 1.into(), &ledger.config.consensus_config));
+        assert!(matches!(
+            try_update(&ledger, &proof),
+            Err(LedgerError::InvalidLeaderProof)
+        ));
+    }
+
+    #[test]
+    fn test_tx_moves_stake() {
+        let leader = coin(0);
+        let sender = Coin::new([1; 32], [0; 32].into(), 10.into());
+        let receiver = Coin::new([2; 32], [1; 32].into(), 7.into());
+        let change = Coin::new([1; 32], [2; 32].into(), 3.into());
+        let (mut ledger, genesis) = ledger(&[leader.commitment(), sender.commitment()]);
+
+        let tx = Tx::new(&[sender], vec![receiver.note(), change.note()]);
+        let h = update_txs(&mut ledger, genesis, 1, leader, &[tx]).unwrap();
+        let state = ledger.state(&h).unwrap();
+        assert!(state.is_nullified(&sender.nullifier()));
+        assert!(state.can_spend(&receiver.commitment()));
+        assert!(state.can_spend(&change.commitment()));
+
+        // the spent note can't be spent again, nor used to lead
+        let double_spend = Tx::new(&[sender], vec![sender.evolve().note()]);
+        assert!(matches!(
+            update_txs(&mut ledger, h, 2, leader.evolve(), &[double_spend]),
+            Err(LedgerError::NullifierExists)
+        ));
+        assert!(matches!(
+            update_ledger(&mut ledger, h, 2, sender),
+            Err(LedgerError::NullifierExists)
+        ));
+
+        // but the new notes can be spent right away
+        let tx = Tx::new(&[receiver, change], vec![sender.evolve().note()]);
+        update_txs(&mut ledger, h, 2, leader.evolve(), &[tx]).unwrap();
+
+        // and lead once they are part of the stake distribution, two epochs later
+        assert!(matches!(
+            update_ledger(&mut ledger, h, 10, receiver),
+            Err(LedgerError::CommitmentNotFound)
+        ));
+        assert!(epoch_state(&ledger, h, 20.into()).is_eligible_leader(&receiver.commitment()));
+        update_ledger(&mut ledger, h, 20, receiver).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_tx() {
+        let leader = coin(0);
+        let sender = Coin::new([1; 32], [0; 32].into(), 10.into());
+        let receiver = Coin::new([2; 32], [1; 32].into(), 10.into());
+        let (mut ledger, genesis) = ledger(&[leader.commitment(), sender.commitment()]);
+
+        let unbalanced = Tx::new(
+            &[sender],
+            vec![Coin::new([2; 32], [1; 32].into(), 11.into()).note()],
+        );
+        assert!(matches!(
+            update_txs(&mut ledger, genesis, 1, leader, &[unbalanced]),
+            Err(LedgerError::UnbalancedTransaction)
+        ));
+
+        let unknown_note = Tx::new(
+            &[coin(5)],
+            vec![Coin::new([2; 32], [1; 32].into(), 1.into()).note()],
+        );
+        assert!(matches!(
+            update_txs(&mut ledger, genesis, 1, leader, &[unknown_note]),
+            Err(LedgerError::CommitmentNotFound)
+        ));
+
+        // the same note can't be spent twice in the same block
+        let txs = [
+            Tx::new(&[sender], vec![receiver.note()]),
+            Tx::new(&[sender], vec![receiver.evolve().note()]),
+        ];
+        assert!(matches!(
+            update_txs(&mut ledger, genesis, 1, leader, &txs),
+            Err(LedgerError::NullifierExists)
+        ));
+
+        // outputs can't overwrite existing notes
+        let existing = Tx::new(
+            &[sender],
+            vec![
+                Note::new(leader.pk(), leader.nonce(), 1.into()),
+                Coin::new([2; 32], [1; 32].into(), 9.into()).note(),
+            ],
+        );
+        assert!(matches!(
+            update_txs(&mut ledger, genesis, 1, leader, &[existing]),
+            Err(LedgerError::CommitmentExists)
+        ));
+    }
+
     #[test]
     fn test_ledger_state_is_properly_updated_on_reorg() {
         let coin_1 = coin(0);
@@ -584,6 +932,61 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_epoch_history() {
+        let coins = (0..4).map(coin).collect::<Vec<_>>();
+        let (mut ledger, genesis) =
+            ledger(&coins.iter().map(|c| c.commitment()).collect::<Vec<_>>());
+
+        let h_1 = update_ledger(&mut ledger, genesis, 1, coins[0]).unwrap();
+        let h_2 = update_ledger(&mut ledger, h_1, 12, coins[1]).unwrap();
+        // skip epoch 2
+        let h_3 = update_ledger(&mut ledger, h_2, 35, coins[2]).unwrap();
+        // a fork that stays in epoch 1
+        let h_4 = update_ledger(&mut ledger, h_1, 15, coins[3]).unwrap();
+
+        let state = &ledger.states[&h_3];
+        assert_eq!(
+            state.epoch_state_for(0.into()),
+            Some(&ledger.states[&h_1].epoch_state)
+        );
+        assert_eq!(
+            state.epoch_state_for(1.into()),
+            Some(&ledger.states[&h_2].epoch_state)
+        );
+        assert_eq!(state.epoch_state_for(2.into()), None);
+        assert_eq!(state.epoch_state_for(3.into()), Some(&state.epoch_state));
+        assert_eq!(
+            state.epoch_state_for(4.into()),
+            Some(&state.next_epoch_state)
+        );
+        assert_eq!(state.epoch_state_for(5.into()), None);
+
+        // each branch keeps its own history
+        let fork = &ledger.states[&h_4];
+        assert_eq!(
+            fork.epoch_state_for(0.into()),
+            Some(&ledger.states[&h_1].epoch_state)
+        );
+        assert_eq!(fork.epoch_state_for(1.into()), Some(&fork.epoch_state));
+        assert_eq!(fork.epoch_state_for(3.into()), None);
+    }
+
+    #[test]
+    fn test_proof_slot_must_match_block_slot() {
+        let coin = coin(0);
+        let (ledger, genesis) = ledger(&[coin.commitment()]);
+        let proof = coin.to_proof(&epoch_state(&ledger, genesis, 1.into()), 1.into());
+        assert!(matches!(
+            ledger.try_update([1; 32], genesis, 2.into(), &proof, [], &[]),
+            Err(LedgerError::ProofSlotMismatch { block, proof })
+                if block == 2.into() && proof == 1.into()
+        ));
+        assert!(ledger
+            .try_update([1; 32], genesis, 1.into(), &proof, [], &[])
+            .is_ok());
+    }
+
     #[test]
     fn test_evolved_coin_is_eligible_for_leadership() {
         let coin = coin(0);
@@ -645,6 +1048,36 @@ pub mod tests {
         update_ledger(&mut ledger, h_2_0, 21, coin_1.evolve()).unwrap();
     }
 
+    #[test]
+    fn test_pruned_states_are_removed() {
+        let coins = (0..100).map(coin).collect::<Vec<_>>();
+        let (mut ledger, genesis) =
+            ledger(&coins.iter().map(|c| c.commitment()).collect::<Vec<_>>());
+        let engine_config = config().consensus_config;
+        let mut engine = cryptarchia_engine::Cryptarchia::from_genesis(genesis, engine_config);
+        let k = config().consensus_config.security_param as u64;
+
+        let mut parent = genesis;
+        let mut leader = coins[0];
+        for slot in 1..50u64 {
+            let id = update_ledger(&mut ledger, parent, slot, leader).unwrap();
+            engine = engine.receive_block(id, parent, slot.into()).unwrap();
+            // a competing block from a different coin on every slot, which is never extended
+            let fork = update_ledger(&mut ledger, parent, slot, coins[slot as usize]).unwrap();
+            engine = engine.receive_block(fork, parent, slot.into()).unwrap();
+            assert_eq!(engine.tip(), id);
+            leader = leader.evolve();
+            parent = id;
+
+            for pruned in engine.prune_forks(k) {
+                ledger.prune_state_at(&pruned);
+            }
+            assert_eq!(ledger.len(), engine.branches().len());
+            assert!(ledger.len() as u64 <= slot + 1 + k + 1);
+            assert!(ledger.state(&engine.tip()).is_some());
+        }
+    }
+
     #[test]
     fn test_orphan_proof_import() {
         let coin = coin(0);
@@ -723,6 +1156,27 @@ pub mod tests {
 
         // an imported proof that uses a coin that was already used in the base branch should not be allowed
         let header_1 = update_ledger(&mut ledger, genesis, 1, coin).unwrap();
+        let proof = |coin: Coin, slot: u64| {
+            coin.to_proof(&epoch_state(&ledger, genesis, slot.into()), slot.into())
+        };
+        assert_eq!(
+            ledger.importable_orphan_proofs(
+                header_1,
+                2.into(),
+                &proof(coin_new_new, 2),
+                [(fork_1, proof(coin, 1)), (fork_2, proof(coin_new, 2))],
+            ),
+            vec![fork_2]
+        );
+        // proofs using the same coin as the block leader can't be imported either
+        assert!(ledger
+            .importable_orphan_proofs(
+                genesis,
+                2.into(),
+                &proof(coin, 2),
+                [(fork_1, proof(coin, 1))]
+            )
+            .is_empty());
         assert!(matches!(
             update_orphans(
                 &mut ledger,
@@ -1,6 +1,6 @@
 use crate::utils::serialize_bytes_newtype;
 
-#[derive(Clone, Debug, Eq, PartialEq, Copy)]
+#[derive(Clone, Debug, Eq, PartialEq, Copy, Hash)]
 pub struct Nonce([u8; 32]);
 impl From<[u8; 32]> for Nonce {
     fn from(nonce: [u8; 32]) -> Self {
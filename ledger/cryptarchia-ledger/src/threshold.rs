@@ -0,0 +1,264 @@
+use crate::Value;
+use num_bigint::BigUint;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+// Fractional bits of the fixed point numbers used in the computation.
+// This is more than the 256 bits of the VRF output, so that rounding errors in the intermediate
+// steps do not affect the threshold in practice. Since only integer operations are involved,
+// results are the same on every platform regardless of the precision.
+const PRECISION: u64 = 320;
+const VRF_OUTPUT_BITS: u64 = 256;
+// Thresholds only depend on the config and on the stake distribution, which does not change
+// often, but are needed by every coin in every slot, so they are cached
+const MAX_CACHED_THRESHOLDS: usize = 1024;
+
+type ThresholdCache = Mutex<HashMap<(u64, Value, Value), BigUint>>;
+
+fn one() -> BigUint {
+    BigUint::from(1u8) << PRECISION
+}
+
+fn mul(a: &BigUint, b: &BigUint) -> BigUint {
+    (a * b) >> PRECISION
+}
+
+fn div(a: &BigUint, b: &BigUint) -> BigUint {
+    (a << PRECISION) / b
+}
+
+// Exact fixed point representation of a float in [0, 1], truncated to `PRECISION` bits
+fn from_f64(x: f64) -> BigUint {
+    assert!((0.0..=1.0).contains(&x), "{x} is not in [0, 1]");
+    let bits = x.to_bits();
+    let biased_exponent = ((bits >> 52) & 0x7ff) as i64;
+    let fraction = bits & ((1 << 52) - 1);
+    // x = mantissa * 2^(exponent - 1075), subnormal numbers have no implicit leading bit
+    let (mantissa, exponent) = if biased_exponent == 0 {
+        (fraction, 1)
+    } else {
+        (fraction | (1 << 52), biased_exponent)
+    };
+    let shift = exponent - 1075 + PRECISION as i64;
+    if shift >= 0 {
+        BigUint::from(mantissa) << shift as u64
+    } else {
+        BigUint::from(mantissa) >> (-shift) as u64
+    }
+}
+
+// atanh(z) = z + z^3/3 + z^5/5 + ..., for 0 <= z <= 1/3
+fn atanh(z: &BigUint) -> BigUint {
+    let z_squared = mul(z, z);
+    let mut power = z.clone();
+    let mut sum = BigUint::ZERO;
+    let mut k = 1u32;
+    while power != BigUint::ZERO {
+        sum += &power / k;
+        power = mul(&power, &z_squared);
+        k += 2;
+    }
+    sum
+}
+
+// ln(2) = 2 * atanh(1/3)
+fn ln_2() -> BigUint {
+    atanh(&(one() / 3u8)) << 1
+}
+
+// -ln(q), for 0 < q <= 1
+fn neg_ln(q: &BigUint) -> BigUint {
+    if *q == one() {
+        return BigUint::ZERO;
+    }
+    // q = q' * 2^-n with q' in [1/2, 1), so that the series converges quickly
+    let n = PRECISION - q.bits();
+    let q = q << n;
+    // ln(q') = -2 * atanh((1 - q') / (1 + q'))
+    let z = div(&(one() - &q), &(one() + &q));
+    ln_2() * n + (atanh(&z) << 1)
+}
+
+// exp(-y), for y >= 0
+fn exp_neg(y: &BigUint) -> BigUint {
+    // y = n * ln(2) + r with r in [0, ln(2)), so that exp(-y) = 2^-n / exp(r)
+    let ln_2 = ln_2();
+    let n = y / &ln_2;
+    if n >= BigUint::from(PRECISION) {
+        return BigUint::ZERO;
+    }
+    let r = y - &ln_2 * &n;
+    // exp(r) = 1 + r + r^2/2! + ...
+    let mut term = one();
+    let mut sum = BigUint::ZERO;
+    let mut k = 0u32;
+    while term != BigUint::ZERO {
+        sum += &term;
+        k += 1;
+        term = mul(&term, &r) / k;
+    }
+    let n = u64::try_from(n).expect("n is smaller than PRECISION");
+    div(&one(), &sum) >> n
+}
+
+/// Threshold the VRF output, read as a 256-bit big-endian integer, has to be below for a coin
+/// to win the slot lottery: `2^256 * phi(f, alpha)`, where `phi(f, alpha) = 1 - (1 - f)^alpha`,
+/// `f` is the active slot coefficient and `alpha` the relative stake of the coin.
+pub(crate) fn leadership_threshold(
+    active_slot_coeff: f64,
+    value: Value,
+    total_stake: Value,
+) -> BigUint {
+    static CACHE: OnceLock<ThresholdCache> = OnceLock::new();
+    let key = (active_slot_coeff.to_bits(), value, total_stake);
+    let mut cache = CACHE
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(threshold) = cache.get(&key) {
+        return threshold.clone();
+    }
+    let threshold = compute_threshold(active_slot_coeff, value, total_stake);
+    if cache.len() >= MAX_CACHED_THRESHOLDS {
+        cache.clear();
+    }
+    cache.insert(key, threshold.clone());
+    threshold
+}
+
+fn compute_threshold(active_slot_coeff: f64, value: Value, total_stake: Value) -> BigUint {
+    let (value, total_stake) = (u32::from(value), u32::from(total_stake));
+    if value == 0 || total_stake == 0 {
+        return BigUint::ZERO;
+    }
+    let q = one() - from_f64(active_slot_coeff);
+    if q == BigUint::ZERO {
+        // every slot is filled, no matter the stake
+        return BigUint::from(1u8) << VRF_OUTPUT_BITS;
+    }
+    // (1 - f)^alpha = exp(-alpha * -ln(1 - f))
+    let y = neg_ln(&q) * value / total_stake;
+    let phi = one() - exp_neg(&y);
+    phi >> (PRECISION - VRF_OUTPUT_BITS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::Blake2b;
+    use blake2::Digest;
+
+    fn threshold(active_slot_coeff: f64, value: u32, total_stake: u32) -> BigUint {
+        compute_threshold(active_slot_coeff, value.into(), total_stake.into())
+    }
+
+    #[test]
+    fn test_threshold_vectors() {
+        // floor(2^256 * (1 - (1 - f)^(value / total_stake))), computed with 250 significant digits
+        // using python's decimal module and the exact value of f as a binary float
+        let vectors = [
+            (
+                0.05,
+                10,
+                1000,
+                "00219b614feb9ebe441731f88cb46c4aba70e0d43e3c516054b0bc6e6ba86c82",
+            ),
+            (
+                0.05,
+                1,
+                1,
+                "0ccccccccccccd00000000000000000000000000000000000000000000000000",
+            ),
+            (
+                0.5,
+                1,
+                3,
+                "34d00ad6148e1bea7d3332a5e11d908733fc4ef53afabf506eb729f98367df0d",
+            ),
+            (
+                0.9,
+                7,
+                10,
+                "ccebd95106d8833384c9b9448291d9ed3c9eb111079c61f0aa4b74def236e8a5",
+            ),
+            (
+                0.05,
+                1,
+                u32::MAX,
+                "000000000d218eadf2d7f4395aa0de0633737a30eac60142eb727132353efe4c",
+            ),
+            (
+                0.999,
+                1,
+                2,
+                "f7e791d8afe2c5d875de0cdca6ba6e24691ddc0c322ada9d1097e7be01be7230",
+            ),
+            (
+                0.5,
+                1,
+                1,
+                "8000000000000000000000000000000000000000000000000000000000000000",
+            ),
+            (
+                1e-6,
+                123456,
+                1000000,
+                "000002123d5df4642ca662e12490686a6a44f72fd6b4edce55a744c9396a08d5",
+            ),
+        ];
+
+        for (f, value, total_stake, expected) in vectors {
+            let expected = BigUint::parse_bytes(expected.as_bytes(), 16).unwrap();
+            let threshold = threshold(f, value, total_stake);
+            // allow for the truncation of the last bit
+            let error = if threshold > expected {
+                &threshold - &expected
+            } else {
+                &expected - &threshold
+            };
+            assert!(
+                error <= BigUint::from(1u8),
+                "f={f} value={value} total_stake={total_stake}: {threshold:x} != {expected:x}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_threshold_edge_cases() {
+        let max = BigUint::from(1u8) << VRF_OUTPUT_BITS;
+        assert_eq!(threshold(1.0, 1, 10), max);
+        assert_eq!(threshold(0.0, 10, 10), BigUint::ZERO);
+        assert_eq!(threshold(0.5, 0, 10), BigUint::ZERO);
+        // the threshold grows with the stake
+        let thresholds = (1..=10).map(|value| threshold(0.3, value, 10));
+        assert!(thresholds
+            .collect::<Vec<_>>()
+            .windows(2)
+            .all(|w| w[0] < w[1] && w[1] < max));
+    }
+
+    #[test]
+    fn test_threshold_statistics() {
+        let (f, value, total_stake) = (0.2, 3, 10);
+        let threshold = threshold(f, value, total_stake);
+        let p = 1.0 - (1.0 - f).powf(f64::from(value) / f64::from(total_stake));
+
+        // hashes are uniformly distributed over the 256-bit outputs, like the VRF outputs
+        let n = 200_000;
+        let wins = (0..n as u64)
+            .filter(|i| {
+                let output: [u8; 32] = Blake2b::new_with_prefix(i.to_be_bytes()).finalize().into();
+                BigUint::from_bytes_be(&output) < threshold
+            })
+            .count();
+
+        // 5 standard deviations from the expected rate
+        let margin_of_error = 5.0 * (p * (1.0 - p) / n as f64).sqrt();
+        let rate = wins as f64 / n as f64;
+        assert!(
+            (rate - p).abs() < margin_of_error,
+            "{rate} != {p}, err={} > {margin_of_error}",
+            (rate - p).abs()
+        );
+    }
+}
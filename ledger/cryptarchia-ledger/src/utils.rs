@@ -20,3 +20,20 @@ macro_rules! serialize_bytes_newtype {
 }
 
 pub(crate) use serialize_bytes_newtype;
+
+/// Serde helpers for byte arrays used as struct fields, e.g. with `#[serde(with = "bytes_array")]`
+#[cfg(feature = "serde")]
+pub(crate) mod bytes_array {
+    pub fn serialize<const N: usize, S: serde::Serializer>(
+        src: &[u8; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        nomos_utils::serde::serialize_bytes_array(*src, serializer)
+    }
+
+    pub fn deserialize<'de, const N: usize, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<[u8; N], D::Error> {
+        nomos_utils::serde::deserialize_bytes_array(deserializer)
+    }
+}
@@ -0,0 +1,130 @@
+use crate::Nonce;
+use cryptarchia_engine::Slot;
+use schnorrkel::{
+    context::signing_context,
+    vrf::{VRFPreOut, VRFProof},
+    ExpansionMode, Keypair, MiniSecretKey,
+};
+
+const LEADER_VRF_CONTEXT: &[u8] = b"cryptarchia-leader-vrf";
+const LOTTERY_CONTEXT: &[u8] = b"cryptarchia-lottery";
+
+pub type SecretKey = [u8; 32];
+
+#[derive(Clone, Debug, Eq, PartialEq, Copy, Hash)]
+pub struct PublicKey([u8; 32]);
+
+/// Output point of the leader VRF, the lottery outcome is derived from it
+#[derive(Clone, Debug, Eq, PartialEq, Copy, Hash)]
+pub struct VrfOutput([u8; 32]);
+
+#[derive(Clone, Debug, Eq, PartialEq, Copy, Hash)]
+pub struct VrfProof([u8; 64]);
+
+pub(crate) fn keypair(sk: &SecretKey) -> Keypair {
+    MiniSecretKey::from_bytes(sk)
+        .expect("secret key has the correct length")
+        .expand_to_keypair(ExpansionMode::Ed25519)
+}
+
+fn leader_input(epoch_nonce: Nonce, slot: Slot) -> [u8; 40] {
+    let mut input = [0; 40];
+    input[..32].copy_from_slice(epoch_nonce.as_ref());
+    input[32..].copy_from_slice(&slot.to_be_bytes());
+    input
+}
+
+pub(crate) fn public_key(sk: &SecretKey) -> PublicKey {
+    PublicKey(keypair(sk).public.to_bytes())
+}
+
+/// Evaluate the leader VRF without building a proof, which is only needed
+/// when the lottery has been won.
+pub(crate) fn evaluate(sk: &SecretKey, epoch_nonce: Nonce, slot: Slot) -> [u8; 32] {
+    let transcript = signing_context(LEADER_VRF_CONTEXT).bytes(&leader_input(epoch_nonce, slot));
+    keypair(sk)
+        .vrf_create_hash(transcript)
+        .make_bytes(LOTTERY_CONTEXT)
+}
+
+pub(crate) fn prove(sk: &SecretKey, epoch_nonce: Nonce, slot: Slot) -> (VrfOutput, VrfProof) {
+    let transcript = signing_context(LEADER_VRF_CONTEXT).bytes(&leader_input(epoch_nonce, slot));
+    let (in_out, proof, _) = keypair(sk).vrf_sign(transcript);
+    (
+        VrfOutput(in_out.to_preout().to_bytes()),
+        VrfProof(proof.to_bytes()),
+    )
+}
+
+/// Check the VRF proof and return the same value `evaluate` would have
+/// returned to the owner of the secret key, or `None` if the proof is invalid.
+pub(crate) fn verify(
+    pk: &PublicKey,
+    epoch_nonce: Nonce,
+    slot: Slot,
+    output: &VrfOutput,
+    proof: &VrfProof,
+) -> Option<[u8; 32]> {
+    let pk = schnorrkel::PublicKey::from_bytes(&pk.0).ok()?;
+    let output = VRFPreOut::from_bytes(&output.0).ok()?;
+    let proof = VRFProof::from_bytes(&proof.0).ok()?;
+    let transcript = signing_context(LEADER_VRF_CONTEXT).bytes(&leader_input(epoch_nonce, slot));
+    let (in_out, _) = pk.vrf_verify(transcript, &output, &proof).ok()?;
+    Some(in_out.make_bytes(LOTTERY_CONTEXT))
+}
+
+impl From<[u8; 32]> for PublicKey {
+    fn from(pk: [u8; 32]) -> Self {
+        Self(pk)
+    }
+}
+
+impl AsRef<[u8]> for PublicKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for VrfOutput {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for VrfProof {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+// ----------- serialization
+use crate::utils::serialize_bytes_newtype;
+
+serialize_bytes_newtype!(PublicKey);
+serialize_bytes_newtype!(VrfOutput);
+serialize_bytes_newtype!(VrfProof);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vrf_proof_verification() {
+        let sk = [1; 32];
+        let pk = public_key(&sk);
+        let nonce = Nonce::from([2; 32]);
+        let (output, proof) = prove(&sk, nonce, 3.into());
+
+        assert_eq!(
+            verify(&pk, nonce, 3.into(), &output, &proof),
+            Some(evaluate(&sk, nonce, 3.into()))
+        );
+        // the proof is bound to the slot, the epoch nonce and the key
+        assert_eq!(verify(&pk, nonce, 4.into(), &output, &proof), None);
+        assert_eq!(verify(&pk, [0; 32].into(), 3.into(), &output, &proof), None);
+        assert_eq!(
+            verify(&public_key(&[0; 32]), nonce, 3.into(), &output, &proof),
+            None
+        );
+    }
+}
@@ -3,7 +3,10 @@ use std::{fmt::Debug, hash::Hash};
 use axum::{
     extract::{Query, State},
     http::HeaderValue,
-    response::{IntoResponse, Response},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
     routing, Json, Router, Server,
 };
 use hyper::{
@@ -12,6 +15,7 @@ use hyper::{
 };
 use overwatch_rs::overwatch::handle::OverwatchHandle;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio_stream::{wrappers::BroadcastStream, StreamExt};
 use tower_http::{
     cors::{Any, CorsLayer},
     trace::TraceLayer,
@@ -29,7 +33,7 @@ use nomos_network::backends::libp2p::Libp2p as NetworkBackend;
 use nomos_storage::backends::StorageSerde;
 
 use nomos_api::{
-    http::{cl, consensus, da, libp2p, mempool, metrics, storage},
+    http::{cl, consensus, cryptarchia, da, libp2p, mempool, metrics, storage},
     Backend,
 };
 
@@ -122,8 +126,37 @@ where
             .route("/da/blobs", routing::post(da_blobs))
             .route("/cl/metrics", routing::get(cl_metrics::<T>))
             .route("/cl/status", routing::post(cl_status::<T>))
+            // consensus endpoints only answer for the consensus service the node is running
             .route("/carnot/info", routing::get(carnot_info::<T, S, SIZE>))
             .route("/carnot/blocks", routing::get(carnot_blocks::<T, S, SIZE>))
+            .route(
+                "/carnot/committed",
+                routing::get(carnot_committed_blocks::<T, S, SIZE>),
+            )
+            .route(
+                "/carnot/equivocations",
+                routing::get(carnot_equivocations::<T, S, SIZE>),
+            )
+            .route(
+                "/cryptarchia/info",
+                routing::get(cryptarchia_info::<T, S, SIZE>),
+            )
+            .route(
+                "/cryptarchia/headers",
+                routing::get(cryptarchia_headers::<T, S, SIZE>),
+            )
+            .route(
+                "/cryptarchia/wallet",
+                routing::get(cryptarchia_wallet::<T, S, SIZE>),
+            )
+            .route(
+                "/cryptarchia/events",
+                routing::get(cryptarchia_events::<T, S, SIZE>),
+            )
+            .route(
+                "/cryptarchia/epoch",
+                routing::get(cryptarchia_epoch::<T, S, SIZE>),
+            )
             .route("/network/info", routing::get(libp2p_info))
             .route("/storage/block", routing::post(block::<S, T>))
             .route("/mempool/add/tx", routing::post(add_tx::<T>))
@@ -282,6 +315,221 @@ where
     make_request_and_return_response!(consensus::carnot_blocks::<Tx, SS, SIZE>(&store, from, to))
 }
 
+#[derive(Deserialize)]
+struct CommittedQueryParams {
+    limit: Option<usize>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/carnot/committed",
+    responses(
+        (status = 200, description = "Query the ids of the latest committed blocks", body = Vec<HeaderId>),
+        (status = 500, description = "Internal server error", body = String),
+    )
+)]
+async fn carnot_committed_blocks<Tx, SS, const SIZE: usize>(
+    State(handle): State<OverwatchHandle>,
+    Query(query): Query<CommittedQueryParams>,
+) -> Response
+where
+    Tx: Transaction + Clone + Debug + Hash + Serialize + DeserializeOwned + Send + Sync + 'static,
+    <Tx as Transaction>::Hash: std::cmp::Ord + Debug + Send + Sync + 'static,
+    SS: StorageSerde + Send + Sync + 'static,
+{
+    let CommittedQueryParams { limit } = query;
+    make_request_and_return_response!(consensus::carnot_committed_blocks::<Tx, SS, SIZE>(
+        &handle, limit
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/carnot/equivocations",
+    responses(
+        (status = 200, description = "Query the equivocations detected so far", body = Vec<carnot_engine::Equivocation<HeaderId>>),
+        (status = 500, description = "Internal server error", body = String),
+    )
+)]
+async fn carnot_equivocations<Tx, SS, const SIZE: usize>(
+    State(handle): State<OverwatchHandle>,
+) -> Response
+where
+    Tx: Transaction + Clone + Debug + Hash + Serialize + DeserializeOwned + Send + Sync + 'static,
+    <Tx as Transaction>::Hash: std::cmp::Ord + Debug + Send + Sync + 'static,
+    SS: StorageSerde + Send + Sync + 'static,
+{
+    make_request_and_return_response!(consensus::carnot_equivocations::<Tx, SS, SIZE>(&handle))
+}
+
+#[utoipa::path(
+    get,
+    path = "/cryptarchia/info",
+    responses(
+        (status = 200, description = "Query the cryptarchia information", body = cryptarchia_consensus::CryptarchiaInfo),
+        (status = 500, description = "Internal server error", body = String),
+    )
+)]
+async fn cryptarchia_info<Tx, SS, const SIZE: usize>(
+    State(handle): State<OverwatchHandle>,
+) -> Response
+where
+    Tx: Transaction
+        + Clone
+        + Debug
+        + Eq
+        + Hash
+        + Serialize
+        + DeserializeOwned
+        + Send
+        + Sync
+        + 'static,
+    <Tx as Transaction>::Hash: std::cmp::Ord + Debug + Send + Sync + 'static,
+    SS: StorageSerde + Send + Sync + 'static,
+{
+    make_request_and_return_response!(cryptarchia::cryptarchia_info::<Tx, SS, SIZE>(&handle))
+}
+
+#[utoipa::path(
+    get,
+    path = "/cryptarchia/headers",
+    responses(
+        (status = 200, description = "Query the ids of the blocks in the local chain, newest first", body = Vec<HeaderId>),
+        (status = 500, description = "Internal server error", body = String),
+    )
+)]
+async fn cryptarchia_headers<Tx, SS, const SIZE: usize>(
+    State(store): State<OverwatchHandle>,
+    Query(query): Query<QueryParams>,
+) -> Response
+where
+    Tx: Transaction
+        + Clone
+        + Debug
+        + Eq
+        + Hash
+        + Serialize
+        + DeserializeOwned
+        + Send
+        + Sync
+        + 'static,
+    <Tx as Transaction>::Hash: std::cmp::Ord + Debug + Send + Sync + 'static,
+    SS: StorageSerde + Send + Sync + 'static,
+{
+    let QueryParams { from, to } = query;
+    make_request_and_return_response!(cryptarchia::cryptarchia_headers::<Tx, SS, SIZE>(
+        &store, from, to
+    ))
+}
+
+#[derive(Deserialize)]
+struct EpochQueryParams {
+    epoch: u32,
+    block: Option<HeaderId>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/cryptarchia/epoch",
+    responses(
+        (status = 200, description = "Query the epoch state used to verify leader proofs in an epoch, as seen by the given block (the tip by default)", body = Option<cryptarchia_ledger::EpochState>),
+        (status = 500, description = "Internal server error", body = String),
+    )
+)]
+async fn cryptarchia_epoch<Tx, SS, const SIZE: usize>(
+    State(handle): State<OverwatchHandle>,
+    Query(query): Query<EpochQueryParams>,
+) -> Response
+where
+    Tx: Transaction
+        + Clone
+        + Debug
+        + Eq
+        + Hash
+        + Serialize
+        + DeserializeOwned
+        + Send
+        + Sync
+        + 'static,
+    <Tx as Transaction>::Hash: std::cmp::Ord + Debug + Send + Sync + 'static,
+    SS: StorageSerde + Send + Sync + 'static,
+{
+    let EpochQueryParams { epoch, block } = query;
+    make_request_and_return_response!(cryptarchia::cryptarchia_epoch_state::<Tx, SS, SIZE>(
+        &handle,
+        epoch.into(),
+        block
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/cryptarchia/wallet",
+    responses(
+        (status = 200, description = "Query the balance of the leader coins owned by this node", body = cryptarchia_consensus::wallet::WalletInfo),
+        (status = 500, description = "Internal server error", body = String),
+    )
+)]
+async fn cryptarchia_wallet<Tx, SS, const SIZE: usize>(
+    State(handle): State<OverwatchHandle>,
+) -> Response
+where
+    Tx: Transaction
+        + Clone
+        + Debug
+        + Eq
+        + Hash
+        + Serialize
+        + DeserializeOwned
+        + Send
+        + Sync
+        + 'static,
+    <Tx as Transaction>::Hash: std::cmp::Ord + Debug + Send + Sync + 'static,
+    SS: StorageSerde + Send + Sync + 'static,
+{
+    make_request_and_return_response!(cryptarchia::cryptarchia_wallet::<Tx, SS, SIZE>(&handle))
+}
+
+#[utoipa::path(
+    get,
+    path = "/cryptarchia/events",
+    responses(
+        (status = 200, description = "Stream of changes to the local chain as server-sent events, each one carrying a JSON encoded chain event", body = cryptarchia_consensus::events::ChainEvent),
+        (status = 500, description = "Internal server error", body = String),
+    )
+)]
+async fn cryptarchia_events<Tx, SS, const SIZE: usize>(
+    State(handle): State<OverwatchHandle>,
+) -> Response
+where
+    Tx: Transaction
+        + Clone
+        + Debug
+        + Eq
+        + Hash
+        + Serialize
+        + DeserializeOwned
+        + Send
+        + Sync
+        + 'static,
+    <Tx as Transaction>::Hash: std::cmp::Ord + Debug + Send + Sync + 'static,
+    SS: StorageSerde + Send + Sync + 'static,
+{
+    match cryptarchia::cryptarchia_chain_events::<Tx, SS, SIZE>(&handle).await {
+        Ok(events) => {
+            // end the stream if the client falls behind, so that it reconnects and catches up
+            // through the headers endpoint instead of silently missing events
+            let stream = BroadcastStream::new(events)
+                .map_while(Result::ok)
+                .map(|event| Event::default().json_data(event));
+            Sse::new(stream)
+                .keep_alive(KeepAlive::default())
+                .into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
 #[utoipa::path(
     get,
     path = "/network/info",
@@ -6,7 +6,8 @@ use std::{
 
 use crate::api::AxumBackend;
 use crate::DataAvailability;
-use crate::{Carnot, Tx, Wire, MB16};
+use crate::{Carnot, Cryptarchia, Tx, Wire, MB16};
+use carnot_consensus::crypto::node_id_from_private_key;
 use clap::{Parser, ValueEnum};
 use color_eyre::eyre::{self, eyre, Result};
 use hex::FromHex;
@@ -84,6 +85,19 @@ pub struct ConsensusArgs {
 
     #[clap(long = "consensus-timeout-secs", env = "CONSENSUS_TIMEOUT_SECS")]
     consensus_timeout_secs: Option<String>,
+
+    /// Factor the view timeout grows by after each consecutive timeout
+    #[clap(
+        long = "consensus-timeout-multiplier",
+        env = "CONSENSUS_TIMEOUT_MULTIPLIER"
+    )]
+    consensus_timeout_multiplier: Option<f64>,
+
+    #[clap(
+        long = "consensus-max-timeout-secs",
+        env = "CONSENSUS_MAX_TIMEOUT_SECS"
+    )]
+    consensus_max_timeout_secs: Option<String>,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -91,6 +105,11 @@ pub struct OverlayArgs {
     #[clap(long = "overlay-nodes", env = "OVERLAY_NODES", num_args = 1.., value_delimiter = ',')]
     pub overlay_nodes: Option<Vec<String>>,
 
+    /// Consensus private keys of the overlay nodes, for test deployments where they are all known.
+    /// Node ids are derived from them, and take precedence over `overlay_nodes`.
+    #[clap(long = "overlay-node-keys", env = "OVERLAY_NODE_KEYS", num_args = 1.., value_delimiter = ',')]
+    pub overlay_node_keys: Option<Vec<String>>,
+
     #[clap(long = "overlay-leader", env = "OVERLAY_LEADER")]
     pub overlay_leader: Option<String>,
 
@@ -119,16 +138,27 @@ pub struct MetricsArgs {
     pub with_metrics: bool,
 }
 
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+pub enum ConsensusType {
+    #[default]
+    Carnot,
+    Cryptarchia,
+}
+
+/// Node configuration, generic over the settings of the consensus service.
+/// It defaults to Carnot, see [`CryptarchiaConfig`] for nodes running Cryptarchia.
 #[derive(Deserialize, Debug, Clone, Serialize)]
-pub struct Config {
+pub struct Config<Consensus = <Carnot as ServiceData>::Settings> {
     pub log: <Logger as ServiceData>::Settings,
     pub network: <NetworkService<NetworkBackend> as ServiceData>::Settings,
     pub http: <ApiService<AxumBackend<Tx, Wire, MB16>> as ServiceData>::Settings,
-    pub consensus: <Carnot as ServiceData>::Settings,
+    pub consensus: Consensus,
     pub da: <DataAvailability as ServiceData>::Settings,
 }
 
-impl Config {
+pub type CryptarchiaConfig = Config<<Cryptarchia as ServiceData>::Settings>;
+
+impl<Consensus> Config<Consensus> {
     pub fn update_log(mut self, log_args: LogArgs) -> Result<Self> {
         let LogArgs {
             backend,
@@ -222,10 +252,25 @@ impl Config {
         Ok(self)
     }
 
+    pub fn update_da(mut self, da_args: DaArgs) -> Result<Self> {
+        let DaArgs { da_voter } = da_args;
+
+        if let Some(voter) = da_voter {
+            let bytes = <[u8; 32]>::from_hex(voter)?;
+            self.da.da_protocol.voter = bytes;
+        }
+
+        Ok(self)
+    }
+}
+
+impl Config {
     pub fn update_consensus(mut self, consensus_args: ConsensusArgs) -> Result<Self> {
         let ConsensusArgs {
             consensus_priv_key,
             consensus_timeout_secs,
+            consensus_timeout_multiplier,
+            consensus_max_timeout_secs,
         } = consensus_args;
 
         if let Some(private_key) = consensus_priv_key {
@@ -238,12 +283,22 @@ impl Config {
             self.consensus.timeout = Duration::from_secs(secs);
         }
 
+        if let Some(multiplier) = consensus_timeout_multiplier {
+            self.consensus.backoff.multiplier = multiplier;
+        }
+
+        if let Some(max_timeout) = consensus_max_timeout_secs {
+            let secs = max_timeout.parse::<u64>()?;
+            self.consensus.backoff.max_timeout = Duration::from_secs(secs);
+        }
+
         Ok(self)
     }
 
     pub fn update_overlay(mut self, overlay_args: OverlayArgs) -> Result<Self> {
         let OverlayArgs {
             overlay_nodes,
+            overlay_node_keys,
             overlay_leader,
             overlay_number_of_committees,
             overlay_super_majority_threshold,
@@ -260,6 +315,17 @@ impl Config {
                 .collect::<Result<Vec<_>, eyre::Report>>()?;
         }
 
+        if let Some(keys) = overlay_node_keys {
+            self.consensus.overlay_settings.nodes = keys
+                .iter()
+                .map(|k| {
+                    <[u8; 32]>::from_hex(k)
+                        .map_err(|e| eyre::eyre!("Failed to decode hex: {}", e))
+                        .map(node_id_from_private_key)
+                })
+                .collect::<Result<Vec<_>, eyre::Report>>()?;
+        }
+
         if let Some(leader) = overlay_leader {
             let bytes = <[u8; 32]>::from_hex(leader)?;
             self.consensus.overlay_settings.current_leader = bytes.into();
@@ -276,15 +342,4 @@ impl Config {
 
         Ok(self)
     }
-
-    pub fn update_da(mut self, da_args: DaArgs) -> Result<Self> {
-        let DaArgs { da_voter } = da_args;
-
-        if let Some(voter) = da_voter {
-            let bytes = <[u8; 32]>::from_hex(voter)?;
-            self.da.da_protocol.voter = bytes;
-        }
-
-        Ok(self)
-    }
 }
@@ -1,10 +1,11 @@
 pub mod api;
 mod config;
-mod tx;
 
 use carnot_consensus::network::adapters::libp2p::Libp2pAdapter as ConsensusNetworkAdapter;
 use carnot_engine::overlay::{RandomBeaconState, RoundRobin, TreeOverlay};
 use color_eyre::eyre::Result;
+use cryptarchia_consensus::network::adapters::libp2p::LibP2pAdapter as CryptarchiaNetworkAdapter;
+use cryptarchia_consensus::CryptarchiaConsensus;
 use full_replication::Certificate;
 use full_replication::{AbsoluteNumber, Attestation, Blob, FullReplication};
 #[cfg(feature = "metrics")]
@@ -39,7 +40,8 @@ use nomos_storage::{
 };
 
 pub use config::{
-    Config, ConsensusArgs, DaArgs, HttpArgs, LogArgs, MetricsArgs, NetworkArgs, OverlayArgs,
+    Config, ConsensusArgs, ConsensusType, CryptarchiaConfig, DaArgs, HttpArgs, LogArgs,
+    MetricsArgs, NetworkArgs, OverlayArgs,
 };
 use nomos_core::{
     da::certificate::select::FillSize as FillSizeWithBlobsCertificate,
@@ -51,7 +53,8 @@ use overwatch_derive::*;
 use overwatch_rs::services::handle::ServiceHandle;
 use serde::{de::DeserializeOwned, Serialize};
 
-pub use tx::Tx;
+pub use nomos_core::tx::carnot::Tx;
+pub use nomos_core::tx::cryptarchia::Tx as CryptarchiaTx;
 
 pub const CL_TOPIC: &str = "cl";
 pub const DA_TOPIC: &str = "da";
@@ -76,6 +79,24 @@ pub type Carnot = CarnotConsensus<
     SledBackend<Wire>,
 >;
 
+pub type Cryptarchia = CryptarchiaConsensus<
+    CryptarchiaNetworkAdapter<CryptarchiaTx, Certificate>,
+    MockPool<HeaderId, CryptarchiaTx, <CryptarchiaTx as Transaction>::Hash>,
+    MempoolNetworkAdapter<CryptarchiaTx, <CryptarchiaTx as Transaction>::Hash>,
+    MockPool<
+        HeaderId,
+        Certificate,
+        <<Certificate as certificate::Certificate>::Blob as blob::Blob>::Hash,
+    >,
+    MempoolNetworkAdapter<
+        Certificate,
+        <<Certificate as certificate::Certificate>::Blob as blob::Blob>::Hash,
+    >,
+    FillSizeWithTx<MB16, CryptarchiaTx>,
+    FillSizeWithBlobsCertificate<MB16, Certificate>,
+    SledBackend<Wire>,
+>;
+
 pub type DataAvailability = DataAvailabilityService<
     FullReplication<AbsoluteNumber<Attestation, Certificate>>,
     BlobCache<<Blob as nomos_core::da::blob::Blob>::Hash, Blob>,
@@ -105,16 +126,42 @@ pub struct Nomos {
     system_sig: ServiceHandle<SystemSig>,
 }
 
+/// Same services as [`Nomos`], running Cryptarchia instead of Carnot.
+/// Transactions are Cryptarchia ledger transfers, as the consensus validates them.
+#[derive(Services)]
+pub struct NomosCryptarchia {
+    logging: ServiceHandle<Logger>,
+    network: ServiceHandle<NetworkService<NetworkBackend>>,
+    cl_mempool:
+        ServiceHandle<Mempool<CryptarchiaTx, <CryptarchiaTx as Transaction>::Hash, TxDiscriminant>>,
+    da_mempool: ServiceHandle<
+        Mempool<
+            Certificate,
+            <<Certificate as certificate::Certificate>::Blob as blob::Blob>::Hash,
+            CertDiscriminant,
+        >,
+    >,
+    cryptarchia: ServiceHandle<Cryptarchia>,
+    http: ServiceHandle<ApiService<AxumBackend<CryptarchiaTx, Wire, MB16>>>,
+    da: ServiceHandle<DataAvailability>,
+    storage: ServiceHandle<StorageService<SledBackend<Wire>>>,
+    #[cfg(feature = "metrics")]
+    metrics: ServiceHandle<Metrics>,
+    system_sig: ServiceHandle<SystemSig>,
+}
+
 pub struct Wire;
 
 impl StorageSerde for Wire {
     type Error = wire::Error;
 
+    // stored values are produced locally, and blocks or account states can be larger than
+    // the default limit meant for network messages
     fn serialize<T: Serialize>(value: T) -> Bytes {
-        wire::serialize(&value).unwrap().into()
+        wire::serialize_with_limit(&value, u64::MAX).unwrap().into()
     }
 
     fn deserialize<T: DeserializeOwned>(buff: Bytes) -> Result<T, Self::Error> {
-        wire::deserialize(&buff)
+        wire::deserialize_with_limit(&buff, u64::MAX)
     }
 }
@@ -2,7 +2,8 @@ use full_replication::{Blob, Certificate};
 #[cfg(feature = "metrics")]
 use nomos_metrics::MetricsSettings;
 use nomos_node::{
-    Config, ConsensusArgs, DaArgs, HttpArgs, LogArgs, MetricsArgs, NetworkArgs, Nomos,
+    Config, ConsensusArgs, ConsensusType, CryptarchiaConfig, CryptarchiaTx, DaArgs, HttpArgs,
+    LogArgs, MetricsArgs, NetworkArgs, Nomos, NomosCryptarchia, NomosCryptarchiaServiceSettings,
     NomosServiceSettings, OverlayArgs, Tx,
 };
 
@@ -15,6 +16,7 @@ use nomos_core::{
 
 use nomos_mempool::network::adapters::libp2p::Settings as AdapterSettings;
 
+use nomos_metrics::NomosRegistry;
 use overwatch_rs::overwatch::*;
 
 const DEFAULT_DB_PATH: &str = "./db";
@@ -24,6 +26,9 @@ const DEFAULT_DB_PATH: &str = "./db";
 struct Args {
     /// Path for a yaml-encoded network config file
     config: std::path::PathBuf,
+    /// Consensus protocol run by the node, the config file must match it
+    #[clap(long = "consensus", env = "CONSENSUS", value_enum, default_value_t)]
+    consensus: ConsensusType,
     /// Overrides log config.
     #[clap(flatten)]
     log_args: LogArgs,
@@ -50,6 +55,7 @@ struct Args {
 fn main() -> Result<()> {
     let Args {
         config,
+        consensus,
         da_args,
         log_args,
         http_args,
@@ -58,13 +64,6 @@ fn main() -> Result<()> {
         overlay_args,
         metrics_args,
     } = Args::parse();
-    let config = serde_yaml::from_reader::<_, Config>(std::fs::File::open(config)?)?
-        .update_da(da_args)?
-        .update_log(log_args)?
-        .update_http(http_args)?
-        .update_consensus(consensus_args)?
-        .update_overlay(overlay_args)?
-        .update_network(network_args)?;
 
     let registry = cfg!(feature = "metrics")
         .then(|| {
@@ -74,6 +73,30 @@ fn main() -> Result<()> {
         })
         .flatten();
 
+    match consensus {
+        ConsensusType::Carnot => {
+            let config = serde_yaml::from_reader::<_, Config>(std::fs::File::open(config)?)?
+                .update_da(da_args)?
+                .update_log(log_args)?
+                .update_http(http_args)?
+                .update_consensus(consensus_args)?
+                .update_overlay(overlay_args)?
+                .update_network(network_args)?;
+            run_carnot(config, registry)
+        }
+        ConsensusType::Cryptarchia => {
+            let config =
+                serde_yaml::from_reader::<_, CryptarchiaConfig>(std::fs::File::open(config)?)?
+                    .update_da(da_args)?
+                    .update_log(log_args)?
+                    .update_http(http_args)?
+                    .update_network(network_args)?;
+            run_cryptarchia(config, registry)
+        }
+    }
+}
+
+fn run_carnot(config: Config, registry: Option<NomosRegistry>) -> Result<()> {
     let app = OverwatchRunner::<Nomos>::run(
         NomosServiceSettings {
             network: config.network,
@@ -111,6 +134,44 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+fn run_cryptarchia(config: CryptarchiaConfig, registry: Option<NomosRegistry>) -> Result<()> {
+    let app = OverwatchRunner::<NomosCryptarchia>::run(
+        NomosCryptarchiaServiceSettings {
+            network: config.network,
+            logging: config.log,
+            http: config.http,
+            cl_mempool: nomos_mempool::Settings {
+                backend: (),
+                network: AdapterSettings {
+                    topic: String::from(nomos_node::CL_TOPIC),
+                    id: <CryptarchiaTx as Transaction>::hash,
+                },
+                registry: registry.clone(),
+            },
+            da_mempool: nomos_mempool::Settings {
+                backend: (),
+                network: AdapterSettings {
+                    topic: String::from(nomos_node::DA_TOPIC),
+                    id: cert_id,
+                },
+                registry: registry.clone(),
+            },
+            cryptarchia: config.consensus,
+            #[cfg(feature = "metrics")]
+            metrics: MetricsSettings { registry },
+            da: config.da,
+            storage: nomos_storage::backends::sled::SledBackendSettings {
+                db_path: std::path::PathBuf::from(DEFAULT_DB_PATH),
+            },
+            system_sig: (),
+        },
+        None,
+    )
+    .map_err(|e| eyre!("Error encountered: {}", e))?;
+    app.wait_finished();
+    Ok(())
+}
+
 fn cert_id(cert: &Certificate) -> <Blob as blob::Blob>::Hash {
     use certificate::Certificate;
     cert.hash()
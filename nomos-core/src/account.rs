@@ -1,4 +1,35 @@
-use serde::{Deserialize, Serialize};
+use crate::crypto::{self, PrivateKey, PublicKey};
+use crate::utils::{display_hex_bytes_newtype, serde_bytes_newtype};
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
-pub struct AccountId;
+pub type Balance = u64;
+pub type Nonce = u64;
+
+/// An account, identified by the ed25519 public key which authorizes its transactions
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AccountId(PublicKey);
+
+impl AccountId {
+    /// Account controlled by the ed25519 key whose seed is `secret_key`
+    pub fn from_secret_key(secret_key: &PrivateKey) -> Self {
+        Self(crypto::public_key(secret_key))
+    }
+
+    pub fn public_key(&self) -> &PublicKey {
+        &self.0
+    }
+}
+
+impl From<PublicKey> for AccountId {
+    fn from(public_key: PublicKey) -> Self {
+        Self(public_key)
+    }
+}
+
+impl From<AccountId> for PublicKey {
+    fn from(id: AccountId) -> Self {
+        id.0
+    }
+}
+
+display_hex_bytes_newtype!(AccountId);
+serde_bytes_newtype!(AccountId, 32);
@@ -5,10 +5,11 @@ use std::hash::Hash;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 // internal
-use crate::block::Block;
+use crate::block::{Block, MAX_CONTENT_SIZE};
 use crate::crypto::Blake2b;
 use crate::da::certificate::BlobCertificateSelect;
 use crate::da::certificate::Certificate;
+use crate::execution::{self, AccountTransaction, State, StateRoot};
 use crate::header::{
     carnot::Builder as CarnotBuilder, cryptarchia::Builder as CryptarchiaBuilder, Header, HeaderId,
 };
@@ -20,6 +21,9 @@ use carnot_engine::{LeaderProof, Qc, View};
 /// Wrapper over a block building `new` method than holds intermediary state and can be
 /// passed around. It also compounds the transaction selection and blob selection heuristics to be
 /// used for transaction and blob selection.
+/// Transactions which can't be applied on top of the parent account state are skipped, and the
+/// resulting state root is committed in the header. Blocks for ledgers that don't track accounts
+/// are built with [`BlockBuilder::build_without_state`] instead.
 ///
 /// Example:
 /// ``` ignore
@@ -28,6 +32,7 @@ use carnot_engine::{LeaderProof, Qc, View};
 ///     BlockBuilder::new( FirstTx::default(), FirstBlob::default())
 ///         .with_transactions([tx1].into_iter())
 ///         .with_blobs([blob1].into_iter())
+///         .with_state(parent_state)
 /// };
 /// builder.build().expect("All block attributes should have been set")
 /// ```
@@ -38,6 +43,7 @@ pub struct BlockBuilder<Tx, Blob, TxSelector, BlobSelector> {
     cryptarchia_header_builder: Option<CryptarchiaBuilder>,
     txs: Option<Box<dyn Iterator<Item = Tx>>>,
     blobs: Option<Box<dyn Iterator<Item = Blob>>>,
+    state: Option<State>,
 }
 
 impl<Tx, C, TxSelector, BlobSelector> BlockBuilder<Tx, C, TxSelector, BlobSelector>
@@ -50,10 +56,15 @@ where
         view: View,
         parent_qc: Qc<HeaderId>,
         leader_proof: LeaderProof,
+        state_root: StateRoot,
     ) -> Block<Tx, C> {
         Block {
             header: Header::Carnot(
-                CarnotBuilder::new(beacon, view, parent_qc, leader_proof).build([0; 32].into(), 0),
+                CarnotBuilder::new(beacon, view, parent_qc, leader_proof).build(
+                    [0; 32].into(),
+                    0,
+                    state_root,
+                ),
             ),
             cl_transactions: IndexSet::new(),
             bl_blobs: IndexSet::new(),
@@ -76,6 +87,7 @@ where
             cryptarchia_header_builder: None,
             txs: None,
             blobs: None,
+            state: None,
         }
     }
 
@@ -109,8 +121,9 @@ where
         self
     }
 
+    /// Build a block without executing its transactions, committing to the empty account state
     #[allow(clippy::result_large_err)]
-    pub fn build(self) -> Result<Block<Tx, C>, String> {
+    pub fn build_without_state(self) -> Result<Block<Tx, C>, String> {
         if let Self {
             tx_selector,
             blob_selector,
@@ -118,38 +131,95 @@ where
             cryptarchia_header_builder: cryptarchia_builder,
             txs: Some(txs),
             blobs: Some(blobs),
+            state: None,
         } = self
         {
             let txs = tx_selector.select_tx_from(txs).collect::<IndexSet<_>>();
             let blobs = blob_selector
                 .select_blob_from(blobs)
                 .collect::<IndexSet<_>>();
+            Self::assemble(
+                carnot_builder,
+                cryptarchia_builder,
+                txs,
+                blobs,
+                State::default().root(),
+            )
+        } else {
+            Err("incomplete block".to_string())
+        }
+    }
 
-            let serialized_content = wire::serialize(&(&txs, &blobs)).unwrap();
-            let content_size = u32::try_from(serialized_content.len()).map_err(|_| {
-                format!(
-                    "Content is too big: {} out of {} max",
-                    serialized_content.len(),
-                    u32::MAX
-                )
-            })?;
-            let content_id = <[u8; 32]>::from(Blake2b::digest(&serialized_content)).into();
+    #[allow(clippy::result_large_err)]
+    fn assemble(
+        carnot_builder: Option<CarnotBuilder>,
+        cryptarchia_builder: Option<CryptarchiaBuilder>,
+        txs: IndexSet<Tx>,
+        blobs: IndexSet<C>,
+        state_root: StateRoot,
+    ) -> Result<Block<Tx, C>, String> {
+        let serialized_content = wire::serialize_with_limit(&(&txs, &blobs), MAX_CONTENT_SIZE)
+            .map_err(|e| format!("Content is too big: {e}, {MAX_CONTENT_SIZE} bytes max"))?;
+        // fits in a u32 as it's smaller than MAX_CONTENT_SIZE
+        let content_size = serialized_content.len() as u32;
+        let content_id = <[u8; 32]>::from(Blake2b::digest(&serialized_content)).into();
+
+        let header = match (carnot_builder, cryptarchia_builder) {
+            (Some(carnot_builder), None) => {
+                Header::Carnot(carnot_builder.build(content_id, content_size, state_root))
+            }
+            (None, Some(cryptarchia_builder)) => {
+                Header::Cryptarchia(cryptarchia_builder.build(content_id, content_size, state_root))
+            }
+            _ => return Err("Exactly one header builder should be set".to_string()),
+        };
 
-            let header = match (carnot_builder, cryptarchia_builder) {
-                (Some(carnot_builder), None) => {
-                    Header::Carnot(carnot_builder.build(content_id, content_size))
-                }
-                (None, Some(cryptarchia_builder)) => {
-                    Header::Cryptarchia(cryptarchia_builder.build(content_id, content_size))
-                }
-                _ => return Err("Exactly one header builder should be set".to_string()),
-            };
+        Ok(Block {
+            header,
+            cl_transactions: txs,
+            bl_blobs: blobs,
+        })
+    }
+}
+
+impl<Tx, C, TxSelector, BlobSelector> BlockBuilder<Tx, C, TxSelector, BlobSelector>
+where
+    Tx: Transaction + AccountTransaction + Clone + Eq + Hash + Serialize + DeserializeOwned,
+    C: Certificate + Clone + Eq + Hash + Serialize + DeserializeOwned,
+    TxSelector: TxSelect<Tx = Tx>,
+    BlobSelector: BlobCertificateSelect<Certificate = C>,
+{
+    /// Account state of the parent block, on top of which transactions are executed
+    #[must_use]
+    pub fn with_state(mut self, state: State) -> Self {
+        self.state = Some(state);
+        self
+    }
 
-            Ok(Block {
-                header,
-                cl_transactions: txs,
-                bl_blobs: blobs,
-            })
+    #[allow(clippy::result_large_err)]
+    pub fn build(self) -> Result<Block<Tx, C>, String> {
+        if let Self {
+            tx_selector,
+            blob_selector,
+            carnot_header_builder: carnot_builder,
+            cryptarchia_header_builder: cryptarchia_builder,
+            txs: Some(txs),
+            blobs: Some(blobs),
+            state: Some(state),
+        } = self
+        {
+            let txs = tx_selector
+                .select_tx_from(execution::valid_transactions(state.clone(), txs))
+                .collect::<IndexSet<_>>();
+            // the selector might not keep every valid transaction, so execute again what's left
+            let state_root = state
+                .try_apply_txs(&txs)
+                .map_err(|e| format!("Invalid transaction selection: {e}"))?
+                .root();
+            let blobs = blob_selector
+                .select_blob_from(blobs)
+                .collect::<IndexSet<_>>();
+            Self::assemble(carnot_builder, cryptarchia_builder, txs, blobs, state_root)
         } else {
             Err("incomplete block".to_string())
         }
@@ -5,13 +5,19 @@ use indexmap::IndexSet;
 use core::hash::Hash;
 // crates
 use crate::header::Header;
-use crate::wire;
+use crate::wire::envelope::{self, message_type, WireMessage};
 use ::serde::{de::DeserializeOwned, Deserialize, Serialize};
 use bytes::Bytes;
 // internal
 
 pub type TxHash = [u8; 32];
 
+/// Upper bound to the encoded size of the transactions and blob certificates of a block
+pub const MAX_CONTENT_SIZE: u64 = 32 * 1024 * 1024;
+/// Upper bound to the encoded size of a block, which leaves room for [`MAX_CONTENT_SIZE`]
+/// of content plus the header and the encoding overhead.
+pub const MAX_BLOCK_SIZE: u64 = MAX_CONTENT_SIZE + 1024 * 1024;
+
 /// A block
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Block<Tx: Clone + Eq + Hash, BlobCertificate: Clone + Eq + Hash> {
@@ -40,11 +46,25 @@ impl<
     > Block<Tx, BlobCertificate>
 {
     /// Encode block into bytes
+    ///
+    /// # Panics
+    /// If the encoded block is larger than [`MAX_BLOCK_SIZE`]
     pub fn as_bytes(&self) -> Bytes {
-        wire::serialize(self).unwrap().into()
+        envelope::encode(self)
+            .expect("blocks do not exceed MAX_BLOCK_SIZE")
+            .into()
     }
 
-    pub fn from_bytes(bytes: &[u8]) -> Self {
-        wire::deserialize(bytes).unwrap()
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, envelope::Error> {
+        envelope::decode(bytes)
     }
 }
+
+impl<
+        Tx: Clone + Eq + Hash + Serialize + DeserializeOwned,
+        BlobCertificate: Clone + Eq + Hash + Serialize + DeserializeOwned,
+    > WireMessage for Block<Tx, BlobCertificate>
+{
+    const MESSAGE_TYPE: u8 = message_type::BLOCK;
+    const MAX_SIZE: u64 = MAX_BLOCK_SIZE;
+}
@@ -1,7 +1,74 @@
 use blake2::digest::typenum::U32;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 pub type PublicKey = [u8; 32];
 pub type PrivateKey = [u8; 32];
-pub type Signature = [u8; 32];
 
 pub type Blake2b = blake2::Blake2b<U32>;
+
+/// Ed25519 signature
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature(ed25519_dalek::Signature);
+
+impl Signature {
+    pub fn to_bytes(&self) -> [u8; 64] {
+        self.0.to_bytes()
+    }
+
+    /// Sign `message` with the ed25519 key whose seed is `secret_key`
+    pub fn sign(secret_key: &PrivateKey, message: &[u8]) -> Self {
+        use ed25519_dalek::Signer;
+        Self(ed25519_dalek::SigningKey::from_bytes(secret_key).sign(message))
+    }
+
+    /// Whether `self` is a valid signature of `message` by `public_key`.
+    /// Verification is strict, so that a signature can't be altered into another valid one.
+    pub fn verify(&self, public_key: &PublicKey, message: &[u8]) -> bool {
+        ed25519_dalek::VerifyingKey::from_bytes(public_key)
+            .is_ok_and(|key| key.verify_strict(message, &self.0).is_ok())
+    }
+}
+
+/// Public key of the ed25519 key whose seed is `secret_key`
+pub fn public_key(secret_key: &PrivateKey) -> PublicKey {
+    ed25519_dalek::SigningKey::from_bytes(secret_key)
+        .verifying_key()
+        .to_bytes()
+}
+
+impl From<[u8; 64]> for Signature {
+    fn from(bytes: [u8; 64]) -> Self {
+        Self(ed25519_dalek::Signature::from_bytes(&bytes))
+    }
+}
+
+impl core::hash::Hash for Signature {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.to_bytes().hash(state)
+    }
+}
+
+impl Serialize for Signature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            const_hex::const_encode::<64, false>(&self.to_bytes())
+                .as_str()
+                .serialize(serializer)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Signature {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            const_hex::decode_to_array::<_, 64>(s)
+                .map(Self::from)
+                .map_err(serde::de::Error::custom)
+        } else {
+            ed25519_dalek::Signature::deserialize(deserializer).map(Self)
+        }
+    }
+}
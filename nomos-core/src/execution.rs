@@ -0,0 +1,519 @@
+//! Execution of the account transfers and stake operations carried by cl transactions.
+//!
+//! Every block commits in its header to the root of the account state obtained by applying
+//! its transactions, in order, to the state of its parent. A block containing a transfer
+//! which can't be applied, or committing to a different root, is invalid.
+// std
+// crates
+use blake2::Digest;
+use carnot_engine::NodeId;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+// internal
+use crate::account::{AccountId, Balance, Nonce};
+use crate::crypto::Blake2b;
+use crate::staking::{Stake, StakeOp, StakeTransaction, StakingTransaction};
+use crate::tx::carnot::TransferTransaction;
+use crate::utils::{display_hex_bytes_newtype, serde_bytes_newtype};
+
+type RedBlackTreeMap<K, V> = rpds::RedBlackTreeMapSync<K, V>;
+
+const STATE_ROOT_TAG: &[u8] = b"nomos-state";
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Invalid nonce for account {account}: expected {expected}, got {actual}")]
+    InvalidNonce {
+        account: AccountId,
+        expected: Nonce,
+        actual: Nonce,
+    },
+    #[error("Account {account} can't pay {required} out of a balance of {balance}")]
+    InsufficientBalance {
+        account: AccountId,
+        balance: Balance,
+        required: Balance,
+    },
+    #[error(
+        "Account {account} can't release {required} out of the {bonded} it bonded for {node:?}"
+    )]
+    InsufficientBond {
+        account: AccountId,
+        node: NodeId,
+        bonded: Stake,
+        required: Stake,
+    },
+    #[error("Invalid equivocation evidence against {0:?}")]
+    InvalidEvidence(NodeId),
+    #[error("Balance or nonce overflow for account {0}")]
+    Overflow(AccountId),
+    #[error("State root mismatch: header commits to {expected}, execution produced {actual}")]
+    StateRootMismatch {
+        expected: StateRoot,
+        actual: StateRoot,
+    },
+}
+
+/// Commitment to the whole account state, see [`State::root`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub struct StateRoot([u8; 32]);
+
+impl From<[u8; 32]> for StateRoot {
+    fn from(root: [u8; 32]) -> Self {
+        Self(root)
+    }
+}
+
+impl From<StateRoot> for [u8; 32] {
+    fn from(root: StateRoot) -> Self {
+        root.0
+    }
+}
+
+display_hex_bytes_newtype!(StateRoot);
+serde_bytes_newtype!(StateRoot, 32);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Account {
+    pub balance: Balance,
+    /// Number of transfers sent from this account, i.e. the nonce of the next one
+    pub nonce: Nonce,
+}
+
+/// Transactions which may carry a [`TransferTransaction`], or a [`StakeTransaction`]
+/// locking and releasing account funds
+pub trait AccountTransaction: StakingTransaction {
+    fn transfer(&self) -> Option<&TransferTransaction>;
+}
+
+// Opaque transactions, as used in tests and simulations, never move funds
+impl AccountTransaction for [u8; 32] {
+    fn transfer(&self) -> Option<&TransferTransaction> {
+        None
+    }
+}
+
+/// Balances and nonces of all accounts, and the stake they locked for consensus nodes.
+///
+/// Accounts which were never touched are not stored and have a zero balance and nonce.
+/// The state is persistent, so that cloning it to keep a copy for each block is cheap.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct State {
+    accounts: RedBlackTreeMap<AccountId, Account>,
+    /// Stake locked by each account for each node, which is no longer part of its balance
+    #[serde(default)]
+    bonds: RedBlackTreeMap<NodeId, RedBlackTreeMap<AccountId, Stake>>,
+}
+
+impl State {
+    pub fn account(&self, id: &AccountId) -> Account {
+        self.accounts.get(id).copied().unwrap_or_default()
+    }
+
+    pub fn accounts(&self) -> impl Iterator<Item = (&AccountId, &Account)> + '_ {
+        self.accounts.iter()
+    }
+
+    /// Stake locked by `account` for `node`
+    pub fn bond(&self, node: &NodeId, account: &AccountId) -> Stake {
+        self.bonds
+            .get(node)
+            .and_then(|bonds| bonds.get(account))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn with_account(self, id: AccountId, account: Account) -> Self {
+        let accounts = if account == Account::default() {
+            self.accounts.remove(&id)
+        } else {
+            self.accounts.insert(id, account)
+        };
+        Self { accounts, ..self }
+    }
+
+    fn with_bond(self, node: NodeId, account: AccountId, stake: Stake) -> Self {
+        let node_bonds = self.bonds.get(&node).cloned().unwrap_or_default();
+        let node_bonds = if stake == 0 {
+            node_bonds.remove(&account)
+        } else {
+            node_bonds.insert(account, stake)
+        };
+        let bonds = if node_bonds.is_empty() {
+            self.bonds.remove(&node)
+        } else {
+            self.bonds.insert(node, node_bonds)
+        };
+        Self { bonds, ..self }
+    }
+
+    /// Account `id` with its nonce incremented, if `nonce` is the expected one
+    fn next_nonce(&self, id: AccountId, nonce: Nonce) -> Result<Account, Error> {
+        let account = self.account(&id);
+        if nonce != account.nonce {
+            return Err(Error::InvalidNonce {
+                account: id,
+                expected: account.nonce,
+                actual: nonce,
+            });
+        }
+        let nonce = account.nonce.checked_add(1).ok_or(Error::Overflow(id))?;
+        Ok(Account { nonce, ..account })
+    }
+
+    /// Apply the transfer or stake operation carried by `tx`.
+    /// Transactions carrying neither leave the state unchanged.
+    pub fn try_apply_tx<Tx: AccountTransaction>(self, tx: &Tx) -> Result<Self, Error> {
+        let state = match tx.transfer() {
+            Some(transfer) => self.try_apply_transfer(transfer)?,
+            None => self,
+        };
+        match tx.stake() {
+            Some(stake) => state.try_apply_stake(stake),
+            None => Ok(state),
+        }
+    }
+
+    /// Move `value` from the sender to the receiver of a transfer.
+    /// The fee is taken from the sender and burned, and the sender nonce is incremented.
+    fn try_apply_transfer(self, transfer: &TransferTransaction) -> Result<Self, Error> {
+        let from = *transfer.from();
+        let sender = self.next_nonce(from, transfer.nonce())?;
+        let required = transfer
+            .value()
+            .checked_add(transfer.fee())
+            .ok_or(Error::Overflow(from))?;
+        let balance = sender
+            .balance
+            .checked_sub(required)
+            .ok_or(Error::InsufficientBalance {
+                account: from,
+                balance: sender.balance,
+                required,
+            })?;
+        let state = self.with_account(from, Account { balance, ..sender });
+
+        // read the receiver after updating the sender, which may be the same account
+        let to = *transfer.to();
+        let receiver = state.account(&to);
+        let balance = receiver
+            .balance
+            .checked_add(transfer.value())
+            .ok_or(Error::Overflow(to))?;
+        Ok(state.with_account(
+            to,
+            Account {
+                balance,
+                ..receiver
+            },
+        ))
+    }
+
+    /// Lock or release funds of the signing account, or burn the stake of an equivocating node.
+    /// The nonce of the signing account is incremented.
+    fn try_apply_stake(self, tx: &StakeTransaction) -> Result<Self, Error> {
+        let id = *tx.account();
+        let account = self.next_nonce(id, tx.nonce())?;
+        match tx.op() {
+            StakeOp::Bond { node, stake } => {
+                let balance =
+                    account
+                        .balance
+                        .checked_sub(*stake)
+                        .ok_or(Error::InsufficientBalance {
+                            account: id,
+                            balance: account.balance,
+                            required: *stake,
+                        })?;
+                let bonded = self
+                    .bond(node, &id)
+                    .checked_add(*stake)
+                    .ok_or(Error::Overflow(id))?;
+                Ok(self
+                    .with_account(id, Account { balance, ..account })
+                    .with_bond(*node, id, bonded))
+            }
+            StakeOp::Unbond { node, stake } => {
+                let bonded = self.bond(node, &id);
+                let remaining = bonded.checked_sub(*stake).ok_or(Error::InsufficientBond {
+                    account: id,
+                    node: *node,
+                    bonded,
+                    required: *stake,
+                })?;
+                let balance = account
+                    .balance
+                    .checked_add(*stake)
+                    .ok_or(Error::Overflow(id))?;
+                Ok(self
+                    .with_account(id, Account { balance, ..account })
+                    .with_bond(*node, id, remaining))
+            }
+            StakeOp::Slash(evidence) => {
+                if !evidence.verify() {
+                    return Err(Error::InvalidEvidence(evidence.offender));
+                }
+                // the stake locked for the offender is burned
+                let state = self.with_account(id, account);
+                Ok(Self {
+                    bonds: state.bonds.remove(&evidence.offender),
+                    ..state
+                })
+            }
+        }
+    }
+
+    pub fn try_apply_txs<'a, Tx: AccountTransaction + 'a>(
+        self,
+        txs: impl IntoIterator<Item = &'a Tx>,
+    ) -> Result<Self, Error> {
+        txs.into_iter().try_fold(self, Self::try_apply_tx)
+    }
+
+    /// Apply the transactions of a block and check the result against the root it commits to
+    pub fn try_apply_block<'a, Tx: AccountTransaction + 'a>(
+        self,
+        txs: impl IntoIterator<Item = &'a Tx>,
+        state_root: StateRoot,
+    ) -> Result<Self, Error> {
+        let state = self.try_apply_txs(txs)?;
+        let actual = state.root();
+        if actual != state_root {
+            return Err(Error::StateRootMismatch {
+                expected: state_root,
+                actual,
+            });
+        }
+        Ok(state)
+    }
+
+    /// Hash of the state tag and the number of stored accounts, followed by every stored
+    /// account, sorted by id, as id || balance || nonce, then by every bond, sorted by node
+    /// and account, as node || account || stake. Numbers are encoded as little endian u64.
+    ///
+    /// The whole state is rehashed for every block, in time linear in the number of stored
+    /// accounts and bonds: about a millisecond for 10k accounts on a release build, which is
+    /// negligible next to fetching and validating a block. Nothing proves single accounts
+    /// against the root yet, so a Merkle tree would only be needed for speed. Replacing the flat
+    /// hash changes every state root, so it must come with a new genesis.
+    pub fn root(&self) -> StateRoot {
+        let mut h = Blake2b::new_with_prefix(STATE_ROOT_TAG);
+        h.update((self.accounts.size() as u64).to_le_bytes());
+        for (id, account) in self.accounts.iter() {
+            h.update(id.public_key());
+            h.update(account.balance.to_le_bytes());
+            h.update(account.nonce.to_le_bytes());
+        }
+        for (node, bonds) in self.bonds.iter() {
+            for (account, stake) in bonds.iter() {
+                h.update(<&[u8; 32]>::from(node));
+                h.update(account.public_key());
+                h.update(stake.to_le_bytes());
+            }
+        }
+        StateRoot(h.finalize().into())
+    }
+}
+
+impl FromIterator<(AccountId, Balance)> for State {
+    /// Genesis state with the given balances
+    fn from_iter<I: IntoIterator<Item = (AccountId, Balance)>>(balances: I) -> Self {
+        balances
+            .into_iter()
+            .fold(Self::default(), |state, (id, balance)| {
+                state.with_account(id, Account { balance, nonce: 0 })
+            })
+    }
+}
+
+/// Keep only the transactions that can be applied on top of `state`, in order,
+/// so that a proposed block is valid
+pub fn valid_transactions<Tx: AccountTransaction>(
+    mut state: State,
+    txs: impl Iterator<Item = Tx>,
+) -> impl Iterator<Item = Tx> {
+    txs.filter(move |tx| match state.clone().try_apply_tx(tx) {
+        Ok(new_state) => {
+            state = new_state;
+            true
+        }
+        Err(_) => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tx::carnot::Tx;
+
+    fn id(seed: u8) -> AccountId {
+        AccountId::from_secret_key(&[seed; 32])
+    }
+
+    fn stake(from: u8, op: StakeOp, nonce: Nonce) -> Tx {
+        Tx::Stake(StakeTransaction::new(&[from; 32], op, nonce))
+    }
+
+    fn transfer(from: u8, to: u8, value: Balance, fee: Balance, nonce: Nonce) -> Tx {
+        Tx::Transfer(TransferTransaction::new(
+            &[from; 32],
+            id(to),
+            value,
+            fee,
+            nonce,
+        ))
+    }
+
+    #[test]
+    fn transfers_move_funds_and_burn_fees() {
+        let state = State::from_iter([(id(1), 100)]);
+        let state = state
+            .try_apply_txs(&[transfer(1, 2, 30, 2, 0), transfer(2, 3, 10, 1, 0)])
+            .unwrap();
+        assert_eq!(
+            state.account(&id(1)),
+            Account {
+                balance: 68,
+                nonce: 1
+            }
+        );
+        assert_eq!(
+            state.account(&id(2)),
+            Account {
+                balance: 19,
+                nonce: 1
+            }
+        );
+        assert_eq!(
+            state.account(&id(3)),
+            Account {
+                balance: 10,
+                nonce: 0
+            }
+        );
+
+        // sending to oneself only costs the fee
+        let state = state.try_apply_tx(&transfer(3, 3, 5, 1, 0)).unwrap();
+        assert_eq!(
+            state.account(&id(3)),
+            Account {
+                balance: 9,
+                nonce: 1
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_transfers_are_rejected() {
+        let state = State::from_iter([(id(1), 100)]);
+        assert_eq!(
+            state.clone().try_apply_tx(&transfer(1, 2, 100, 1, 0)),
+            Err(Error::InsufficientBalance {
+                account: id(1),
+                balance: 100,
+                required: 101
+            })
+        );
+        assert_eq!(
+            state.clone().try_apply_tx(&transfer(1, 2, u64::MAX, 1, 0)),
+            Err(Error::Overflow(id(1)))
+        );
+        assert_eq!(
+            state.clone().try_apply_tx(&transfer(1, 2, 10, 0, 1)),
+            Err(Error::InvalidNonce {
+                account: id(1),
+                expected: 0,
+                actual: 1
+            })
+        );
+        // a transfer can't be replayed
+        let tx = transfer(1, 2, 10, 0, 0);
+        assert!(state.try_apply_txs(&[tx.clone(), tx]).is_err());
+    }
+
+    #[test]
+    fn bonded_stake_is_locked() {
+        let node = NodeId::new([7; 32]);
+        let state = State::from_iter([(id(1), 100)]);
+        let state = state
+            .try_apply_txs(&[
+                stake(1, StakeOp::Bond { node, stake: 60 }, 0),
+                stake(1, StakeOp::Unbond { node, stake: 10 }, 1),
+            ])
+            .unwrap();
+        assert_eq!(state.bond(&node, &id(1)), 50);
+        assert_eq!(
+            state.account(&id(1)),
+            Account {
+                balance: 50,
+                nonce: 2
+            }
+        );
+
+        // locked funds can't be spent
+        assert!(state
+            .clone()
+            .try_apply_tx(&transfer(1, 2, 51, 0, 2))
+            .is_err());
+        assert!(state
+            .clone()
+            .try_apply_tx(&stake(1, StakeOp::Bond { node, stake: 51 }, 2))
+            .is_err());
+        // and only the account which bonded them can release them
+        assert_eq!(
+            state
+                .clone()
+                .try_apply_tx(&stake(2, StakeOp::Unbond { node, stake: 1 }, 0)),
+            Err(Error::InsufficientBond {
+                account: id(2),
+                node,
+                bonded: 0,
+                required: 1
+            })
+        );
+
+        let unbonded = state
+            .clone()
+            .try_apply_tx(&stake(1, StakeOp::Unbond { node, stake: 50 }, 2))
+            .unwrap();
+        assert_eq!(unbonded.bond(&node, &id(1)), 0);
+        assert_eq!(unbonded.account(&id(1)).balance, 100);
+        assert_ne!(unbonded.root(), state.root());
+    }
+
+    #[test]
+    fn root_commits_to_balances_and_nonces() {
+        let state = State::from_iter([(id(1), 100), (id(2), 0)]);
+        // untouched accounts are not part of the state
+        assert_eq!(state.root(), State::from_iter([(id(1), 100)]).root());
+        assert_ne!(state.root(), State::default().root());
+
+        let txs = [transfer(1, 2, 0, 0, 0)];
+        let new_state = state.clone().try_apply_txs(&txs).unwrap();
+        assert_ne!(new_state.root(), state.root());
+        assert_eq!(
+            state.clone().try_apply_block(&txs, new_state.root()),
+            Ok(new_state.clone())
+        );
+        assert_eq!(
+            state.clone().try_apply_block(&txs, state.root()),
+            Err(Error::StateRootMismatch {
+                expected: state.root(),
+                actual: new_state.root()
+            })
+        );
+    }
+
+    #[test]
+    fn only_applicable_transactions_are_valid() {
+        let state = State::from_iter([(id(1), 10)]);
+        let txs = vec![
+            transfer(1, 2, 5, 0, 0),
+            transfer(1, 2, 5, 0, 0),
+            transfer(1, 2, 10, 0, 1),
+            transfer(1, 2, 5, 0, 1),
+        ];
+        let valid = valid_transactions(state, txs.clone().into_iter()).collect::<Vec<_>>();
+        assert_eq!(valid, vec![txs[0].clone(), txs[3].clone()]);
+    }
+}
@@ -1,11 +1,19 @@
 use super::{ContentId, HeaderId};
+use crate::block::MAX_BLOCK_SIZE;
 use crate::crypto::Blake2b;
+use crate::execution::StateRoot;
 use crate::wire;
 use blake2::Digest;
 use serde::{Deserialize, Serialize};
 
 use carnot_engine::overlay::RandomBeaconState;
-use carnot_engine::{LeaderProof, Qc, View};
+use carnot_engine::{LeaderProof, Qc, SignableBlockId, View};
+
+impl SignableBlockId for HeaderId {
+    fn signable_bytes(&self) -> Vec<u8> {
+        self.0.to_vec()
+    }
+}
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Header {
@@ -15,6 +23,7 @@ pub struct Header {
     leader_proof: LeaderProof,
     content_id: ContentId,
     content_size: u32,
+    state_root: StateRoot,
 }
 
 impl Header {
@@ -25,6 +34,7 @@ impl Header {
         leader_proof: LeaderProof,
         content_id: ContentId,
         content_size: u32,
+        state_root: StateRoot,
     ) -> Self {
         Self {
             beacon,
@@ -33,6 +43,7 @@ impl Header {
             leader_proof,
             content_id,
             content_size,
+            state_root,
         }
     }
 
@@ -42,7 +53,10 @@ impl Header {
 
     pub fn id(&self) -> HeaderId {
         let mut h = Blake2b::new();
-        let bytes = wire::serialize(&self).unwrap();
+        // headers of blocks received from peers are only bounded by the block size,
+        // and their qc signatures grow with the committees
+        let bytes = wire::serialize_with_limit(&self, MAX_BLOCK_SIZE)
+            .expect("headers do not exceed the block size limit");
         h.update(&bytes);
         HeaderId(h.finalize().into())
     }
@@ -63,6 +77,11 @@ impl Header {
         self.content_size
     }
 
+    /// Root of the account state after executing the block transactions
+    pub fn state_root(&self) -> StateRoot {
+        self.state_root
+    }
+
     pub fn view(&self) -> View {
         self.view
     }
@@ -103,7 +122,7 @@ impl Builder {
         }
     }
 
-    pub fn build(self, content_id: ContentId, content_size: u32) -> Header {
+    pub fn build(self, content_id: ContentId, content_size: u32, state_root: StateRoot) -> Header {
         Header::new(
             self.beacon,
             self.view,
@@ -111,6 +130,7 @@ impl Builder {
             self.leader_proof,
             content_id,
             content_size,
+            state_root,
         )
     }
 }
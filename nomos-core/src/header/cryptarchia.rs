@@ -1,5 +1,6 @@
 use super::{ContentId, HeaderId};
 use crate::crypto::Blake2b;
+use crate::execution::StateRoot;
 use blake2::Digest;
 use cryptarchia_engine::Slot;
 use cryptarchia_ledger::LeaderProof;
@@ -17,6 +18,8 @@ pub struct Header {
     content_size: u32,
     // id of block contents
     content_id: ContentId,
+    // root of the account state after executing the block transactions
+    state_root: StateRoot,
     leader_proof: LeaderProof,
     orphaned_leader_proofs: Vec<Header>,
 }
@@ -30,12 +33,18 @@ impl Header {
         h.update(b"\x01");
         h.update(self.content_size.to_be_bytes());
         h.update(self.content_id.0);
+        h.update(<[u8; 32]>::from(self.state_root));
         h.update(self.slot.to_be_bytes());
         h.update(self.parent.0);
 
         h.update(self.leader_proof.commitment());
         h.update(self.leader_proof.nullifier());
         h.update(self.leader_proof.evolved_commitment());
+        h.update(self.leader_proof.pk());
+        h.update(self.leader_proof.nonce());
+        h.update(u32::from(self.leader_proof.value()).to_be_bytes());
+        h.update(self.leader_proof.vrf_output());
+        h.update(self.leader_proof.vrf_proof());
 
         for proof in &self.orphaned_leader_proofs {
             proof.update_hasher(h)
@@ -56,6 +65,10 @@ impl Header {
         self.slot
     }
 
+    pub fn state_root(&self) -> StateRoot {
+        self.state_root
+    }
+
     pub fn orphaned_proofs(&self) -> &[Header] {
         &self.orphaned_leader_proofs
     }
@@ -64,6 +77,7 @@ impl Header {
         parent: HeaderId,
         content_size: u32,
         content_id: ContentId,
+        state_root: StateRoot,
         slot: Slot,
         leader_proof: LeaderProof,
     ) -> Self {
@@ -71,6 +85,7 @@ impl Header {
             parent,
             content_size,
             content_id,
+            state_root,
             slot,
             leader_proof,
             orphaned_leader_proofs: vec![],
@@ -105,12 +120,13 @@ impl Builder {
         self
     }
 
-    pub fn build(self, content_id: ContentId, content_size: u32) -> Header {
+    pub fn build(self, content_id: ContentId, content_size: u32, state_root: StateRoot) -> Header {
         Header {
             parent: self.parent,
             slot: self.slot,
             content_size,
             content_id,
+            state_root,
             leader_proof: self.leader_proof,
             orphaned_leader_proofs: self.orphaned_leader_proofs,
         }
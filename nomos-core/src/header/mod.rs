@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 
+use crate::execution::StateRoot;
 use crate::utils::{display_hex_bytes_newtype, serde_bytes_newtype};
 
 pub mod carnot;
@@ -47,6 +48,13 @@ impl Header {
             Self::Carnot(header) => header.parent(),
         }
     }
+
+    pub fn state_root(&self) -> StateRoot {
+        match self {
+            Self::Cryptarchia(header) => header.state_root(),
+            Self::Carnot(header) => header.state_root(),
+        }
+    }
 }
 
 impl From<[u8; 32]> for HeaderId {
@@ -2,6 +2,7 @@ pub mod account;
 pub mod block;
 pub mod crypto;
 pub mod da;
+pub mod execution;
 pub mod header;
 pub mod staking;
 pub mod tx;
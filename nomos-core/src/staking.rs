@@ -1 +1,176 @@
+// std
+// crates
+use ::serde::{Deserialize, Serialize};
+use carnot_engine::{Equivocation, NodeId};
+// internal
+use crate::account::{AccountId, Nonce};
+use crate::crypto::{PrivateKey, Signature};
+use crate::header::HeaderId;
+use crate::wire;
+
+const STAKE_SIGNING_TAG: &[u8] = b"nomos-stake";
+
 pub type Stake = u64;
+
+/// Change to the stake of a consensus node
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum StakeOp {
+    /// Lock `stake` from the balance of the signing account for `node`,
+    /// registering it if it was not staking yet
+    Bond { node: NodeId, stake: Stake },
+    /// Release `stake` out of what the signing account locked for `node` back to its balance
+    Unbond { node: NodeId, stake: Stake },
+    /// Forfeit all the stake of a node which signed conflicting messages.
+    /// The evidence is self certifying, so any account can report it.
+    Slash(Equivocation<HeaderId>),
+}
+
+/// Verified stake operation
+///
+/// Can only be constructed if it's signed by `account`, whose funds are locked or released,
+/// but does not imply that it can be successfully applied to the ledger.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StakeTransaction {
+    account: AccountId,
+    op: StakeOp,
+    // shares the sequence of the account transfers, so that the operation can't be replayed
+    nonce: Nonce,
+    signature: Signature,
+}
+
+impl StakeTransaction {
+    /// Build a stake operation on behalf of the account controlled by `secret_key`, signed with it
+    pub fn new(secret_key: &PrivateKey, op: StakeOp, nonce: Nonce) -> Self {
+        let account = AccountId::from_secret_key(secret_key);
+        let signature = Signature::sign(secret_key, &signed_bytes(&account, &op, nonce));
+        Self {
+            account,
+            op,
+            nonce,
+            signature,
+        }
+    }
+
+    pub fn account(&self) -> &AccountId {
+        &self.account
+    }
+
+    pub fn op(&self) -> &StakeOp {
+        &self.op
+    }
+
+    pub fn nonce(&self) -> Nonce {
+        self.nonce
+    }
+
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+}
+
+/// Bytes signed by the account: the signing tag followed by the account public key,
+/// the nonce as little endian u64 and the wire encoding of the operation.
+fn signed_bytes(account: &AccountId, op: &StakeOp, nonce: Nonce) -> Vec<u8> {
+    [
+        STAKE_SIGNING_TAG,
+        account.public_key(),
+        &nonce.to_le_bytes(),
+        &wire::serialize_with_limit(op, u64::MAX).expect("stake operations can be encoded"),
+    ]
+    .concat()
+}
+
+mod serde {
+    use super::*;
+    use ::serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    // Same as for transfers, the signature is checked while deserializing
+    #[derive(Serialize, Deserialize)]
+    struct WireStakeTransaction {
+        account: AccountId,
+        op: StakeOp,
+        nonce: Nonce,
+        signature: Signature,
+    }
+
+    impl<'de> Deserialize<'de> for StakeTransaction {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let WireStakeTransaction {
+                account,
+                op,
+                nonce,
+                signature,
+            } = WireStakeTransaction::deserialize(deserializer)?;
+            if !signature.verify(account.public_key(), &signed_bytes(&account, &op, nonce)) {
+                return Err(D::Error::custom("invalid stake signature"));
+            }
+            Ok(StakeTransaction {
+                account,
+                op,
+                nonce,
+                signature,
+            })
+        }
+    }
+
+    impl Serialize for StakeTransaction {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            WireStakeTransaction {
+                account: self.account,
+                op: self.op.clone(),
+                nonce: self.nonce,
+                signature: self.signature,
+            }
+            .serialize(serializer)
+        }
+    }
+}
+
+/// Transactions which may carry a [`StakeTransaction`]
+pub trait StakingTransaction {
+    fn stake(&self) -> Option<&StakeTransaction>;
+}
+
+// Opaque transactions, as used in tests and simulations, never change the stake
+impl StakingTransaction for [u8; 32] {
+    fn stake(&self) -> Option<&StakeTransaction> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsigned_operations_are_rejected() {
+        let node = NodeId::new([7; 32]);
+        let tx = StakeTransaction::new(&[1; 32], StakeOp::Bond { node, stake: 10 }, 0);
+        assert_eq!(tx.account(), &AccountId::from_secret_key(&[1; 32]));
+
+        let bytes = wire::serialize(&tx).unwrap();
+        assert_eq!(wire::deserialize::<StakeTransaction>(&bytes).unwrap(), tx);
+
+        // the signature is bound to the operation, so a bond can't be turned into an unbond
+        let tampered = StakeTransaction {
+            op: StakeOp::Unbond { node, stake: 10 },
+            ..tx.clone()
+        };
+        let bytes = wire::serialize(&tampered).unwrap();
+        assert!(wire::deserialize::<StakeTransaction>(&bytes).is_err());
+
+        // nor be moved to the funds of another account
+        let stolen = StakeTransaction {
+            account: AccountId::from_secret_key(&[2; 32]),
+            ..tx
+        };
+        let bytes = wire::serialize(&stolen).unwrap();
+        assert!(wire::deserialize::<StakeTransaction>(&bytes).is_err());
+    }
+}
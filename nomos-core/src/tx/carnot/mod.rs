@@ -3,17 +3,21 @@
 use bytes::Bytes;
 use serde::{Deserialize, Serialize};
 // internal
+use crate::execution::AccountTransaction;
+use crate::staking::{StakeTransaction, StakingTransaction};
 pub use crate::tx::carnot::transaction::TransferTransaction;
 use crate::tx::{Transaction, TransactionHasher};
+use crate::wire;
 
 mod transaction;
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum Tx {
     Transfer(TransferTransaction),
+    Stake(StakeTransaction),
 }
 
-// TODO: We should probably abstract the de/serialization of the transaction as it s done in transaction.rs
+// the hash covers the wire encoding of the whole transaction, signature included
 fn hash_carnot_tx(tx: &Tx) -> [u8; 32] {
     use blake2::{
         digest::{consts::U32, Digest},
@@ -30,6 +34,26 @@ impl Transaction for Tx {
     type Hash = [u8; 32];
 
     fn as_bytes(&self) -> Bytes {
-        [].to_vec().into()
+        wire::serialize(self)
+            .expect("carnot transaction serialization failed")
+            .into()
+    }
+}
+
+impl StakingTransaction for Tx {
+    fn stake(&self) -> Option<&StakeTransaction> {
+        match self {
+            Self::Stake(stake) => Some(stake),
+            Self::Transfer(_) => None,
+        }
+    }
+}
+
+impl AccountTransaction for Tx {
+    fn transfer(&self) -> Option<&TransferTransaction> {
+        match self {
+            Self::Transfer(transfer) => Some(transfer),
+            Self::Stake(_) => None,
+        }
     }
 }
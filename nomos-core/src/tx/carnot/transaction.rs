@@ -1,25 +1,97 @@
-use crate::account::AccountId;
-use crate::crypto::Signature;
+use crate::account::{AccountId, Balance, Nonce};
+use crate::crypto::{PrivateKey, Signature};
+
+const TRANSFER_SIGNING_TAG: &[u8] = b"nomos-transfer";
 
 /// Verified transactions
 ///
 /// Can only be constructed if the signature is valid,
 /// but does not imply that it can be successfully applied
 /// to the ledger.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct TransferTransaction {
-    pub from: AccountId,
-    pub to: AccountId,
-    pub value: u64,
+    from: AccountId,
+    to: AccountId,
+    value: Balance,
+    fee: Balance,
+    // position of the transaction among those sent by `from`, starting at 0,
+    // so that the same transfer can't be applied twice
+    nonce: Nonce,
     // TODO: here for the moment because I still want to retain the ability
     // to go from `Transaction` to wire format. We could otherwise
     // save the id and rely on some storage
-    _signature: Signature,
+    signature: Signature,
+}
+
+impl TransferTransaction {
+    /// Build a transfer from the account controlled by `secret_key`, signed with it
+    pub fn new(
+        secret_key: &PrivateKey,
+        to: AccountId,
+        value: Balance,
+        fee: Balance,
+        nonce: Nonce,
+    ) -> Self {
+        let from = AccountId::from_secret_key(secret_key);
+        let signature = Signature::sign(secret_key, &signed_bytes(&from, &to, value, fee, nonce));
+        Self {
+            from,
+            to,
+            value,
+            fee,
+            nonce,
+            signature,
+        }
+    }
+
+    pub fn from(&self) -> &AccountId {
+        &self.from
+    }
+
+    pub fn to(&self) -> &AccountId {
+        &self.to
+    }
+
+    pub fn value(&self) -> Balance {
+        self.value
+    }
+
+    pub fn fee(&self) -> Balance {
+        self.fee
+    }
+
+    pub fn nonce(&self) -> Nonce {
+        self.nonce
+    }
+
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+}
+
+/// Bytes signed by the sender: the signing tag followed by the sender and receiver public keys,
+/// and by the value, fee and nonce as little endian u64.
+fn signed_bytes(
+    from: &AccountId,
+    to: &AccountId,
+    value: Balance,
+    fee: Balance,
+    nonce: Nonce,
+) -> Vec<u8> {
+    [
+        TRANSFER_SIGNING_TAG,
+        from.public_key(),
+        to.public_key(),
+        &value.to_le_bytes(),
+        &fee.to_le_bytes(),
+        &nonce.to_le_bytes(),
+    ]
+    .concat()
 }
 
 mod serde {
     use super::*;
-    use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use ::serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
     // We have this additional definition so that we can automatically derive
     // Serialize/Deserialize for the type while still being able to check
     // the signature while deserializing.
@@ -29,7 +101,9 @@ mod serde {
     struct WireTransferTransaction {
         from: AccountId,
         to: AccountId,
-        value: u64,
+        value: Balance,
+        fee: Balance,
+        nonce: Nonce,
         signature: Signature,
     }
 
@@ -42,14 +116,23 @@ mod serde {
                 from,
                 to,
                 value,
+                fee,
+                nonce,
                 signature,
             } = WireTransferTransaction::deserialize(deserializer)?;
-            //TODO: check signature
+            if !signature.verify(
+                from.public_key(),
+                &signed_bytes(&from, &to, value, fee, nonce),
+            ) {
+                return Err(D::Error::custom("invalid transfer signature"));
+            }
             Ok(TransferTransaction {
                 from,
                 to,
                 value,
-                _signature: signature,
+                fee,
+                nonce,
+                signature,
             })
         }
     }
@@ -60,12 +143,71 @@ mod serde {
             S: Serializer,
         {
             WireTransferTransaction {
-                from: self.from.clone(),
-                to: self.to.clone(),
+                from: self.from,
+                to: self.to,
                 value: self.value,
-                signature: self._signature,
+                fee: self.fee,
+                nonce: self.nonce,
+                signature: self.signature,
             }
             .serialize(serializer)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wire;
+
+    #[test]
+    fn signature_checked_on_deserialization() {
+        let bob = AccountId::from_secret_key(&[2; 32]);
+        let tx = TransferTransaction::new(&[1; 32], bob, 10, 1, 0);
+        assert_eq!(tx.from(), &AccountId::from_secret_key(&[1; 32]));
+
+        let bytes = wire::serialize(&tx).unwrap();
+        assert_eq!(
+            wire::deserialize::<TransferTransaction>(&bytes).unwrap(),
+            tx
+        );
+        let json = serde_json::to_string(&tx).unwrap();
+        assert_eq!(
+            serde_json::from_str::<TransferTransaction>(&json).unwrap(),
+            tx
+        );
+        // owned input can't lend strings to the deserializer
+        assert_eq!(
+            serde_json::from_reader::<_, TransferTransaction>(json.as_bytes()).unwrap(),
+            tx
+        );
+        assert_eq!(
+            serde_json::from_value::<TransferTransaction>(serde_json::to_value(&tx).unwrap())
+                .unwrap(),
+            tx
+        );
+
+        // the signature covers every field: value, fee and nonce are the last 24 bytes
+        // before the signature
+        let signature_start = bytes.len() - 64;
+        for offset in [
+            0,
+            32,
+            signature_start - 24,
+            signature_start - 16,
+            signature_start - 8,
+        ] {
+            let mut tampered = bytes.clone();
+            tampered[offset] ^= 1;
+            assert!(wire::deserialize::<TransferTransaction>(&tampered).is_err());
+        }
+
+        // and is bound to the sender
+        let stolen = TransferTransaction {
+            from: AccountId::from_secret_key(&[3; 32]),
+            ..tx
+        };
+        let bytes = wire::serialize(&stolen).unwrap();
+        assert!(wire::deserialize::<TransferTransaction>(&bytes).is_err());
+    }
+}
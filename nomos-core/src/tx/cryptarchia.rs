@@ -0,0 +1,48 @@
+// std
+// crates
+use blake2::Digest;
+use bytes::Bytes;
+pub use cryptarchia_ledger::Tx;
+// internal
+use crate::crypto::Blake2b;
+use crate::tx::{Transaction, TransactionHasher};
+use crate::wire;
+
+// Signatures are part of the hash, which is also the mempool key, so that a copy of a transfer
+// with forged signatures can't take the place of the valid one.
+// Transfers signed more than once spend the same notes, so only one of them can be included.
+fn hash_cryptarchia_tx(tx: &Tx) -> [u8; 32] {
+    Blake2b::digest(tx.as_bytes()).into()
+}
+
+impl Transaction for Tx {
+    const HASHER: TransactionHasher<Self> = hash_cryptarchia_tx;
+    type Hash = [u8; 32];
+
+    fn as_bytes(&self) -> Bytes {
+        wire::serialize(self)
+            .expect("cryptarchia transaction serialization failed")
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cryptarchia_ledger::Coin;
+
+    #[test]
+    fn signatures_are_part_of_the_hash() {
+        let coin = Coin::new([1; 32], [0; 32].into(), 10.into());
+        let output = Coin::new([2; 32], [0; 32].into(), 10.into()).note();
+        let tx = Tx::new(&[coin], vec![output]);
+
+        // a copy with a forged signature, as anyone can build from a gossiped transaction
+        let mut forged = serde_json::to_value(&tx).unwrap();
+        forged["inputs"][0]["signature"] = "00".repeat(64).into();
+        let forged: Tx = serde_json::from_value(forged).unwrap();
+        assert!(!forged.verify_signatures());
+        assert_eq!(forged.digest(), tx.digest());
+        assert_ne!(forged.hash(), tx.hash());
+    }
+}
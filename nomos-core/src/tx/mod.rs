@@ -5,6 +5,7 @@ use bytes::Bytes;
 // internal
 
 pub mod carnot;
+pub mod cryptarchia;
 #[cfg(feature = "mock")]
 pub mod mock;
 pub mod select;
@@ -37,7 +37,7 @@ macro_rules! serde_bytes_newtype {
                 D: serde::Deserializer<'de>,
             {
                 if deserializer.is_human_readable() {
-                    let s = <&str>::deserialize(deserializer)?;
+                    let s = String::deserialize(deserializer)?;
                     const_hex::decode_to_array(s)
                         .map(Self)
                         .map_err(serde::de::Error::custom)
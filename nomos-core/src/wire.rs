@@ -1,4 +1,8 @@
 //! Serializer and Deserializer for wire formats.
+//!
+//! Messages exchanged between nodes are wrapped in an [`envelope`], which carries the version
+//! and type of the message and bounds its size.
+pub mod envelope;
 
 // TODO: we're using bincode for now, but might need strong guarantees about
 // the underlying format in the future for standardization.
@@ -13,6 +17,7 @@ use bincode::{
 use once_cell::sync::Lazy;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::io::Read;
 
 pub type Error = bincode::Error;
 // type composition is cool but also makes naming types a bit akward
@@ -25,13 +30,15 @@ type BincodeOptions = WithOtherTrailing<
 >;
 
 const DATA_LIMIT: u64 = 2048; // Do not serialize/deserialize more than 2Kb
-static OPTIONS: Lazy<BincodeOptions> = Lazy::new(|| {
+static OPTIONS: Lazy<BincodeOptions> = Lazy::new(|| options(DATA_LIMIT));
+
+fn options(limit: u64) -> BincodeOptions {
     bincode::DefaultOptions::new()
         .with_little_endian()
-        .with_limit(DATA_LIMIT)
+        .with_limit(limit)
         .with_fixint_encoding()
         .reject_trailing_bytes()
-});
+}
 
 type BincodeDeserializer<'de> = bincode::Deserializer<SliceReader<'de>, BincodeOptions>;
 type BincodeSerializer<T> = bincode::Serializer<T, BincodeOptions>;
@@ -87,6 +94,13 @@ pub fn serializer(buffer: &mut Vec<u8>) -> Serializer<&'_ mut Vec<u8>> {
     }
 }
 
+/// Return a serializer for wire format that fails if more than `limit` bytes are written.
+pub fn serializer_with_limit(buffer: &mut Vec<u8>, limit: u64) -> Serializer<&'_ mut Vec<u8>> {
+    Serializer {
+        inner: bincode::Serializer::new(buffer, options(limit)),
+    }
+}
+
 /// Return a serializer for wire format that overwrites (but now grow) the provided
 /// buffer.
 ///
@@ -111,6 +125,28 @@ pub fn deserialize<T: DeserializeOwned>(item: &[u8]) -> Result<T, Error> {
     deserializer(item).deserialize()
 }
 
+/// Serialize an object directly into a vec, failing if it takes more than `limit` bytes
+pub fn serialize_with_limit<T: Serialize>(item: &T, limit: u64) -> Result<Vec<u8>, Error> {
+    options(limit).serialize(item)
+}
+
+/// Deserialize an object directly, failing if it takes more than `limit` bytes
+pub fn deserialize_with_limit<T: DeserializeOwned>(item: &[u8], limit: u64) -> Result<T, Error> {
+    options(limit).deserialize(item)
+}
+
+/// Size in bytes of the serialized object, failing if it takes more than `limit` bytes
+pub fn serialized_size<T: Serialize>(item: &T, limit: u64) -> Result<u64, Error> {
+    options(limit).serialized_size(item)
+}
+
+/// Deserialize an object as it is read from `reader`, without buffering it first.
+///
+/// Reading stops at the end of the object, so trailing data is left in `reader`.
+pub fn deserialize_from<T: DeserializeOwned, R: Read>(reader: R, limit: u64) -> Result<T, Error> {
+    options(limit).deserialize_from(reader)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -153,4 +189,15 @@ mod tests {
         let deserialized = <String>::deserialize(deserializer.get_deserializer()).unwrap();
         assert_eq!(tmp, deserialized);
     }
+
+    #[test]
+    fn ser_de_limit() {
+        let tmp = vec![0u8; 4096];
+        assert!(serialize(&tmp).is_err());
+        let serialized = serialize_with_limit(&tmp, 8192).unwrap();
+        let deserialized: Vec<u8> = deserialize_with_limit(&serialized, 8192).unwrap();
+        assert_eq!(tmp, deserialized);
+        let streamed: Vec<u8> = deserialize_from(serialized.as_slice(), 8192).unwrap();
+        assert_eq!(tmp, streamed);
+    }
 }
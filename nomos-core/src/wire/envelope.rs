@@ -0,0 +1,400 @@
+//! Versioned envelope for messages exchanged between nodes.
+//!
+//! Every message is prefixed by a fixed size header:
+//!
+//! | offset | size | field                                          |
+//! |--------|------|------------------------------------------------|
+//! | 0      | 1    | magic byte, always `0x4e` (`'N'`)              |
+//! | 1      | 1    | format version, currently `1`                  |
+//! | 2      | 1    | message type, see [`message_type`]             |
+//! | 3      | 4    | payload length in bytes, little endian `u32`   |
+//! | 7      | len  | payload                                        |
+//!
+//! The payload is the message encoded with the version 1 format, which is the bincode 1
+//! encoding with the following rules:
+//! * integers are encoded with a fixed size, in little endian;
+//! * sequences, strings and maps are prefixed by their length as a `u64`;
+//! * enum variants are prefixed by their index as a `u32`;
+//! * options are prefixed by a `0` (`None`) or `1` (`Some`) byte;
+//! * structs and tuples are the concatenation of their fields, in declaration order.
+//!
+//! A message is rejected if the magic byte or the version are unknown, if its type is not
+//! the one expected, if the payload length exceeds the size limit of the message type, or if
+//! the payload is not exactly `len` bytes long. The header is checked before the payload is
+//! read, so that oversized messages are rejected without being buffered.
+// std
+use std::io::{self, Read};
+// crates
+use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+// internal
+use crate::wire;
+
+pub const MAGIC: u8 = 0x4e;
+pub const VERSION: u8 = 1;
+pub const HEADER_SIZE: usize = 7;
+
+/// Message types currently in use
+pub mod message_type {
+    pub const BLOCK: u8 = 0x01;
+    pub const CARNOT_PROPOSAL: u8 = 0x10;
+    pub const CARNOT_GOSSIP: u8 = 0x11;
+    pub const CARNOT_SYNC_REQUEST: u8 = 0x12;
+    pub const CARNOT_SYNC_ITEM: u8 = 0x13;
+    pub const CRYPTARCHIA_GOSSIP: u8 = 0x20;
+    pub const CRYPTARCHIA_SYNC_REQUEST: u8 = 0x21;
+}
+
+/// A message that can be sent in an envelope
+pub trait WireMessage: Serialize + DeserializeOwned {
+    /// Type of the message, written in the header
+    const MESSAGE_TYPE: u8;
+    /// Upper bound to the payload size, larger messages are neither encoded nor decoded
+    const MAX_SIZE: u64;
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("invalid magic byte {0:#04x}")]
+    BadMagic(u8),
+    #[error("unsupported wire format version {0}")]
+    UnsupportedVersion(u8),
+    #[error("expected message type {expected:#04x}, got {actual:#04x}")]
+    UnexpectedMessageType { expected: u8, actual: u8 },
+    #[error("message of {size} bytes exceeds the limit of {limit} bytes")]
+    TooLarge { size: u64, limit: u64 },
+    #[error("expected a payload of {expected} bytes, got {actual}")]
+    LengthMismatch { expected: u64, actual: u64 },
+    #[error("message header is truncated")]
+    Truncated,
+    #[error("invalid payload: {0}")]
+    Payload(#[from] wire::Error),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+impl From<Error> for io::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Io(error) => error,
+            error => io::Error::new(io::ErrorKind::InvalidData, error),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub version: u8,
+    pub message_type: u8,
+    pub length: u32,
+}
+
+impl Header {
+    pub fn new(message_type: u8, length: u32) -> Self {
+        Self {
+            version: VERSION,
+            message_type,
+            length,
+        }
+    }
+
+    pub fn encode(&self) -> [u8; HEADER_SIZE] {
+        let mut bytes = [0; HEADER_SIZE];
+        bytes[0] = MAGIC;
+        bytes[1] = self.version;
+        bytes[2] = self.message_type;
+        bytes[3..].copy_from_slice(&self.length.to_le_bytes());
+        bytes
+    }
+
+    /// Decode the header, checking the magic byte and the version
+    pub fn decode(bytes: &[u8; HEADER_SIZE]) -> Result<Self, Error> {
+        if bytes[0] != MAGIC {
+            return Err(Error::BadMagic(bytes[0]));
+        }
+        if bytes[1] != VERSION {
+            return Err(Error::UnsupportedVersion(bytes[1]));
+        }
+        let length = u32::from_le_bytes(bytes[3..].try_into().expect("4 bytes"));
+        Ok(Self {
+            version: bytes[1],
+            message_type: bytes[2],
+            length,
+        })
+    }
+
+    /// Check that the header announces a message of type `T` of at most `limit` bytes
+    fn check<T: WireMessage>(&self, limit: u64) -> Result<(), Error> {
+        if self.message_type != T::MESSAGE_TYPE {
+            return Err(Error::UnexpectedMessageType {
+                expected: T::MESSAGE_TYPE,
+                actual: self.message_type,
+            });
+        }
+        let size = u64::from(self.length);
+        if size > limit {
+            return Err(Error::TooLarge { size, limit });
+        }
+        Ok(())
+    }
+}
+
+/// Encode `item` in an envelope, with the size limit of its message type
+pub fn encode<T: WireMessage>(item: &T) -> Result<Vec<u8>, Error> {
+    encode_with_limit(item, T::MAX_SIZE)
+}
+
+/// Encode `item` in an envelope, failing if the payload takes more than `limit` bytes
+pub fn encode_with_limit<T: WireMessage>(item: &T, limit: u64) -> Result<Vec<u8>, Error> {
+    let limit = limit.min(u64::from(u32::MAX));
+    let size = wire::serialized_size(item, u64::MAX)?;
+    if size > limit {
+        return Err(Error::TooLarge { size, limit });
+    }
+    let header = Header::new(T::MESSAGE_TYPE, size as u32);
+    let mut bytes = Vec::with_capacity(HEADER_SIZE + size as usize);
+    bytes.extend_from_slice(&header.encode());
+    wire::serializer_with_limit(&mut bytes, size).serialize_into(item)?;
+    Ok(bytes)
+}
+
+/// Decode a message of type `T` from an envelope, with the size limit of its message type
+pub fn decode<T: WireMessage>(bytes: &[u8]) -> Result<T, Error> {
+    decode_with_limit(bytes, T::MAX_SIZE)
+}
+
+/// Decode a message of type `T` from an envelope, failing if the payload takes more than
+/// `limit` bytes
+pub fn decode_with_limit<T: WireMessage>(bytes: &[u8], limit: u64) -> Result<T, Error> {
+    let (header, payload) = bytes
+        .split_first_chunk::<HEADER_SIZE>()
+        .ok_or(Error::Truncated)?;
+    let header = Header::decode(header)?;
+    header.check::<T>(limit)?;
+    if payload.len() as u64 != u64::from(header.length) {
+        return Err(Error::LengthMismatch {
+            expected: header.length.into(),
+            actual: payload.len() as u64,
+        });
+    }
+    Ok(wire::deserialize_with_limit(payload, header.length.into())?)
+}
+
+/// Decode a message of type `T` as it is read from `reader`, without buffering the payload.
+///
+/// Exactly one envelope is consumed from `reader`.
+pub fn decode_from<T: WireMessage, R: Read>(mut reader: R) -> Result<T, Error> {
+    let mut header = [0; HEADER_SIZE];
+    reader.read_exact(&mut header).map_err(truncated)?;
+    let header = Header::decode(&header)?;
+    header.check::<T>(T::MAX_SIZE)?;
+    let length = u64::from(header.length);
+    let mut payload = reader.take(length);
+    let item = wire::deserialize_from(&mut payload, length)?;
+    if payload.limit() != 0 {
+        return Err(Error::LengthMismatch {
+            expected: length,
+            actual: length - payload.limit(),
+        });
+    }
+    Ok(item)
+}
+
+/// Read a message of type `T` from an async `reader`.
+///
+/// Returns `None` if `reader` is closed before a new envelope starts.
+pub async fn read_message<T: WireMessage, R: AsyncRead + Unpin>(
+    reader: &mut R,
+) -> Result<Option<T>, Error> {
+    let mut header = [0; HEADER_SIZE];
+    match reader.read_exact(&mut header).await {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    let header = Header::decode(&header)?;
+    header.check::<T>(T::MAX_SIZE)?;
+    let mut payload = vec![0; header.length as usize];
+    reader.read_exact(&mut payload).await?;
+    Ok(Some(wire::deserialize_with_limit(
+        &payload,
+        header.length.into(),
+    )?))
+}
+
+/// Write `item` in an envelope to an async `writer`
+pub async fn write_message<T: WireMessage, W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    item: &T,
+) -> Result<(), Error> {
+    let bytes = encode(item)?;
+    writer.write_all(&bytes).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+fn truncated(error: io::Error) -> Error {
+    match error.kind() {
+        io::ErrorKind::UnexpectedEof => Error::Truncated,
+        _ => Error::Io(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Message {
+        id: u32,
+        data: Vec<u8>,
+    }
+
+    impl WireMessage for Message {
+        const MESSAGE_TYPE: u8 = 0xff;
+        const MAX_SIZE: u64 = 1024;
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Other(u32);
+
+    impl WireMessage for Other {
+        const MESSAGE_TYPE: u8 = 0xfe;
+        const MAX_SIZE: u64 = 1024;
+    }
+
+    fn message(size: usize) -> Message {
+        Message {
+            id: 42,
+            data: vec![7; size],
+        }
+    }
+
+    #[test]
+    fn layout() {
+        let bytes = encode(&message(2)).unwrap();
+        assert_eq!(
+            bytes,
+            [
+                [MAGIC, VERSION, 0xff].as_slice(),
+                &14u32.to_le_bytes(),
+                &42u32.to_le_bytes(),
+                &2u64.to_le_bytes(),
+                &[7, 7],
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let bytes = encode(&message(512)).unwrap();
+        assert_eq!(decode::<Message>(&bytes).unwrap(), message(512));
+        assert_eq!(
+            decode_from::<Message, _>(bytes.as_slice()).unwrap(),
+            message(512)
+        );
+        let read = futures::executor::block_on(read_message::<Message, _>(&mut bytes.as_slice()));
+        assert_eq!(read.unwrap(), Some(message(512)));
+    }
+
+    #[test]
+    fn size_limits() {
+        assert!(matches!(
+            encode(&message(2048)),
+            Err(Error::TooLarge { limit: 1024, .. })
+        ));
+        let bytes = encode_with_limit(&message(2048), 4096).unwrap();
+        assert!(matches!(
+            decode::<Message>(&bytes),
+            Err(Error::TooLarge { limit: 1024, .. })
+        ));
+        assert!(matches!(
+            decode_from::<Message, _>(bytes.as_slice()),
+            Err(Error::TooLarge { limit: 1024, .. })
+        ));
+        assert_eq!(
+            decode_with_limit::<Message>(&bytes, 4096).unwrap(),
+            message(2048)
+        );
+    }
+
+    #[test]
+    fn rejects_malformed() {
+        let bytes = encode(&message(8)).unwrap();
+        assert!(matches!(
+            decode::<Message>(&bytes[..3]),
+            Err(Error::Truncated)
+        ));
+        assert!(matches!(
+            decode_from::<Message, _>(&bytes[..3]),
+            Err(Error::Truncated)
+        ));
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = 0;
+        assert!(matches!(
+            decode::<Message>(&bad_magic),
+            Err(Error::BadMagic(0))
+        ));
+
+        let mut bad_version = bytes.clone();
+        bad_version[1] = VERSION + 1;
+        assert!(matches!(
+            decode::<Message>(&bad_version),
+            Err(Error::UnsupportedVersion(_))
+        ));
+
+        assert!(matches!(
+            decode::<Other>(&bytes),
+            Err(Error::UnexpectedMessageType {
+                expected: 0xfe,
+                actual: 0xff
+            })
+        ));
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(matches!(
+            decode::<Message>(&trailing),
+            Err(Error::LengthMismatch { .. })
+        ));
+
+        // the header announces more bytes than the message takes
+        let mut longer = bytes.clone();
+        longer[3..HEADER_SIZE].copy_from_slice(&(bytes.len() as u32 - 6).to_le_bytes());
+        longer.push(0);
+        assert!(decode::<Message>(&longer).is_err());
+        assert!(matches!(
+            decode_from::<Message, _>(longer.as_slice()),
+            Err(Error::LengthMismatch { .. })
+        ));
+
+        assert!(matches!(
+            decode::<Message>(&bytes[..bytes.len() - 1]),
+            Err(Error::LengthMismatch { .. })
+        ));
+        assert!(decode_from::<Message, _>(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn read_until_closed() {
+        let mut bytes = encode(&message(1)).unwrap();
+        bytes.extend(encode(&message(2)).unwrap());
+        let mut reader = bytes.as_slice();
+        futures::executor::block_on(async {
+            assert_eq!(
+                read_message::<Message, _>(&mut reader).await.unwrap(),
+                Some(message(1))
+            );
+            assert_eq!(
+                read_message::<Message, _>(&mut reader).await.unwrap(),
+                Some(message(2))
+            );
+            assert_eq!(read_message::<Message, _>(&mut reader).await.unwrap(), None);
+        });
+    }
+}
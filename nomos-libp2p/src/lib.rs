@@ -125,6 +125,19 @@ impl Swarm {
             .unsubscribe(&gossipsub::IdentTopic::new(topic))
     }
 
+    /// Ignores messages from a peer, whether it's the author or just propagating them
+    pub fn blacklist_peer(&mut self, peer_id: &PeerId) {
+        self.swarm.behaviour_mut().gossipsub.blacklist_peer(peer_id);
+    }
+
+    /// Accepts messages from a previously blacklisted peer again
+    pub fn remove_blacklisted_peer(&mut self, peer_id: &PeerId) {
+        self.swarm
+            .behaviour_mut()
+            .gossipsub
+            .remove_blacklisted_peer(peer_id);
+    }
+
     /// Returns a reference to the underlying [`libp2p::Swarm`]
     pub fn swarm(&self) -> &libp2p::Swarm<Behaviour> {
         &self.swarm
@@ -6,7 +6,7 @@ use tokio::sync::oneshot;
 
 use carnot_consensus::{
     network::adapters::libp2p::Libp2pAdapter as ConsensusNetworkAdapter, CarnotConsensus,
-    CarnotInfo, ConsensusMsg,
+    CarnotInfo, ConsensusMsg, Equivocation,
 };
 use carnot_engine::{
     overlay::{RandomBeaconState, RoundRobin, TreeOverlay},
@@ -86,3 +86,40 @@ where
 
     Ok(receiver.await?)
 }
+
+pub async fn carnot_committed_blocks<Tx, SS, const SIZE: usize>(
+    handle: &OverwatchHandle,
+    limit: Option<usize>,
+) -> Result<Vec<HeaderId>, super::DynError>
+where
+    Tx: Transaction + Clone + Debug + Hash + Serialize + DeserializeOwned + Send + Sync + 'static,
+    <Tx as Transaction>::Hash: std::cmp::Ord + Debug + Send + Sync + 'static,
+    SS: StorageSerde + Send + Sync + 'static,
+{
+    let relay = handle.relay::<Carnot<Tx, SS, SIZE>>().connect().await?;
+    let (sender, receiver) = oneshot::channel();
+    relay
+        .send(ConsensusMsg::GetCommittedBlocks { limit, tx: sender })
+        .await
+        .map_err(|(e, _)| e)?;
+
+    Ok(receiver.await?)
+}
+
+pub async fn carnot_equivocations<Tx, SS, const SIZE: usize>(
+    handle: &OverwatchHandle,
+) -> Result<Vec<Equivocation>, super::DynError>
+where
+    Tx: Transaction + Clone + Debug + Hash + Serialize + DeserializeOwned + Send + Sync + 'static,
+    <Tx as Transaction>::Hash: std::cmp::Ord + Debug + Send + Sync + 'static,
+    SS: StorageSerde + Send + Sync + 'static,
+{
+    let relay = handle.relay::<Carnot<Tx, SS, SIZE>>().connect().await?;
+    let (sender, receiver) = oneshot::channel();
+    relay
+        .send(ConsensusMsg::GetEquivocations { tx: sender })
+        .await
+        .map_err(|(e, _)| e)?;
+
+    Ok(receiver.await?)
+}
@@ -0,0 +1,205 @@
+use std::{fmt::Debug, hash::Hash};
+
+use overwatch_rs::overwatch::handle::OverwatchHandle;
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::{broadcast, oneshot};
+
+use cryptarchia_consensus::{
+    events::ChainEvent, network::adapters::libp2p::LibP2pAdapter as ConsensusNetworkAdapter,
+    wallet::WalletInfo, ConsensusMsg, CryptarchiaConsensus, CryptarchiaInfo,
+};
+use cryptarchia_engine::Epoch;
+use cryptarchia_ledger::EpochState;
+use full_replication::Certificate;
+use nomos_core::{
+    da::{
+        blob,
+        certificate::{self, select::FillSize as FillSizeWithBlobsCertificate},
+    },
+    header::HeaderId,
+    tx::{select::FillSize as FillSizeWithTx, Transaction},
+};
+use nomos_mempool::{
+    backend::mockpool::MockPool, network::adapters::libp2p::Libp2pAdapter as MempoolNetworkAdapter,
+};
+use nomos_storage::backends::{sled::SledBackend, StorageSerde};
+
+pub type Cryptarchia<Tx, SS, const SIZE: usize> = CryptarchiaConsensus<
+    ConsensusNetworkAdapter<Tx, Certificate>,
+    MockPool<HeaderId, Tx, <Tx as Transaction>::Hash>,
+    MempoolNetworkAdapter<Tx, <Tx as Transaction>::Hash>,
+    MockPool<
+        HeaderId,
+        Certificate,
+        <<Certificate as certificate::Certificate>::Blob as blob::Blob>::Hash,
+    >,
+    MempoolNetworkAdapter<
+        Certificate,
+        <<Certificate as certificate::Certificate>::Blob as blob::Blob>::Hash,
+    >,
+    FillSizeWithTx<SIZE, Tx>,
+    FillSizeWithBlobsCertificate<SIZE, Certificate>,
+    SledBackend<SS>,
+>;
+
+pub async fn cryptarchia_info<Tx, SS, const SIZE: usize>(
+    handle: &OverwatchHandle,
+) -> Result<CryptarchiaInfo, super::DynError>
+where
+    Tx: Transaction
+        + Clone
+        + Debug
+        + Eq
+        + Hash
+        + Serialize
+        + DeserializeOwned
+        + Send
+        + Sync
+        + 'static,
+    <Tx as Transaction>::Hash: std::cmp::Ord + Debug + Send + Sync + 'static,
+    SS: StorageSerde + Send + Sync + 'static,
+{
+    let relay = handle
+        .relay::<Cryptarchia<Tx, SS, SIZE>>()
+        .connect()
+        .await?;
+    let (sender, receiver) = oneshot::channel();
+    relay
+        .send(ConsensusMsg::Info { tx: sender })
+        .await
+        .map_err(|(e, _)| e)?;
+
+    Ok(receiver.await?)
+}
+
+pub async fn cryptarchia_headers<Tx, SS, const SIZE: usize>(
+    handle: &OverwatchHandle,
+    from: Option<HeaderId>,
+    to: Option<HeaderId>,
+) -> Result<Vec<HeaderId>, super::DynError>
+where
+    Tx: Transaction
+        + Clone
+        + Debug
+        + Eq
+        + Hash
+        + Serialize
+        + DeserializeOwned
+        + Send
+        + Sync
+        + 'static,
+    <Tx as Transaction>::Hash: std::cmp::Ord + Debug + Send + Sync + 'static,
+    SS: StorageSerde + Send + Sync + 'static,
+{
+    let relay = handle
+        .relay::<Cryptarchia<Tx, SS, SIZE>>()
+        .connect()
+        .await?;
+    let (sender, receiver) = oneshot::channel();
+    relay
+        .send(ConsensusMsg::GetHeaders {
+            from,
+            to,
+            tx: sender,
+        })
+        .await
+        .map_err(|(e, _)| e)?;
+
+    Ok(receiver.await?)
+}
+
+pub async fn cryptarchia_wallet<Tx, SS, const SIZE: usize>(
+    handle: &OverwatchHandle,
+) -> Result<WalletInfo, super::DynError>
+where
+    Tx: Transaction
+        + Clone
+        + Debug
+        + Eq
+        + Hash
+        + Serialize
+        + DeserializeOwned
+        + Send
+        + Sync
+        + 'static,
+    <Tx as Transaction>::Hash: std::cmp::Ord + Debug + Send + Sync + 'static,
+    SS: StorageSerde + Send + Sync + 'static,
+{
+    let relay = handle
+        .relay::<Cryptarchia<Tx, SS, SIZE>>()
+        .connect()
+        .await?;
+    let (sender, receiver) = oneshot::channel();
+    relay
+        .send(ConsensusMsg::Wallet { tx: sender })
+        .await
+        .map_err(|(e, _)| e)?;
+
+    Ok(receiver.await?)
+}
+
+pub async fn cryptarchia_chain_events<Tx, SS, const SIZE: usize>(
+    handle: &OverwatchHandle,
+) -> Result<broadcast::Receiver<ChainEvent>, super::DynError>
+where
+    Tx: Transaction
+        + Clone
+        + Debug
+        + Eq
+        + Hash
+        + Serialize
+        + DeserializeOwned
+        + Send
+        + Sync
+        + 'static,
+    <Tx as Transaction>::Hash: std::cmp::Ord + Debug + Send + Sync + 'static,
+    SS: StorageSerde + Send + Sync + 'static,
+{
+    let relay = handle
+        .relay::<Cryptarchia<Tx, SS, SIZE>>()
+        .connect()
+        .await?;
+    let (sender, receiver) = oneshot::channel();
+    relay
+        .send(ConsensusMsg::SubscribeChainEvents { tx: sender })
+        .await
+        .map_err(|(e, _)| e)?;
+
+    Ok(receiver.await?)
+}
+
+pub async fn cryptarchia_epoch_state<Tx, SS, const SIZE: usize>(
+    handle: &OverwatchHandle,
+    epoch: Epoch,
+    block: Option<HeaderId>,
+) -> Result<Option<EpochState>, super::DynError>
+where
+    Tx: Transaction
+        + Clone
+        + Debug
+        + Eq
+        + Hash
+        + Serialize
+        + DeserializeOwned
+        + Send
+        + Sync
+        + 'static,
+    <Tx as Transaction>::Hash: std::cmp::Ord + Debug + Send + Sync + 'static,
+    SS: StorageSerde + Send + Sync + 'static,
+{
+    let relay = handle
+        .relay::<Cryptarchia<Tx, SS, SIZE>>()
+        .connect()
+        .await?;
+    let (sender, receiver) = oneshot::channel();
+    relay
+        .send(ConsensusMsg::EpochState {
+            epoch,
+            block,
+            tx: sender,
+        })
+        .await
+        .map_err(|(e, _)| e)?;
+
+    Ok(receiver.await?)
+}
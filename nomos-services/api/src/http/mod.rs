@@ -1,6 +1,7 @@
 pub type DynError = Box<dyn std::error::Error + Send + Sync + 'static>;
 pub mod cl;
 pub mod consensus;
+pub mod cryptarchia;
 pub mod da;
 pub mod libp2p;
 pub mod mempool;
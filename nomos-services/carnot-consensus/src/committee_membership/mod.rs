@@ -11,11 +11,15 @@ use carnot_engine::overlay::{
     CommitteeMembership, Error as RandomBeaconError, FreezeMembership, RandomBeaconState,
 };
 use nomos_core::block::Block;
+use nomos_core::staking::StakingTransaction;
+
+mod stake;
+pub use stake::StakeMembership;
 
 pub trait UpdateableCommitteeMembership: CommitteeMembership {
     type Error: Error;
 
-    fn on_new_block_received<Tx: Hash + Clone + Eq, Blob: Clone + Eq + Hash>(
+    fn on_new_block_received<Tx: Hash + Clone + Eq + StakingTransaction, Blob: Clone + Eq + Hash>(
         &self,
         block: &Block<Tx, Blob>,
     ) -> Result<Self, Self::Error>;
@@ -25,7 +29,10 @@ pub trait UpdateableCommitteeMembership: CommitteeMembership {
 impl UpdateableCommitteeMembership for FreezeMembership {
     type Error = Infallible;
 
-    fn on_new_block_received<Tx: Hash + Clone + Eq, Blob: Clone + Eq + Hash>(
+    fn on_new_block_received<
+        Tx: Hash + Clone + Eq + StakingTransaction,
+        Blob: Clone + Eq + Hash,
+    >(
         &self,
         _block: &Block<Tx, Blob>,
     ) -> Result<Self, Self::Error> {
@@ -40,7 +47,10 @@ impl UpdateableCommitteeMembership for FreezeMembership {
 impl UpdateableCommitteeMembership for RandomBeaconState {
     type Error = RandomBeaconError;
 
-    fn on_new_block_received<Tx: Hash + Clone + Eq, Blob: Clone + Eq + Hash>(
+    fn on_new_block_received<
+        Tx: Hash + Clone + Eq + StakingTransaction,
+        Blob: Clone + Eq + Hash,
+    >(
         &self,
         block: &Block<Tx, Blob>,
     ) -> Result<Self, Self::Error> {
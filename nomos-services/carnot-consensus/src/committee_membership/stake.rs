@@ -0,0 +1,357 @@
+// std
+use std::collections::BTreeMap;
+use std::hash::Hash;
+// crates
+use blake2::Digest;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+// internal
+use super::UpdateableCommitteeMembership;
+use crate::TimeoutQc;
+use carnot_engine::overlay::CommitteeMembership;
+use carnot_engine::{NodeId, View};
+use nomos_core::block::Block;
+use nomos_core::crypto::Blake2b;
+use nomos_core::staking::{Stake, StakeOp, StakeTransaction, StakingTransaction};
+
+const SORTITION_TAG: &[u8] = b"nomos-stake-sortition";
+
+/// Committee membership following the stake bonded by the nodes.
+///
+/// Stake changes are taken from the transactions of the received blocks, and only take effect
+/// at the beginning of the following epoch of `epoch_length` views. Blocks are only received
+/// once their transactions were executed, so every stake operation is signed and backed by
+/// funds locked in the account state.
+///
+/// The nodes with at least `min_stake` are eligible for the committees. If they are more than
+/// `max_nodes`, the seats are drawn among them proportionally to their stake. The committees
+/// are then shuffled by `M`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StakeMembership<M> {
+    inner: M,
+    epoch_length: u64,
+    min_stake: Stake,
+    max_nodes: usize,
+    epoch: u64,
+    stakes: BTreeMap<NodeId, Stake>,
+    nodes: Vec<NodeId>,
+}
+
+impl<M> StakeMembership<M> {
+    /// Membership for the first epoch, from the stake bonded at genesis
+    pub fn new(
+        inner: M,
+        epoch_length: u64,
+        min_stake: Stake,
+        max_nodes: usize,
+        stakes: impl IntoIterator<Item = (NodeId, Stake)>,
+    ) -> Self {
+        assert!(epoch_length > 0, "epochs must last at least one view");
+        assert!(max_nodes > 0, "committees need at least one node");
+        let mut membership = Self {
+            inner,
+            epoch_length,
+            min_stake,
+            max_nodes,
+            epoch: 0,
+            stakes: stakes.into_iter().filter(|(_, stake)| *stake > 0).collect(),
+            nodes: Vec::new(),
+        };
+        membership.nodes = membership.select_nodes();
+        membership
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Stake currently bonded by `node`, which might not be reflected in the committees until
+    /// the next epoch
+    pub fn stake(&self, node: &NodeId) -> Stake {
+        self.stakes.get(node).copied().unwrap_or_default()
+    }
+
+    pub fn stakes(&self) -> &BTreeMap<NodeId, Stake> {
+        &self.stakes
+    }
+
+    fn epoch_of(&self, view: View) -> u64 {
+        u64::try_from(i64::from(view)).unwrap_or_default() / self.epoch_length
+    }
+
+    /// Eligible nodes, or `max_nodes` of them drawn without replacement, each with a probability
+    /// proportional to its stake. The draw is seeded by the epoch, so that all nodes agree on it.
+    fn select_nodes(&self) -> Vec<NodeId> {
+        let mut candidates: Vec<(NodeId, Stake)> = self
+            .stakes
+            .iter()
+            .filter(|(_, stake)| **stake >= self.min_stake)
+            .map(|(node, stake)| (*node, *stake))
+            .collect();
+        if candidates.len() <= self.max_nodes {
+            return candidates.into_iter().map(|(node, _)| node).collect();
+        }
+        let seed = Blake2b::new_with_prefix(SORTITION_TAG)
+            .chain_update(self.epoch.to_le_bytes())
+            .finalize();
+        let mut rng = ChaCha20Rng::from_seed(seed.into());
+        // stakes are only removed once they reach 0, so the total is positive while
+        // there are candidates left
+        let mut total: u128 = candidates.iter().map(|(_, stake)| u128::from(*stake)).sum();
+        let mut nodes = Vec::with_capacity(self.max_nodes);
+        while nodes.len() < self.max_nodes {
+            let mut target = rng.gen_range(0..total);
+            let index = candidates
+                .iter()
+                .position(|(_, stake)| {
+                    let stake = u128::from(*stake);
+                    if target < stake {
+                        return true;
+                    }
+                    target -= stake;
+                    false
+                })
+                .expect("target is below the total stake");
+            let (node, stake) = candidates.remove(index);
+            total -= u128::from(stake);
+            nodes.push(node);
+        }
+        nodes.sort();
+        nodes
+    }
+
+    fn apply(&mut self, op: &StakeOp) {
+        match op {
+            StakeOp::Bond { node, stake } => {
+                let bonded = self.stakes.entry(*node).or_default();
+                *bonded = bonded.saturating_add(*stake);
+            }
+            StakeOp::Unbond { node, stake } => {
+                if let Some(bonded) = self.stakes.get_mut(node) {
+                    *bonded = bonded.saturating_sub(*stake);
+                    if *bonded == 0 {
+                        self.stakes.remove(node);
+                    }
+                }
+            }
+            StakeOp::Slash(evidence) => {
+                if evidence.verify() {
+                    self.stakes.remove(&evidence.offender);
+                } else {
+                    tracing::debug!("ignoring invalid equivocation evidence");
+                }
+            }
+        }
+    }
+
+    fn advance_to(&mut self, view: View) {
+        let epoch = self.epoch_of(view);
+        if epoch <= self.epoch {
+            return;
+        }
+        self.epoch = epoch;
+        let nodes = self.select_nodes();
+        // keep the previous committees rather than stalling consensus with no nodes at all
+        if nodes.is_empty() {
+            tracing::warn!("no node has enough stake for epoch {epoch}, keeping the current nodes");
+            return;
+        }
+        self.nodes = nodes;
+    }
+}
+
+impl<M: CommitteeMembership> CommitteeMembership for StakeMembership<M> {
+    fn reshape_committees(&self, nodes: &mut [NodeId]) {
+        self.inner.reshape_committees(nodes)
+    }
+
+    fn nodes(&self) -> Option<&[NodeId]> {
+        Some(&self.nodes)
+    }
+}
+
+impl<M: UpdateableCommitteeMembership> UpdateableCommitteeMembership for StakeMembership<M> {
+    type Error = M::Error;
+
+    fn on_new_block_received<
+        Tx: Hash + Clone + Eq + StakingTransaction,
+        Blob: Clone + Eq + Hash,
+    >(
+        &self,
+        block: &Block<Tx, Blob>,
+    ) -> Result<Self, Self::Error> {
+        let mut membership = Self {
+            inner: self.inner.on_new_block_received(block)?,
+            ..self.clone()
+        };
+        // stake bonded in the first block of an epoch only counts from the next one
+        membership.advance_to(block.header().carnot().view());
+        block
+            .transactions()
+            .filter_map(StakingTransaction::stake)
+            .for_each(|tx| membership.apply(StakeTransaction::op(tx)));
+        Ok(membership)
+    }
+
+    fn on_timeout_qc_received(&self, qc: &TimeoutQc) -> Result<Self, Self::Error> {
+        let mut membership = Self {
+            inner: self.inner.on_timeout_qc_received(qc)?,
+            ..self.clone()
+        };
+        membership.advance_to(qc.view().next());
+        Ok(membership)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use carnot_engine::overlay::{FreezeMembership, RoundRobin, TreeOverlay, TreeOverlaySettings};
+    use carnot_engine::{Overlay, QcSignature, StandardQc};
+
+    use super::*;
+    use crate::crypto::node_id;
+    use crate::network::messages::VoteMsg;
+    use crate::Vote;
+    use bls_signatures::PrivateKey;
+
+    fn node(i: u8) -> NodeId {
+        NodeId::new([i; 32])
+    }
+
+    fn timeout_qc(view: i64) -> TimeoutQc {
+        TimeoutQc::new(
+            View::new(view),
+            StandardQc::genesis([0; 32].into()),
+            node(0),
+            QcSignature::default(),
+        )
+    }
+
+    #[test]
+    fn stake_changes_apply_from_next_epoch() {
+        let mut membership =
+            StakeMembership::new(FreezeMembership, 10, 5, 10, [(node(1), 5), (node(2), 10)]);
+        assert_eq!(membership.nodes(), Some(&[node(1), node(2)][..]));
+
+        membership.apply(&StakeOp::Bond {
+            node: node(3),
+            stake: 5,
+        });
+        membership.apply(&StakeOp::Unbond {
+            node: node(1),
+            stake: 3,
+        });
+        assert_eq!(membership.stake(&node(1)), 2);
+        assert_eq!(membership.stake(&node(3)), 5);
+
+        membership.advance_to(View::new(9));
+        assert_eq!(membership.epoch(), 0);
+        assert_eq!(membership.nodes(), Some(&[node(1), node(2)][..]));
+
+        membership.advance_to(View::new(10));
+        assert_eq!(membership.epoch(), 1);
+        assert_eq!(membership.nodes(), Some(&[node(2), node(3)][..]));
+    }
+
+    #[test]
+    fn seats_are_drawn_by_stake() {
+        let stakes = [(node(1), 1), (node(2), 1000), (node(3), 1000)];
+        let mut membership = StakeMembership::new(FreezeMembership, 1, 1, 2, stakes);
+        let mut seats = BTreeMap::<NodeId, usize>::new();
+        for view in 0..100 {
+            membership.advance_to(View::new(view));
+            let nodes = membership.nodes().unwrap();
+            assert_eq!(nodes.len(), 2);
+            for node in nodes {
+                *seats.entry(*node).or_default() += 1;
+            }
+        }
+        assert!(seats.get(&node(1)).copied().unwrap_or_default() < 10);
+        assert!(seats[&node(2)] > 90);
+        assert!(seats[&node(3)] > 90);
+
+        // the draw only depends on the epoch
+        let mut other = StakeMembership::new(FreezeMembership, 1, 1, 2, stakes);
+        other.advance_to(View::new(99));
+        assert_eq!(other.nodes(), membership.nodes());
+    }
+
+    #[test]
+    fn unbonding_everything_removes_the_node() {
+        let mut membership = StakeMembership::new(FreezeMembership, 10, 1, 10, [(node(1), 5)]);
+        membership.apply(&StakeOp::Unbond {
+            node: node(1),
+            stake: 10,
+        });
+        assert!(membership.stakes().is_empty());
+
+        // nobody is left to run consensus, so the current nodes stay
+        membership.advance_to(View::new(20));
+        assert_eq!(membership.epoch(), 2);
+        assert_eq!(membership.nodes(), Some(&[node(1)][..]));
+    }
+
+    #[test]
+    fn equivocating_nodes_are_slashed() {
+        let key = PrivateKey::new([1; 32]);
+        let offender = node_id(&key.public_key());
+        let vote = |block: [u8; 32]| {
+            let vote = Vote {
+                view: View::new(1),
+                block: block.into(),
+            };
+            VoteMsg::new(offender, vote, None, &key)
+        };
+        let mut membership =
+            StakeMembership::new(FreezeMembership, 10, 1, 10, [(offender, 5), (node(2), 5)]);
+
+        // voting twice for the same block is fine
+        assert_eq!(vote([1; 32]).equivocation(&vote([1; 32])), None);
+        let mut evidence = vote([1; 32]).equivocation(&vote([2; 32])).unwrap();
+        evidence.view = View::new(2);
+        membership.apply(&StakeOp::Slash(evidence));
+        assert_eq!(membership.stake(&offender), 5);
+
+        let evidence = vote([1; 32]).equivocation(&vote([2; 32])).unwrap();
+        membership.apply(&StakeOp::Slash(evidence));
+        assert_eq!(membership.stake(&offender), 0);
+        membership.advance_to(View::new(10));
+        assert_eq!(membership.nodes(), Some(&[node(2)][..]));
+    }
+
+    #[test]
+    fn overlay_follows_stake_at_epoch_boundaries() {
+        let stakes = (1..=10).map(|i| (node(i), 1));
+        let membership = StakeMembership::new(FreezeMembership, 10, 1, 20, stakes);
+        let overlay = TreeOverlay::new(TreeOverlaySettings {
+            nodes: Vec::new(),
+            current_leader: node(1),
+            number_of_committees: 3,
+            leader: RoundRobin::new(),
+            committee_membership: membership,
+            super_majority_threshold: None,
+        });
+        assert_eq!(overlay.leader_super_majority_threshold(node(1)), 7);
+
+        let overlay = overlay
+            .update_committees(|mut membership| {
+                membership.apply(&StakeOp::Bond {
+                    node: node(11),
+                    stake: 1,
+                });
+                membership.apply(&StakeOp::Bond {
+                    node: node(12),
+                    stake: 1,
+                });
+                membership.on_timeout_qc_received(&timeout_qc(5))
+            })
+            .unwrap();
+        assert_eq!(overlay.leader_super_majority_threshold(node(1)), 7);
+
+        let overlay = overlay
+            .update_committees(|membership| membership.on_timeout_qc_received(&timeout_qc(9)))
+            .unwrap();
+        assert_eq!(overlay.leader_super_majority_threshold(node(1)), 8);
+    }
+}
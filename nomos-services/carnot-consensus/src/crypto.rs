@@ -0,0 +1,72 @@
+// std
+// crates
+use bls_signatures::{PrivateKey, PublicKey, Serialize as _, Signature};
+use serde::{Deserialize, Serialize};
+// internal
+use crate::NodeId;
+
+/// Id of the node owning the given consensus key, see [`NodeId::from_public_key`]
+pub fn node_id(public_key: &PublicKey) -> NodeId {
+    NodeId::from_public_key(public_key)
+}
+
+/// Id of the node using `private_key` as consensus key, see [`CarnotSettings`](crate::CarnotSettings)
+pub fn node_id_from_private_key(private_key: [u8; 32]) -> NodeId {
+    node_id(&PrivateKey::new(private_key).public_key())
+}
+
+/// Signature of a consensus message along with the public key of the signer.
+/// Both are kept in their compressed form, and are only checked when the signature is verified.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct NodeSignature {
+    public_key: Box<[u8]>,
+    signature: Box<[u8]>,
+}
+
+impl NodeSignature {
+    pub fn sign(private_key: &PrivateKey, message: &[u8]) -> Self {
+        Self {
+            public_key: private_key.public_key().as_bytes().into(),
+            signature: private_key.sign(message).as_bytes().into(),
+        }
+    }
+
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    /// Whether `message` was signed by the node with id `signer`
+    pub fn verify(&self, signer: &NodeId, message: &[u8]) -> bool {
+        let Ok(public_key) = PublicKey::from_bytes(&self.public_key) else {
+            return false;
+        };
+        let Ok(signature) = Signature::from_bytes(&self.signature) else {
+            return false;
+        };
+        node_id(&public_key) == *signer && public_key.verify(signature, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signatures_are_bound_to_the_signer() {
+        let key = PrivateKey::new([1; 32]);
+        let other = PrivateKey::new([2; 32]);
+        let signer = node_id_from_private_key([1; 32]);
+        let signature = NodeSignature::sign(&key, b"vote");
+
+        assert!(signature.verify(&signer, b"vote"));
+        assert!(!signature.verify(&signer, b"timeout"));
+        assert!(!signature.verify(&node_id(&other.public_key()), b"vote"));
+        // a valid signature by another node cannot be attributed to `signer`
+        assert!(!NodeSignature::sign(&other, b"vote").verify(&signer, b"vote"));
+        assert!(!NodeSignature::default().verify(&signer, b"vote"));
+    }
+}
@@ -1,11 +1,13 @@
 pub mod committee_membership;
+pub mod crypto;
 pub mod leader_selection;
 pub mod network;
+pub mod pacemaker;
 mod tally;
 mod task_manager;
 
 // std
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::pin::Pin;
@@ -15,21 +17,24 @@ use bls_signatures::PrivateKey;
 pub use carnot_engine::NodeId;
 use futures::{Stream, StreamExt};
 use leader_selection::UpdateableLeaderSelection;
+use pacemaker::{BackoffSettings, Pacemaker};
 use serde::Deserialize;
 use serde::{de::DeserializeOwned, Serialize};
 use serde_with::serde_as;
-use tokio::sync::oneshot::Sender;
+use thiserror::Error;
+use tokio::sync::{mpsc, oneshot::Sender};
 use tracing::{error, instrument};
 // internal
 use crate::network::messages::{
-    NetworkMessage, NewViewMsg, ProposalMsg, TimeoutMsg, TimeoutQcMsg, VoteMsg,
+    NetworkMessage, NewViewMsg, ProposalMsg, SyncItem, SyncRequest, TimeoutMsg, TimeoutQcMsg,
+    ViewSync, VoteMsg,
 };
 use crate::network::NetworkAdapter;
 use crate::tally::{
     happy::CarnotTally, timeout::TimeoutTally, unhappy::NewViewTally, CarnotTallySettings,
 };
 use carnot_engine::{
-    overlay::RandomBeaconState, Carnot, Committee, LeaderProof, Overlay, Payload, View,
+    overlay::RandomBeaconState, Carnot, Committee, LeaderProof, Overlay, Payload, QcSignature, View,
 };
 use task_manager::TaskManager;
 
@@ -37,7 +42,9 @@ use crate::committee_membership::UpdateableCommitteeMembership;
 use nomos_core::block::builder::BlockBuilder;
 use nomos_core::block::Block;
 use nomos_core::da::certificate::{BlobCertificateSelect, Certificate};
+use nomos_core::execution::{self, AccountTransaction, State as AccountState};
 use nomos_core::header::{carnot::Builder, HeaderId};
+use nomos_core::staking::StakingTransaction;
 use nomos_core::tx::{Transaction, TxSelect};
 use nomos_core::vote::Tally;
 use nomos_mempool::{
@@ -58,6 +65,24 @@ const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
 // Limit the number of blocks returned by GetBlocks
 // Approx 64KB of data
 const BLOCKS_LIMIT: usize = 512;
+// Maximum number of proposals and timeout qcs exchanged in a single sync request
+const SYNC_BATCH_SIZE: u64 = 128;
+const SYNC_BUFFER_SIZE: usize = 16;
+// Timeout qcs are stored by view under this prefix, so that they can be served to lagging nodes
+const TIMEOUT_QC_KEY: &str = "carnot/timeout_qc";
+// Equivocations detected so far, see `ConsensusMsg::GetEquivocations`
+const EQUIVOCATIONS_KEY: &str = "carnot/equivocations";
+// Latest consensus state, to resume from it after a restart
+const CHECKPOINT_KEY: &str = "carnot/checkpoint";
+// Headers of committed blocks are indexed by view under this prefix
+const COMMITTED_KEY: &str = "carnot/committed";
+// Blocks and timeout qcs that moved the node to a new view, indexed by view. They are replayed
+// on the overlay after a restart, as leader selection and committees depend on them.
+const OVERLAY_BLOCK_KEY: &str = "carnot/overlay/block";
+const OVERLAY_TIMEOUT_QC_KEY: &str = "carnot/overlay/timeout_qc";
+// Account state of the latest committed block, together with its id. The states of the
+// pending blocks are rebuilt after a restart by executing them again.
+const ACCOUNT_STATE_KEY: &str = "carnot/accounts";
 
 fn default_timeout() -> Duration {
     DEFAULT_TIMEOUT
@@ -72,6 +97,22 @@ type Qc = carnot_engine::Qc<HeaderId>;
 type StandardQc = carnot_engine::StandardQc<HeaderId>;
 type Vote = carnot_engine::Vote<HeaderId>;
 type Timeout = carnot_engine::Timeout<HeaderId>;
+pub type Equivocation = carnot_engine::Equivocation<HeaderId>;
+type Checkpoint = carnot_engine::Checkpoint<HeaderId>;
+type Error = carnot_engine::Error<HeaderId>;
+// Account state after each safe block from the latest committed one on. The states are
+// persistent maps, so they share most of their structure.
+type AccountStates = HashMap<HeaderId, AccountState>;
+
+#[derive(Debug, Error)]
+enum BlockError {
+    #[error("Consensus error: {0}")]
+    Consensus(#[from] Error),
+    #[error("Execution error: {0}")]
+    Execution(#[from] execution::Error),
+    #[error("Account state of block {0:?} not found")]
+    StateMissing(HeaderId),
+}
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CarnotSettings<O: Overlay, Ts, Bs> {
@@ -79,10 +120,16 @@ pub struct CarnotSettings<O: Overlay, Ts, Bs> {
     pub overlay_settings: O::Settings,
     #[serde(default = "default_timeout")]
     pub timeout: Duration,
+    /// How the timeout grows while views keep timing out, see [`Pacemaker`]
+    #[serde(default)]
+    pub backoff: BackoffSettings,
     #[serde(default)]
     pub transaction_selector_settings: Ts,
     #[serde(default)]
     pub blob_selector_settings: Bs,
+    /// Account balances at the genesis block
+    #[serde(default)]
+    pub genesis_accounts: AccountState,
 }
 
 impl<O: Overlay, Ts: Clone, Bs: Clone> Clone for CarnotSettings<O, Ts, Bs> {
@@ -91,8 +138,10 @@ impl<O: Overlay, Ts: Clone, Bs: Clone> Clone for CarnotSettings<O, Ts, Bs> {
             private_key: self.private_key,
             overlay_settings: self.overlay_settings.clone(),
             timeout: self.timeout,
+            backoff: self.backoff,
             transaction_selector_settings: self.transaction_selector_settings.clone(),
             blob_selector_settings: self.blob_selector_settings.clone(),
+            genesis_accounts: self.genesis_accounts.clone(),
         }
     }
 }
@@ -105,13 +154,17 @@ impl<O: Overlay, Ts, Bs> CarnotSettings<O, Ts, Bs> {
         transaction_selector_settings: Ts,
         blob_selector_settings: Bs,
         timeout: Duration,
+        backoff: BackoffSettings,
+        genesis_accounts: AccountState,
     ) -> Self {
         Self {
             private_key,
             overlay_settings,
             timeout,
+            backoff,
             transaction_selector_settings,
             blob_selector_settings,
+            genesis_accounts,
         }
     }
 }
@@ -177,6 +230,8 @@ where
     DaPool: MemPool<BlockId = HeaderId> + Send + Sync + 'static,
     DaPool::Settings: Send + Sync + 'static,
     ClPool::Item: Transaction<Hash = ClPool::Key>
+        + StakingTransaction
+        + AccountTransaction
         + Debug
         + Clone
         + Eq
@@ -253,8 +308,10 @@ where
             private_key,
             overlay_settings,
             timeout,
+            backoff,
             transaction_selector_settings,
             blob_selector_settings,
+            genesis_accounts,
         } = self.service_state.settings_reader.get_updated_settings();
 
         let overlay = O::new(overlay_settings);
@@ -266,70 +323,48 @@ where
                 leader_id: NodeId::new([0; 32]),
             },
         };
-        let mut carnot = Carnot::from_genesis(NodeId::new(private_key), genesis, overlay);
+        let node_id = crypto::node_id_from_private_key(private_key);
+        let mut accounts = AccountStates::from([(genesis.id, genesis_accounts)]);
+        let mut carnot = Carnot::from_genesis(node_id, genesis, overlay).with_qc_verification();
         let adapter = A::new(network_relay).await;
         let private_key = PrivateKey::new(private_key);
-        let self_committee = carnot.self_committee();
-        let leader_committee = [carnot.id()].into_iter().collect::<Committee>();
-        let tally_settings = CarnotTallySettings {
-            threshold: carnot.super_majority_threshold(),
-            participating_nodes: carnot.child_committees().into_iter().flatten().collect(),
-        };
-        let leader_tally_settings = CarnotTallySettings {
-            threshold: carnot.leader_super_majority_threshold(),
-            participating_nodes: carnot.root_committee(),
-        };
 
         let tx_selector = TxS::new(transaction_selector_settings);
         let blob_selector = BS::new(blob_selector_settings);
 
         let mut task_manager = TaskManager::new();
+        let mut pacemaker = Pacemaker::new(timeout, backoff);
+        let (synced_items_sender, mut synced_items) = mpsc::channel(SYNC_BUFFER_SIZE);
 
-        let genesis_block = carnot.genesis_block();
-        Self::process_view_change(
-            carnot.clone(),
-            genesis_block.view.prev(),
-            &mut task_manager,
-            adapter.clone(),
-            timeout,
-        )
-        .await;
-        // we already have the genesis block, no need to wait for it
-        task_manager.push(
-            genesis_block.view,
-            Self::gather_votes(
+        if let Some(checkpoint) = load(&storage_relay, CHECKPOINT_KEY).await {
+            carnot = Self::recover(carnot, checkpoint, &mut accounts, &storage_relay).await;
+            Self::process_view_change(
+                carnot.clone(),
+                carnot.current_view().prev(),
+                &mut task_manager,
                 adapter.clone(),
-                self_committee.clone(),
-                genesis_block.clone(),
-                tally_settings.clone(),
-            ),
-        );
-
-        if carnot.is_next_leader() {
-            tracing::info!("is next leader, gathering vores");
-            let network_adapter = adapter.clone();
-            task_manager.push(genesis_block.view.next(), async move {
-                let Event::Approve { qc, .. } = Self::gather_votes(
-                    network_adapter,
-                    leader_committee.clone(),
-                    genesis_block,
-                    leader_tally_settings.clone(),
-                )
-                .await
-                else {
-                    tracing::debug!("Failed to gather initial votes");
-                    return Event::None;
-                };
-                tracing::info!("got enough votes");
-                Event::ProposeBlock { qc }
-            });
+                &mut pacemaker,
+            )
+            .await;
+            // catch up with what happened while we were down
+            Self::request_sync(&carnot, None, adapter.clone(), synced_items_sender.clone());
+        } else {
+            Self::start_from_genesis(&carnot, &mut task_manager, adapter.clone(), &mut pacemaker)
+                .await;
         }
+        let mut sync_requests = adapter.sync_requests_stream().await;
+        let mut equivocations = adapter.equivocations_stream().await;
+        let mut view_sync = adapter.view_sync_stream().await;
+        let mut offences: Vec<Equivocation> = load(&storage_relay, EQUIVOCATIONS_KEY)
+            .await
+            .unwrap_or_default();
         let mut lifecycle_stream = self.service_state.lifecycle_handle.message_stream();
         loop {
             tokio::select! {
                     Some(event) = task_manager.next() => {
                         carnot = Self::process_carnot_event(
                             carnot,
+                            &mut accounts,
                             event,
                             &mut task_manager,
                             adapter.clone(),
@@ -337,14 +372,55 @@ where
                             cl_mempool_relay.clone(),
                             da_mempool_relay.clone(),
                             storage_relay.clone(),
+                            synced_items_sender.clone(),
                             tx_selector.clone(),
                             blob_selector.clone(),
-                            timeout,
+                            &mut pacemaker,
+                        )
+                        .await
+                    }
+                    Some(items) = synced_items.recv() => {
+                        carnot = Self::process_synced_items(
+                            carnot,
+                            &mut accounts,
+                            items,
+                            &mut task_manager,
+                            adapter.clone(),
+                            storage_relay.clone(),
+                            cl_mempool_relay.clone(),
+                            da_mempool_relay.clone(),
+                            synced_items_sender.clone(),
+                            &mut pacemaker,
                         )
                         .await
                     }
+                    Some(msg) = view_sync.next() => {
+                        carnot = Self::process_view_sync(
+                            carnot,
+                            &mut accounts,
+                            msg,
+                            &mut task_manager,
+                            adapter.clone(),
+                            storage_relay.clone(),
+                            synced_items_sender.clone(),
+                            &mut pacemaker,
+                        )
+                        .await
+                    }
+                    Some((request, reply)) = sync_requests.next() => {
+                        Self::serve_sync_request(&carnot, request, reply, storage_relay.clone());
+                    }
+                    Some(evidence) = equivocations.next() => {
+                        Self::process_equivocation(
+                            &adapter,
+                            &storage_relay,
+                            &mut offences,
+                            evidence,
+                        )
+                        .await;
+                    }
                     Some(msg) = self.service_state.inbound_relay.next() => {
-                        Self::process_message(&carnot, msg);
+                        Self::process_message(&carnot, &offences, msg, storage_relay.clone());
                     }
                     Some(msg) = lifecycle_stream.next() => {
                         if Self::should_stop_service(msg).await {
@@ -361,6 +437,12 @@ where
 #[allow(clippy::large_enum_variant)]
 enum Output<Tx: Clone + Eq + Hash, BlobCertificate: Clone + Eq + Hash> {
     Send(carnot_engine::Send<HeaderId>),
+    /// Sent to the next leader as well
+    ForwardToLeader {
+        send: carnot_engine::Send<HeaderId>,
+        leader: NodeId,
+        qc: Option<Qc>,
+    },
     BroadcastTimeoutQc {
         timeout_qc: TimeoutQc,
     },
@@ -378,6 +460,8 @@ where
     DaPool: MemPool<BlockId = HeaderId> + Send + Sync + 'static,
     DaPool::Settings: Send + Sync + 'static,
     ClPool::Item: Transaction<Hash = ClPool::Key>
+        + StakingTransaction
+        + AccountTransaction
         + Debug
         + Clone
         + Eq
@@ -423,7 +507,97 @@ where
         }
     }
 
-    fn process_message(carnot: &Carnot<O, HeaderId>, msg: ConsensusMsg) {
+    /// Start following consensus from the genesis block, which every node already has
+    async fn start_from_genesis(
+        carnot: &Carnot<O, HeaderId>,
+        task_manager: &mut TaskManager<View, Event<ClPool::Item, DaPool::Item>>,
+        adapter: A,
+        pacemaker: &mut Pacemaker,
+    ) {
+        let self_committee = carnot.self_committee();
+        let leader_committee = [carnot.id()].into_iter().collect::<Committee>();
+        let tally_settings = CarnotTallySettings {
+            threshold: carnot.super_majority_threshold(),
+            participating_nodes: carnot.child_committees().into_iter().flatten().collect(),
+            subtrees: Vec::new(),
+        };
+        let leader_tally_settings = CarnotTallySettings::for_leader(carnot);
+
+        let genesis_block = carnot.genesis_block();
+        Self::process_view_change(
+            carnot.clone(),
+            genesis_block.view.prev(),
+            task_manager,
+            adapter.clone(),
+            pacemaker,
+        )
+        .await;
+        // we already have the genesis block, no need to wait for it
+        task_manager.push(
+            genesis_block.view,
+            Self::gather_votes(
+                adapter.clone(),
+                self_committee.clone(),
+                genesis_block.clone(),
+                tally_settings.clone(),
+            ),
+        );
+
+        if carnot.is_next_leader() {
+            tracing::info!("is next leader, gathering vores");
+            let network_adapter = adapter.clone();
+            task_manager.push(genesis_block.view.next(), async move {
+                let Event::Approve { qc, .. } = Self::gather_votes(
+                    network_adapter,
+                    leader_committee.clone(),
+                    genesis_block,
+                    leader_tally_settings.clone(),
+                )
+                .await
+                else {
+                    tracing::debug!("Failed to gather initial votes");
+                    return Event::None;
+                };
+                tracing::info!("got enough votes");
+                Event::ProposeBlock { qc }
+            });
+        }
+    }
+
+    /// Keep track of offences not known yet, and let other nodes know about them
+    async fn process_equivocation(
+        adapter: &A,
+        storage_relay: &OutboundRelay<StorageMsg<Storage>>,
+        offences: &mut Vec<Equivocation>,
+        evidence: Equivocation,
+    ) {
+        // the same offence can be proven by different pairs of messages
+        if offences.iter().any(|known| {
+            known.offender == evidence.offender
+                && known.view == evidence.view
+                && known.kind == evidence.kind
+        }) {
+            return;
+        }
+        tracing::warn!(
+            "{:?} equivocated in view {} ({:?})",
+            evidence.offender,
+            evidence.view,
+            evidence.kind
+        );
+        offences.push(evidence.clone());
+        store(storage_relay, EQUIVOCATIONS_KEY, &*offences).await;
+        adapter
+            .broadcast(NetworkMessage::Equivocation(evidence))
+            .await;
+    }
+
+    fn process_message(
+        carnot: &Carnot<O, HeaderId>,
+        offences: &[Equivocation],
+        msg: ConsensusMsg,
+        storage_relay: OutboundRelay<StorageMsg<Storage>>,
+    ) {
         match msg {
             ConsensusMsg::Info { tx } => {
                 let info = CarnotInfo {
@@ -442,24 +616,69 @@ where
             ConsensusMsg::GetBlocks { from, to, tx } => {
                 // default to tip block if not present
                 let from = from.unwrap_or(carnot.tip().id);
+                let genesis = carnot.genesis_block();
                 // default to genesis block if not present
-                let to = to.unwrap_or(carnot.genesis_block().id);
-
-                let mut res = Vec::new();
-                let mut cur = from;
-                let blocks = carnot.safe_blocks();
+                let to = to.unwrap_or(genesis.id);
+
+                tokio::spawn(async move {
+                    let mut res = Vec::new();
+                    let mut cur = from;
+                    loop {
+                        // genesis is the only block that is not in storage
+                        let block = if cur == genesis.id {
+                            Some(genesis.clone())
+                        } else {
+                            load::<_, Block<ClPool::Item, DaPool::Item>>(&storage_relay, cur)
+                                .await
+                                .map(|block| block.header().carnot().to_carnot_block())
+                        };
+                        let Some(block) = block else {
+                            break;
+                        };
+                        res.push(block.clone());
+                        // limit the response size
+                        if cur == to || cur == genesis.id || res.len() >= BLOCKS_LIMIT {
+                            break;
+                        }
+                        cur = block.parent();
+                    }
 
-                while let Some(block) = blocks.get(&cur) {
-                    res.push(block.clone());
-                    // limit the response size
-                    if cur == to || cur == carnot.genesis_block().id || res.len() >= BLOCKS_LIMIT {
-                        break;
+                    tx.send(res).unwrap_or_else(|_| {
+                        tracing::error!("could not send blocks through channel")
+                    });
+                });
+            }
+            ConsensusMsg::GetCommittedBlocks { limit, tx } => {
+                let latest_committed_view = carnot.latest_committed_view();
+                let genesis = carnot.genesis_block();
+                let limit = limit.unwrap_or(BLOCKS_LIMIT).min(BLOCKS_LIMIT);
+
+                tokio::spawn(async move {
+                    let mut res = Vec::new();
+                    let mut view = latest_committed_view;
+                    while res.len() < limit {
+                        if view == genesis.view {
+                            res.push(genesis.id);
+                            break;
+                        }
+                        let block: Option<carnot_engine::Block<HeaderId>> =
+                            load(&storage_relay, committed_key(view)).await;
+                        let Some(block) = block else {
+                            break;
+                        };
+                        res.push(block.id);
+                        view = block.parent_qc.high_qc().view;
                     }
-                    cur = block.parent();
-                }
 
-                tx.send(res)
-                    .unwrap_or_else(|_| tracing::error!("could not send blocks through channel"));
+                    tx.send(res).unwrap_or_else(|_| {
+                        tracing::error!("could not send committed blocks through channel")
+                    });
+                });
+            }
+            ConsensusMsg::GetEquivocations { tx } => {
+                tx.send(offences.to_vec()).unwrap_or_else(|_| {
+                    tracing::error!("could not send equivocations through channel")
+                });
             }
         }
     }
@@ -467,6 +686,7 @@ where
     #[allow(clippy::too_many_arguments)]
     async fn process_carnot_event(
         mut carnot: Carnot<O, HeaderId>,
+        accounts: &mut AccountStates,
         event: Event<ClPool::Item, DaPool::Item>,
         task_manager: &mut TaskManager<View, Event<ClPool::Item, DaPool::Item>>,
         adapter: A,
@@ -474,38 +694,49 @@ where
         cl_mempool_relay: OutboundRelay<MempoolMsg<HeaderId, ClPool::Item, ClPool::Key>>,
         da_mempool_relay: OutboundRelay<MempoolMsg<HeaderId, DaPool::Item, DaPool::Key>>,
         storage_relay: OutboundRelay<StorageMsg<Storage>>,
+        synced_items: mpsc::Sender<Vec<SyncItem>>,
         tx_selector: TxS,
         blobl_selector: BS,
-        timeout: Duration,
+        pacemaker: &mut Pacemaker,
     ) -> Carnot<O, HeaderId> {
         let mut output = None;
+        let prev_state = carnot.clone();
         let prev_view = carnot.current_view();
         match event {
             Event::Proposal { block, stream } => {
                 (carnot, output) = Self::process_block(
                     carnot,
+                    accounts,
                     block,
                     stream,
                     task_manager,
                     adapter.clone(),
-                    storage_relay,
+                    storage_relay.clone(),
                     cl_mempool_relay,
                     da_mempool_relay,
+                    synced_items,
                 )
                 .await;
             }
-            Event::Approve { block, .. } => {
+            Event::Approve { qc, block, .. } => {
                 tracing::debug!("approving proposal {:?}", block);
-                let (new_carnot, out) = carnot.approve_block(block);
-                carnot = new_carnot;
-                output = Some(Output::Send::<ClPool::Item, DaPool::Item>(out));
+                match carnot.approve_block(block) {
+                    Ok((new_carnot, out)) => {
+                        carnot = new_carnot;
+                        output = Some(Self::approval_output(&carnot, out, Some(qc)));
+                    }
+                    Err(e) => tracing::debug!("could not approve proposal: {e}"),
+                }
             }
             Event::LocalTimeout { view } => {
                 tracing::debug!("local timeout");
                 let (new_carnot, out) = carnot.local_timeout();
                 carnot = new_carnot;
                 output = out.map(Output::Send);
+                // we might be stuck because we missed some proposals or timeout qcs
+                Self::request_sync(&carnot, None, adapter.clone(), synced_items);
                 // keep timeout until the situation is resolved
+                let timeout = pacemaker.timeout();
                 task_manager.push(view, async move {
                     tokio::time::sleep(timeout).await;
                     Event::LocalTimeout { view }
@@ -514,6 +745,7 @@ where
             Event::NewView {
                 timeout_qc,
                 new_views,
+                ..
             } => {
                 (carnot, output) = Self::approve_new_view(
                     carnot,
@@ -525,18 +757,33 @@ where
                 .await;
             }
             Event::TimeoutQc { timeout_qc } => {
-                (carnot, output) =
-                    Self::receive_timeout_qc(carnot, timeout_qc, task_manager, adapter.clone())
-                        .await;
+                store(
+                    &storage_relay,
+                    timeout_qc_key(timeout_qc.view()),
+                    &timeout_qc,
+                )
+                .await;
+                (carnot, output) = Self::receive_timeout_qc(
+                    carnot,
+                    timeout_qc,
+                    task_manager,
+                    adapter.clone(),
+                    &storage_relay,
+                )
+                .await;
             }
-            Event::RootTimeout { timeouts } => {
-                (carnot, output) = Self::process_root_timeout(carnot, timeouts).await;
+            Event::RootTimeout {
+                timeouts,
+                signature,
+            } => {
+                (carnot, output) = Self::process_root_timeout(carnot, timeouts, signature).await;
             }
             Event::ProposeBlock { qc } => {
                 output = Self::propose_block(
                     carnot.id(),
                     private_key,
                     qc,
+                    accounts,
                     tx_selector.clone(),
                     blobl_selector.clone(),
                     cl_mempool_relay,
@@ -554,13 +801,27 @@ where
                 prev_view,
                 task_manager,
                 adapter.clone(),
-                timeout,
+                pacemaker,
             )
             .await;
         }
 
+        if let Some(Output::BroadcastTimeoutQc { timeout_qc }) = &output {
+            store(
+                &storage_relay,
+                timeout_qc_key(timeout_qc.view()),
+                timeout_qc,
+            )
+            .await;
+        }
+        // votes and timeouts must be persisted before they are sent
+        Self::persist_state(&prev_state, &carnot, accounts, &storage_relay).await;
         if let Some(output) = output {
-            handle_output(&adapter, carnot.id(), output).await;
+            if !flush(&storage_relay).await {
+                tracing::error!("could not persist consensus state, not sending its output");
+                return carnot;
+            }
+            handle_output(&adapter, carnot.id(), &private_key, output).await;
         }
 
         carnot
@@ -571,16 +832,19 @@ where
         level = "debug",
         skip(
             carnot,
+            accounts,
             adapter,
             task_manager,
             stream,
             storage_relay,
             cl_mempool_relay,
-            da_mempool_relay
+            da_mempool_relay,
+            synced_items
         )
     )]
     async fn process_block(
         mut carnot: Carnot<O, HeaderId>,
+        accounts: &mut AccountStates,
         block: Block<ClPool::Item, DaPool::Item>,
         mut stream: Pin<Box<dyn Stream<Item = Block<ClPool::Item, DaPool::Item>> + Send>>,
         task_manager: &mut TaskManager<View, Event<ClPool::Item, DaPool::Item>>,
@@ -588,6 +852,7 @@ where
         storage_relay: OutboundRelay<StorageMsg<Storage>>,
         cl_mempool_relay: OutboundRelay<MempoolMsg<HeaderId, ClPool::Item, ClPool::Key>>,
         da_mempool_relay: OutboundRelay<MempoolMsg<HeaderId, DaPool::Item, DaPool::Key>>,
+        synced_items: mpsc::Sender<Vec<SyncItem>>,
     ) -> (
         Carnot<O, HeaderId>,
         Option<Output<ClPool::Item, DaPool::Item>>,
@@ -606,65 +871,50 @@ where
         let tally_settings = CarnotTallySettings {
             threshold: carnot.super_majority_threshold(),
             participating_nodes: carnot.child_committees().into_iter().flatten().collect(),
+            subtrees: Vec::new(),
         };
-        let leader_tally_settings = CarnotTallySettings {
-            threshold: carnot.leader_super_majority_threshold(),
-            // TODO: add children of root committee
-            participating_nodes: carnot.root_committee(),
-        };
-
-        match carnot.receive_block(block.to_carnot_block()) {
-            Ok(mut new_state) => {
-                let new_view = new_state.current_view();
-                let msg = <StorageMsg<_>>::new_store_message(block.id(), original_block.clone());
-                if let Err((e, _msg)) = storage_relay.send(msg).await {
-                    tracing::error!("Could not send block to storage: {e}");
-                }
+        let leader_tally_settings = CarnotTallySettings::for_leader(&carnot);
 
-                // remove included content from mempool
-                mark_in_block(
-                    cl_mempool_relay,
-                    original_block.transactions().map(Transaction::hash),
-                    block.id(),
-                )
-                .await;
-
-                mark_in_block(
-                    da_mempool_relay,
-                    original_block.blobs().map(Certificate::hash),
-                    block.id(),
-                )
-                .await;
-
-                if new_view != carnot.current_view() {
-                    task_manager.push(
-                        block.view(),
-                        Self::gather_votes(
-                            adapter.clone(),
-                            self_committee,
-                            block.to_carnot_block(),
-                            tally_settings,
-                        ),
-                    );
-                    new_state = Self::update_overlay(
-                        new_state,
-                        |leader_selection| leader_selection.on_new_block_received(&original_block),
-                        |committee_membership| {
-                            committee_membership.on_new_block_received(&original_block)
-                        },
-                    );
-                } else {
-                    task_manager.push(block.view(), async move {
-                        if let Some(block) = stream.next().await {
-                            Event::Proposal { block, stream }
-                        } else {
-                            Event::None
-                        }
-                    });
-                }
+        match Self::apply_block(
+            &carnot,
+            accounts,
+            &original_block,
+            &storage_relay,
+            cl_mempool_relay,
+            da_mempool_relay,
+        )
+        .await
+        {
+            Ok(new_state) => {
+                task_manager.push(
+                    block.view(),
+                    Self::gather_votes(
+                        adapter.clone(),
+                        self_committee,
+                        block.to_carnot_block(),
+                        tally_settings,
+                    ),
+                );
                 carnot = new_state;
             }
-            Err(_) => tracing::debug!("invalid block {:?}", block),
+            Err(BlockError::Consensus(Error::DuplicateBlock(_))) => {
+                task_manager.push(block.view(), async move {
+                    if let Some(block) = stream.next().await {
+                        Event::Proposal { block, stream }
+                    } else {
+                        Event::None
+                    }
+                });
+            }
+            // we missed some proposals or timeout qcs before this one
+            Err(BlockError::Consensus(
+                e @ (Error::ParentMissing(_) | Error::FutureView { .. }),
+            )) => {
+                tracing::debug!("{e}, syncing with peers up to proposal {:?}", block.id());
+                Self::request_sync(&carnot, Some(block.id()), adapter, synced_items);
+                return (carnot, None);
+            }
+            Err(e) => tracing::debug!("invalid block {:?}: {e}", block),
         }
 
         if carnot.is_next_leader() {
@@ -686,6 +936,471 @@ where
         (carnot, None)
     }
 
+    /// Validate `block` and add it to `carnot` and its account state to `accounts`, then persist
+    /// it, remove its content from the mempools and update the overlay if the view changed
+    async fn apply_block(
+        carnot: &Carnot<O, HeaderId>,
+        accounts: &mut AccountStates,
+        original_block: &Block<ClPool::Item, DaPool::Item>,
+        storage_relay: &OutboundRelay<StorageMsg<Storage>>,
+        cl_mempool_relay: OutboundRelay<MempoolMsg<HeaderId, ClPool::Item, ClPool::Key>>,
+        da_mempool_relay: OutboundRelay<MempoolMsg<HeaderId, DaPool::Item, DaPool::Key>>,
+    ) -> Result<Carnot<O, HeaderId>, BlockError> {
+        let block = original_block.header().carnot();
+        let mut new_state = carnot.receive_block(block.to_carnot_block())?;
+        let block_accounts = accounts
+            .get(&block.parent())
+            .ok_or(BlockError::StateMissing(block.parent()))?
+            .clone()
+            .try_apply_block(original_block.transactions(), block.state_root())?;
+        accounts.insert(block.id(), block_accounts);
+        let msg = <StorageMsg<_>>::new_store_message(block.id(), original_block.clone());
+        if let Err((e, _msg)) = storage_relay.send(msg).await {
+            tracing::error!("Could not send block to storage: {e}");
+        }
+
+        // remove included content from mempool
+        mark_in_block(
+            cl_mempool_relay,
+            original_block.transactions().map(Transaction::hash),
+            block.id(),
+        )
+        .await;
+
+        mark_in_block(
+            da_mempool_relay,
+            original_block.blobs().map(Certificate::hash),
+            block.id(),
+        )
+        .await;
+
+        if new_state.current_view() != carnot.current_view() {
+            new_state = Self::update_overlay(
+                new_state,
+                |leader_selection| leader_selection.on_new_block_received(original_block),
+                |committee_membership| committee_membership.on_new_block_received(original_block),
+            );
+            store(storage_relay, overlay_block_key(block.view()), block.id()).await;
+        }
+        Ok(new_state)
+    }
+
+    /// Persist the state needed to resume from `carnot` after a restart, together with the blocks
+    /// committed since `prev` and the account state of the latest committed block.
+    /// Account states of blocks older than the latest committed one are dropped.
+    async fn persist_state(
+        prev: &Carnot<O, HeaderId>,
+        carnot: &Carnot<O, HeaderId>,
+        accounts: &mut AccountStates,
+        storage_relay: &OutboundRelay<StorageMsg<Storage>>,
+    ) {
+        let checkpoint = carnot.checkpoint();
+        if checkpoint == prev.checkpoint() {
+            return;
+        }
+        let latest_committed = &checkpoint.latest_committed_block;
+        if latest_committed.view > prev.latest_committed_view() {
+            let safe_blocks = carnot.safe_blocks();
+            accounts.retain(|id, _| {
+                safe_blocks
+                    .get(id)
+                    .is_some_and(|block| block.view >= latest_committed.view)
+            });
+            match accounts.get(&latest_committed.id) {
+                Some(state) => {
+                    store(
+                        storage_relay,
+                        ACCOUNT_STATE_KEY,
+                        (latest_committed.id, state),
+                    )
+                    .await
+                }
+                None => tracing::error!(
+                    "no account state for committed block {:?}",
+                    latest_committed.id
+                ),
+            }
+        }
+        let mut committed = Vec::new();
+        let mut block = Some(checkpoint.latest_committed_block.clone());
+        while let Some(current) = block.filter(|block| block.view > prev.latest_committed_view()) {
+            block = carnot.safe_blocks().get(&current.parent()).cloned();
+            committed.push(current);
+        }
+        for block in committed.into_iter().rev() {
+            store(storage_relay, committed_key(block.view), block).await;
+        }
+        store(storage_relay, CHECKPOINT_KEY, checkpoint).await;
+    }
+
+    /// Rebuild the state persisted before a restart on top of `carnot`, which starts from genesis.
+    /// The overlay is brought back to the state it had at `checkpoint` by replaying the blocks and
+    /// timeout qcs that moved the node through each view, and the account states of the pending
+    /// blocks are rebuilt by executing them on top of the one of the latest committed block.
+    async fn recover(
+        mut carnot: Carnot<O, HeaderId>,
+        checkpoint: Checkpoint,
+        accounts: &mut AccountStates,
+        storage_relay: &OutboundRelay<StorageMsg<Storage>>,
+    ) -> Carnot<O, HeaderId> {
+        let committed_id = checkpoint.latest_committed_block.id;
+        if let Some((id, state)) =
+            load::<_, (HeaderId, AccountState)>(storage_relay, ACCOUNT_STATE_KEY).await
+        {
+            if id == committed_id {
+                accounts.insert(id, state);
+            }
+        }
+        for block in &checkpoint.pending_blocks {
+            // pending blocks are sorted by view, so parents are always executed first
+            let Some(parent_accounts) = accounts.get(&block.parent()) else {
+                continue;
+            };
+            let Some(stored): Option<Block<ClPool::Item, DaPool::Item>> =
+                load(storage_relay, block.id).await
+            else {
+                continue;
+            };
+            let header = stored.header().carnot();
+            match parent_accounts
+                .clone()
+                .try_apply_block(stored.transactions(), header.state_root())
+            {
+                Ok(state) => {
+                    accounts.insert(block.id, state);
+                }
+                Err(e) => {
+                    tracing::error!("could not execute pending block {:?}: {e}", block.id);
+                }
+            }
+        }
+        if !accounts.contains_key(&committed_id) {
+            tracing::warn!("no account state for committed block {committed_id:?}");
+        }
+        let mut view = carnot.current_view();
+        while view < checkpoint.current_view {
+            let id: Option<HeaderId> = load(storage_relay, overlay_block_key(view.next())).await;
+            let block: Option<Block<ClPool::Item, DaPool::Item>> = match id {
+                Some(id) => load(storage_relay, id).await,
+                None => None,
+            };
+            if let Some(block) = block {
+                carnot = Self::update_overlay(
+                    carnot,
+                    |leader_selection| leader_selection.on_new_block_received(&block),
+                    |committee_membership| committee_membership.on_new_block_received(&block),
+                );
+            } else if let Some(timeout_qc) =
+                load::<_, TimeoutQc>(storage_relay, overlay_timeout_qc_key(view)).await
+            {
+                carnot = Self::update_overlay(
+                    carnot,
+                    |leader_selection| leader_selection.on_timeout_qc_received(&timeout_qc),
+                    |committee_membership| committee_membership.on_timeout_qc_received(&timeout_qc),
+                );
+            } else {
+                tracing::warn!("no record of how view {view} ended, the overlay may be off");
+            }
+            view = view.next();
+        }
+        tracing::info!(
+            "resuming from view {} with latest committed view {}",
+            checkpoint.current_view,
+            checkpoint.latest_committed_block.view
+        );
+        Carnot::from_checkpoint(
+            carnot.id(),
+            carnot.genesis_block(),
+            checkpoint,
+            carnot.overlay().clone(),
+        )
+        .with_qc_verification()
+    }
+
+    /// Fetch from peers the proposals and timeout qcs following our latest committed block, up to
+    /// the proposal `to` if given. The fetched items are sent through `synced_items`.
+    fn request_sync(
+        carnot: &Carnot<O, HeaderId>,
+        to: Option<HeaderId>,
+        adapter: A,
+        synced_items: mpsc::Sender<Vec<SyncItem>>,
+    ) {
+        let request = SyncRequest {
+            from: carnot.latest_committed_view(),
+            to,
+            limit: SYNC_BATCH_SIZE,
+        };
+        tokio::spawn(async move {
+            let items = adapter.request_sync(request).await;
+            if items.is_empty() {
+                return;
+            }
+            if let Err(e) = synced_items.send(items).await {
+                tracing::error!("Could not send synced items to consensus: {e}");
+            }
+        });
+    }
+
+    /// Replay proposals and timeout qcs fetched from peers in view order, until one of them
+    /// can't be applied. Proposals go through the same validation as the ones received while
+    /// following consensus, so that the node never accepts a chain with invalid QCs.
+    #[allow(clippy::too_many_arguments)]
+    async fn process_synced_items(
+        mut carnot: Carnot<O, HeaderId>,
+        accounts: &mut AccountStates,
+        items: Vec<SyncItem>,
+        task_manager: &mut TaskManager<View, Event<ClPool::Item, DaPool::Item>>,
+        adapter: A,
+        storage_relay: OutboundRelay<StorageMsg<Storage>>,
+        cl_mempool_relay: OutboundRelay<MempoolMsg<HeaderId, ClPool::Item, ClPool::Key>>,
+        da_mempool_relay: OutboundRelay<MempoolMsg<HeaderId, DaPool::Item, DaPool::Key>>,
+        synced_items: mpsc::Sender<Vec<SyncItem>>,
+        pacemaker: &mut Pacemaker,
+    ) -> Carnot<O, HeaderId> {
+        let prev_state = carnot.clone();
+        let prev_view = carnot.current_view();
+        let full_batch = items.len() as u64 >= SYNC_BATCH_SIZE;
+        for item in items {
+            match item {
+                SyncItem::TimeoutQc(timeout_qc) => {
+                    let new_state = match carnot.receive_timeout_qc(timeout_qc.clone()) {
+                        Ok(new_state) => new_state,
+                        // we already moved past this view
+                        Err(Error::OldView { .. }) => continue,
+                        Err(e) => {
+                            tracing::debug!("invalid synced timeout qc: {e}");
+                            break;
+                        }
+                    };
+                    carnot = Self::update_overlay(
+                        new_state,
+                        |leader_selection| leader_selection.on_timeout_qc_received(&timeout_qc),
+                        |committee_membership| {
+                            committee_membership.on_timeout_qc_received(&timeout_qc)
+                        },
+                    );
+                    store(
+                        &storage_relay,
+                        timeout_qc_key(timeout_qc.view()),
+                        &timeout_qc,
+                    )
+                    .await;
+                    store(
+                        &storage_relay,
+                        overlay_timeout_qc_key(timeout_qc.view()),
+                        &timeout_qc,
+                    )
+                    .await;
+                }
+                SyncItem::Proposal(proposal) => {
+                    let block =
+                        match Block::<ClPool::Item, DaPool::Item>::from_bytes(&proposal.data) {
+                            Ok(block) => block,
+                            Err(e) => {
+                                tracing::debug!(
+                                    "malformed synced proposal {:?}: {e}",
+                                    proposal.proposal
+                                );
+                                break;
+                            }
+                        };
+                    let header = block.header().carnot();
+                    if block.header().id() != proposal.proposal
+                        || !carnot.safe_blocks().contains_key(&header.parent())
+                    {
+                        tracing::debug!(
+                            "synced proposal {:?} does not extend our chain",
+                            proposal.proposal
+                        );
+                        break;
+                    }
+                    if carnot.safe_blocks().contains_key(&header.id()) {
+                        continue;
+                    }
+                    match Self::apply_block(
+                        &carnot,
+                        accounts,
+                        &block,
+                        &storage_relay,
+                        cl_mempool_relay.clone(),
+                        da_mempool_relay.clone(),
+                    )
+                    .await
+                    {
+                        Ok(new_state) => carnot = new_state,
+                        Err(e) => {
+                            tracing::debug!("invalid synced proposal {:?}: {e}", header.id());
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        if carnot.current_view() != prev_view {
+            tracing::info!("synced from view {prev_view} to {}", carnot.current_view());
+            Self::process_view_change(
+                carnot.clone(),
+                prev_view,
+                task_manager,
+                adapter.clone(),
+                pacemaker,
+            )
+            .await;
+            if full_batch {
+                Self::request_sync(&carnot, None, adapter, synced_items);
+            }
+        }
+        Self::persist_state(&prev_state, &carnot, accounts, &storage_relay).await;
+        carnot
+    }
+
+    /// Catch up with the rest of the network if `msg` shows it already moved past our view.
+    /// After a timeout qc whose high qc we know we can jump straight to the following view,
+    /// otherwise we fetch the missing proposals and timeout qcs from peers.
+    #[allow(clippy::too_many_arguments)]
+    async fn process_view_sync(
+        mut carnot: Carnot<O, HeaderId>,
+        accounts: &mut AccountStates,
+        msg: ViewSync,
+        task_manager: &mut TaskManager<View, Event<ClPool::Item, DaPool::Item>>,
+        adapter: A,
+        storage_relay: OutboundRelay<StorageMsg<Storage>>,
+        synced_items: mpsc::Sender<Vec<SyncItem>>,
+        pacemaker: &mut Pacemaker,
+    ) -> Carnot<O, HeaderId> {
+        let prev_view = carnot.current_view();
+        if msg.view() <= prev_view {
+            return carnot;
+        }
+        let validation = match &msg {
+            ViewSync::Qc(qc) => carnot.validate_qc(qc),
+            ViewSync::TimeoutQc(timeout_qc) => carnot.validate_timeout_qc(timeout_qc),
+        };
+        if let Err(e) = validation {
+            tracing::debug!("ignoring qc for view {}: {e}", msg.view());
+            return carnot;
+        }
+        if !pacemaker.should_sync(prev_view, msg.view()) {
+            return carnot;
+        }
+        match msg {
+            ViewSync::TimeoutQc(timeout_qc)
+                if carnot.safe_blocks().contains_key(&timeout_qc.high_qc().id) =>
+            {
+                tracing::info!(
+                    "jumping from view {prev_view} to {}",
+                    timeout_qc.view().next()
+                );
+                let prev_state = carnot.clone();
+                store(
+                    &storage_relay,
+                    timeout_qc_key(timeout_qc.view()),
+                    &timeout_qc,
+                )
+                .await;
+                (carnot, _) = Self::receive_timeout_qc(
+                    carnot,
+                    timeout_qc,
+                    task_manager,
+                    adapter.clone(),
+                    &storage_relay,
+                )
+                .await;
+                Self::persist_state(&prev_state, &carnot, accounts, &storage_relay).await;
+                Self::process_view_change(
+                    carnot.clone(),
+                    prev_view,
+                    task_manager,
+                    adapter,
+                    pacemaker,
+                )
+                .await;
+            }
+            msg => {
+                tracing::info!(
+                    "view {} is ahead of ours ({prev_view}), syncing with peers",
+                    msg.view()
+                );
+                Self::request_sync(&carnot, None, adapter, synced_items);
+            }
+        }
+        carnot
+    }
+
+    /// Serve the proposals on our chain after view `from`, up to `to` or our tip, together with
+    /// the timeout qcs of the views in between, so that the requester can replay them in order
+    fn serve_sync_request(
+        carnot: &Carnot<O, HeaderId>,
+        request: SyncRequest,
+        reply: Sender<Vec<SyncItem>>,
+        storage_relay: OutboundRelay<StorageMsg<Storage>>,
+    ) {
+        let SyncRequest { from, to, limit } = request;
+        let blocks = carnot.safe_blocks();
+        let genesis = carnot.genesis_block().id;
+        let (mut block, end) = match to.and_then(|id| blocks.get(&id)) {
+            Some(block) => (block.clone(), block.view.next()),
+            // timeout qcs after the tip are relevant only when following the chain to its end
+            None => (carnot.tip(), carnot.current_view()),
+        };
+        let mut chain = Vec::new();
+        while block.view > from && block.id != genesis {
+            chain.push((block.id, block.view));
+            let Some(parent) = blocks.get(&block.parent()) else {
+                break;
+            };
+            block = parent.clone();
+        }
+        chain.reverse();
+        let limit = limit.min(SYNC_BATCH_SIZE) as usize;
+
+        tokio::spawn(async move {
+            let mut items = Vec::new();
+            let mut last_view = from;
+            // views between two consecutive proposals, or after the last one, ended with a timeout
+            for (id, view) in chain
+                .into_iter()
+                .map(|(id, view)| (Some(id), view))
+                .chain(std::iter::once((None, end)))
+            {
+                let mut timeout_view = last_view.next();
+                while timeout_view < view && items.len() < limit {
+                    if let Some(timeout_qc) =
+                        load(&storage_relay, timeout_qc_key(timeout_view)).await
+                    {
+                        items.push(SyncItem::TimeoutQc(timeout_qc));
+                    }
+                    timeout_view = timeout_view.next();
+                }
+                let Some(id) = id.filter(|_| items.len() < limit) else {
+                    break;
+                };
+                let block: Option<Block<ClPool::Item, DaPool::Item>> =
+                    load(&storage_relay, id).await;
+                match block {
+                    Some(block) => {
+                        let LeaderProof::LeaderId { leader_id } =
+                            *block.header().carnot().leader_proof();
+                        items.push(SyncItem::Proposal(ProposalMsg {
+                            proposal: id,
+                            data: block.as_bytes().to_vec().into_boxed_slice(),
+                            view,
+                            leader: leader_id,
+                            signature: None,
+                        }))
+                    }
+                    // serve only a contiguous sequence of views
+                    None => break,
+                }
+                last_view = view;
+            }
+            if reply.send(items).is_err() {
+                tracing::debug!("Could not send sync response, requester is gone");
+            }
+        });
+    }
+
     #[allow(clippy::type_complexity)]
     #[instrument(level = "debug", skip(task_manager, adapter))]
     async fn approve_new_view(
@@ -699,33 +1414,60 @@ where
         Option<Output<ClPool::Item, DaPool::Item>>,
     ) {
         let leader_committee = [carnot.id()].into_iter().collect();
-        let leader_tally_settings = CarnotTallySettings {
-            threshold: carnot.leader_super_majority_threshold(),
-            // TODO: add children of root committee
-            participating_nodes: carnot.root_committee(),
-        };
-        let (new_carnot, out) = carnot.approve_new_view(timeout_qc.clone(), new_views);
+        let leader_tally_settings = CarnotTallySettings::for_leader(&carnot);
+        let approval = carnot.approve_new_view(timeout_qc.clone(), new_views);
         let new_view = timeout_qc.view().next();
         if carnot.is_next_leader() {
             let high_qc = carnot.high_qc();
             task_manager.push(new_view, async move {
-                let _votes = Self::gather_new_views(
+                let Event::NewView { signature, .. } = Self::gather_new_views(
                     adapter,
                     leader_committee,
                     timeout_qc,
                     leader_tally_settings.clone(),
                 )
-                .await;
+                .await
+                else {
+                    return Event::None;
+                };
                 Event::ProposeBlock {
                     qc: Qc::Aggregated(AggregateQc {
                         high_qc,
                         view: new_view,
+                        signature,
                     }),
                 }
             });
         }
 
-        (new_carnot, Some(Output::Send(out)))
+        match approval {
+            Ok((new_carnot, out)) => {
+                let output = Self::approval_output(&new_carnot, out, None);
+                (new_carnot, Some(output))
+            }
+            Err(e) => {
+                tracing::debug!("could not approve new view {new_view}: {e}");
+                (carnot, None)
+            }
+        }
+    }
+
+    /// Children of the root committee send their votes and new views to the next leader too,
+    /// together with the qc of the votes they collected from their own children
+    fn approval_output(
+        carnot: &Carnot<O, HeaderId>,
+        send: carnot_engine::Send<HeaderId>,
+        qc: Option<Qc>,
+    ) -> Output<ClPool::Item, DaPool::Item> {
+        if carnot.is_child_of_root_committee() {
+            Output::ForwardToLeader {
+                send,
+                leader: carnot.overlay().next_leader(),
+                qc,
+            }
+        } else {
+            Output::Send(send)
+        }
     }
 
     #[allow(clippy::type_complexity)]
@@ -735,15 +1477,23 @@ where
         timeout_qc: TimeoutQc,
         task_manager: &mut TaskManager<View, Event<ClPool::Item, DaPool::Item>>,
         adapter: A,
+        storage_relay: &OutboundRelay<StorageMsg<Storage>>,
     ) -> (
         Carnot<O, HeaderId>,
         Option<Output<ClPool::Item, DaPool::Item>>,
     ) {
-        let mut new_state = carnot.receive_timeout_qc(timeout_qc.clone());
+        let mut new_state = match carnot.receive_timeout_qc(timeout_qc.clone()) {
+            Ok(new_state) => new_state,
+            Err(e) => {
+                tracing::debug!("ignoring timeout qc: {e}");
+                return (carnot, None);
+            }
+        };
         let self_committee = carnot.self_committee();
         let tally_settings = CarnotTallySettings {
             threshold: carnot.super_majority_threshold(),
             participating_nodes: carnot.child_committees().into_iter().flatten().collect(),
+            subtrees: Vec::new(),
         };
         task_manager.push(
             timeout_qc.view().next(),
@@ -755,6 +1505,12 @@ where
                 |leader_selection| leader_selection.on_timeout_qc_received(&timeout_qc),
                 |committee_membership| committee_membership.on_timeout_qc_received(&timeout_qc),
             );
+            store(
+                storage_relay,
+                overlay_timeout_qc_key(timeout_qc.view()),
+                &timeout_qc,
+            )
+            .await;
         }
         (new_state, None)
     }
@@ -764,6 +1520,7 @@ where
     async fn process_root_timeout(
         carnot: Carnot<O, HeaderId>,
         timeouts: HashSet<Timeout>,
+        signature: QcSignature,
     ) -> (
         Carnot<O, HeaderId>,
         Option<Output<ClPool::Item, DaPool::Item>>,
@@ -795,15 +1552,17 @@ where
             .clone();
         let mut output = None;
         if carnot.is_member_of_root_committee() {
-            let timeout_qc = TimeoutQc::new(carnot.current_view(), high_qc, carnot.id());
+            let timeout_qc = TimeoutQc::new(carnot.current_view(), high_qc, carnot.id(), signature);
             output = Some(Output::BroadcastTimeoutQc { timeout_qc });
         }
         (carnot, output)
     }
 
+    #[allow(clippy::too_many_arguments)]
     #[instrument(
         level = "debug",
         skip(
+            accounts,
             cl_mempool_relay,
             da_mempool_relay,
             private_key,
@@ -815,12 +1574,17 @@ where
         id: NodeId,
         private_key: PrivateKey,
         qc: Qc,
+        accounts: &AccountStates,
         tx_selector: TxS,
         blob_selector: BS,
         cl_mempool_relay: OutboundRelay<MempoolMsg<HeaderId, ClPool::Item, ClPool::Key>>,
         da_mempool_relay: OutboundRelay<MempoolMsg<HeaderId, DaPool::Item, DaPool::Key>>,
     ) -> Option<Output<ClPool::Item, DaPool::Item>> {
         let mut output = None;
+        let Some(accounts) = accounts.get(&qc.block()).cloned() else {
+            tracing::error!("no account state for block {:?}, can't propose", qc.block());
+            return None;
+        };
         let cl_txs = get_mempool_contents(cl_mempool_relay);
         let da_certs = get_mempool_contents(da_mempool_relay);
 
@@ -836,6 +1600,7 @@ where
                     ))
                     .with_transactions(cl_txs)
                     .with_blobs_certificates(da_certs)
+                    .with_state(accounts)
                     .build()
                 else {
                     panic!("Proposal block should always succeed to be built")
@@ -853,12 +1618,25 @@ where
         prev_view: View,
         task_manager: &mut TaskManager<View, Event<ClPool::Item, DaPool::Item>>,
         adapter: A,
-        timeout: Duration,
+        pacemaker: &mut Pacemaker,
     ) {
         let current_view = carnot.current_view();
-        // First we cancel previous processing view tasks
-        task_manager.cancel(prev_view);
+        // First we cancel previous processing view tasks, the view could have jumped by more
+        // than one, e.g. after syncing with other nodes
+        let mut view = prev_view;
+        while view < current_view {
+            task_manager.cancel(view);
+            view = view.next();
+        }
         tracing::debug!("Advanced view from {prev_view} to {current_view}");
+        pacemaker.on_view_change(&carnot, prev_view);
+        let timeout = pacemaker.timeout();
+        if pacemaker.consecutive_timeouts() > 0 {
+            tracing::debug!(
+                "{} consecutive timeouts, view {current_view} will time out in {timeout:?}",
+                pacemaker.consecutive_timeouts()
+            );
+        }
         // View change!
         task_manager.push(current_view, async move {
             tokio::time::sleep(timeout).await;
@@ -879,9 +1657,11 @@ where
                     adapter,
                     carnot.self_committee(),
                     current_view,
+                    // children of the root committee send their timeouts to the root committee too
                     CarnotTallySettings {
                         threshold: carnot.leader_super_majority_threshold(),
-                        participating_nodes: carnot.root_committee(),
+                        participating_nodes: carnot.root_committee_with_children(),
+                        subtrees: Vec::new(),
                     },
                 ),
             );
@@ -935,9 +1715,10 @@ where
             .new_view_stream(&committee, timeout_qc.view().next())
             .await;
         match tally.tally(timeout_qc.clone(), stream).await {
-            Ok((_qc, new_views)) => Event::NewView {
+            Ok((signature, new_views)) => Event::NewView {
                 timeout_qc,
                 new_views,
+                signature,
             },
             Err(_e) => {
                 todo!("Handle tally error {_e}");
@@ -955,7 +1736,10 @@ where
         let tally = TimeoutTally::new(tally);
         let stream = adapter.timeout_stream(&committee, view).await;
         match tally.tally(view, stream).await {
-            Ok((_, timeouts)) => Event::RootTimeout { timeouts },
+            Ok((signature, timeouts)) => Event::RootTimeout {
+                timeouts,
+                signature,
+            },
             Err(_e) => {
                 todo!("Handle tally error {_e}");
             }
@@ -970,16 +1754,12 @@ where
         let stream = adapter
             .proposal_chunks_stream(view)
             .await
-            .filter_map(move |msg| {
-                async move {
-                    let proposal = Block::from_bytes(&msg.data);
-                    if proposal.header().id() == msg.proposal {
-                        // TODO: Leader is faulty? what should we do?
-                        Some(proposal)
-                    } else {
-                        None
-                    }
-                }
+            .filter_map(move |msg| async move {
+                // proposals building on blocks we missed are fetched from peers in `process_block`
+                let proposal = Block::from_bytes(&msg.data)
+                    .map_err(|e| tracing::debug!("malformed proposal {:?}: {e}", msg.proposal))
+                    .ok()?;
+                (proposal.header().id() == msg.proposal).then_some(proposal)
             });
         let mut stream = Box::pin(stream);
         if let Some(block) = stream.next().await {
@@ -1028,56 +1808,35 @@ where
     }
 }
 
-async fn handle_output<A, Tx, C>(adapter: &A, node_id: NodeId, output: Output<Tx, C>)
-where
+async fn handle_output<A, Tx, C>(
+    adapter: &A,
+    node_id: NodeId,
+    private_key: &PrivateKey,
+    output: Output<Tx, C>,
+) where
     A: NetworkAdapter,
     Tx: Hash + Eq + Clone + Serialize + DeserializeOwned + Debug,
     C: Clone + Eq + Hash + Serialize + DeserializeOwned,
 {
     match output {
-        Output::Send(carnot_engine::Send { to, payload }) => match payload {
-            Payload::Vote(vote) => {
-                adapter
-                    .send(
-                        NetworkMessage::Vote(VoteMsg {
-                            voter: node_id,
-                            vote,
-                            qc: None, // TODO: handle root commmittee members
-                        }),
-                        &to,
-                    )
-                    .await;
-            }
-            Payload::Timeout(timeout) => {
-                adapter
-                    .send(
-                        NetworkMessage::Timeout(TimeoutMsg {
-                            voter: node_id,
-                            vote: timeout,
-                        }),
-                        &to,
-                    )
-                    .await;
-            }
-            Payload::NewView(new_view) => {
-                adapter
-                    .send(
-                        NetworkMessage::NewView(NewViewMsg {
-                            voter: node_id,
-                            vote: new_view,
-                        }),
-                        &to,
-                    )
-                    .await;
-            }
-        },
+        Output::Send(send) => send_payload(adapter, node_id, private_key, send, None).await,
+        Output::ForwardToLeader { send, leader, qc } => {
+            let to_leader = carnot_engine::Send {
+                to: [leader].into_iter().collect(),
+                payload: send.payload.clone(),
+            };
+            send_payload(adapter, node_id, private_key, send, qc.clone()).await;
+            send_payload(adapter, node_id, private_key, to_leader, qc).await;
+        }
         Output::BroadcastProposal { proposal } => {
             adapter
-                .broadcast(NetworkMessage::Proposal(ProposalMsg {
-                    proposal: proposal.header().id(),
-                    data: proposal.as_bytes().to_vec().into_boxed_slice(),
-                    view: proposal.header().carnot().view(),
-                }))
+                .broadcast(NetworkMessage::Proposal(ProposalMsg::new(
+                    proposal.as_bytes().to_vec().into_boxed_slice(),
+                    proposal.header().id(),
+                    proposal.header().carnot().view(),
+                    node_id,
+                    private_key,
+                )))
                 .await;
         }
         Output::BroadcastTimeoutQc { timeout_qc } => {
@@ -1091,6 +1850,41 @@ where
     }
 }
 
+async fn send_payload<A: NetworkAdapter>(
+    adapter: &A,
+    node_id: NodeId,
+    private_key: &PrivateKey,
+    carnot_engine::Send { to, payload }: carnot_engine::Send<HeaderId>,
+    qc: Option<Qc>,
+) {
+    match payload {
+        Payload::Vote(vote) => {
+            adapter
+                .send(
+                    NetworkMessage::Vote(VoteMsg::new(node_id, vote, qc, private_key)),
+                    &to,
+                )
+                .await;
+        }
+        Payload::Timeout(timeout) => {
+            adapter
+                .send(
+                    NetworkMessage::Timeout(TimeoutMsg::new(node_id, timeout, private_key)),
+                    &to,
+                )
+                .await;
+        }
+        Payload::NewView(new_view) => {
+            adapter
+                .send(
+                    NetworkMessage::NewView(NewViewMsg::new(node_id, new_view, private_key)),
+                    &to,
+                )
+                .await;
+        }
+    }
+}
+
 #[allow(clippy::large_enum_variant)]
 enum Event<Tx: Clone + Hash + Eq, BlobCertificate: Clone + Eq + Hash> {
     Proposal {
@@ -1109,12 +1903,14 @@ enum Event<Tx: Clone + Hash + Eq, BlobCertificate: Clone + Eq + Hash> {
     NewView {
         timeout_qc: TimeoutQc,
         new_views: HashSet<NewView>,
+        signature: QcSignature,
     },
     TimeoutQc {
         timeout_qc: TimeoutQc,
     },
     RootTimeout {
         timeouts: HashSet<Timeout>,
+        signature: QcSignature,
     },
     ProposeBlock {
         qc: Qc,
@@ -1135,6 +1931,16 @@ pub enum ConsensusMsg {
         to: Option<HeaderId>,
         tx: Sender<Vec<carnot_engine::Block<HeaderId>>>,
     },
+    /// Ids of the committed blocks, starting from the latest one and going back towards genesis,
+    /// up to `limit` of them
+    GetCommittedBlocks {
+        limit: Option<usize>,
+        tx: Sender<Vec<HeaderId>>,
+    },
+    /// Evidence of all the equivocations detected so far, in the order they were detected
+    GetEquivocations {
+        tx: Sender<Vec<Equivocation>>,
+    },
 }
 
 impl RelayMessage for ConsensusMsg {}
@@ -1168,6 +1974,64 @@ async fn get_mempool_contents<Item, Key>(
     rx.await
 }
 
+fn timeout_qc_key(view: View) -> (&'static str, View) {
+    (TIMEOUT_QC_KEY, view)
+}
+
+fn committed_key(view: View) -> (&'static str, View) {
+    (COMMITTED_KEY, view)
+}
+
+fn overlay_block_key(view: View) -> (&'static str, View) {
+    (OVERLAY_BLOCK_KEY, view)
+}
+
+fn overlay_timeout_qc_key(view: View) -> (&'static str, View) {
+    (OVERLAY_TIMEOUT_QC_KEY, view)
+}
+
+async fn load<Storage, T>(
+    storage_relay: &OutboundRelay<StorageMsg<Storage>>,
+    key: impl Serialize,
+) -> Option<T>
+where
+    Storage: StorageBackend + Send + Sync + 'static,
+    T: DeserializeOwned,
+{
+    let (msg, receiver) = <StorageMsg<Storage>>::new_load_message(key);
+    if let Err((e, _)) = storage_relay.send(msg).await {
+        tracing::error!("Could not send load request to storage: {e}");
+        return None;
+    }
+    receiver.recv().await.ok().flatten()
+}
+
+async fn store<Storage>(
+    storage_relay: &OutboundRelay<StorageMsg<Storage>>,
+    key: impl Serialize,
+    value: impl Serialize,
+) where
+    Storage: StorageBackend + Send + Sync + 'static,
+{
+    let msg = <StorageMsg<Storage>>::new_store_message(key, value);
+    if let Err((e, _)) = storage_relay.send(msg).await {
+        tracing::error!("Could not send data to storage: {e}");
+    }
+}
+
+/// Wait until everything stored so far is durably written, returning whether it succeeded
+async fn flush<Storage>(storage_relay: &OutboundRelay<StorageMsg<Storage>>) -> bool
+where
+    Storage: StorageBackend + Send + Sync + 'static,
+{
+    let (msg, receiver) = <StorageMsg<Storage>>::new_flush_message();
+    if let Err((e, _)) = storage_relay.send(msg).await {
+        tracing::error!("Could not send flush request to storage: {e}");
+        return false;
+    }
+    receiver.into_inner().await.is_ok()
+}
+
 async fn mark_in_block<Item, Key>(
     mempool: OutboundRelay<MempoolMsg<HeaderId, Item, Key>>,
     ids: impl Iterator<Item = Key>,
@@ -1197,6 +2061,7 @@ mod tests {
             local_high_qc: StandardQc {
                 view: View::new(0),
                 id: [0; 32].into(),
+                signature: QcSignature::default(),
             },
             tip: Block {
                 id: [0; 32].into(),
@@ -1204,6 +2069,7 @@ mod tests {
                 parent_qc: Qc::Standard(StandardQc {
                     view: View::new(0),
                     id: [0; 32].into(),
+                    signature: QcSignature::default(),
                 }),
                 leader_proof: LeaderProof::LeaderId {
                     leader_id: NodeId::new([0; 32]),
@@ -1216,6 +2082,7 @@ mod tests {
                 parent_qc: Qc::Standard(StandardQc {
                     view: View::new(0),
                     id: [0; 32].into(),
+                    signature: QcSignature::default(),
                 }),
                 leader_proof: LeaderProof::LeaderId {
                     leader_id: NodeId::new([0; 32]),
@@ -1227,7 +2094,7 @@ mod tests {
         eprintln!("{serialized}");
         assert_eq!(
             serialized,
-            r#"{"id":"0000000000000000000000000000000000000000000000000000000000000000","current_view":1,"highest_voted_view":-1,"local_high_qc":{"view":0,"id":"0000000000000000000000000000000000000000000000000000000000000000"},"tip":{"id":"0000000000000000000000000000000000000000000000000000000000000000","view":0,"parent_qc":{"Standard":{"view":0,"id":"0000000000000000000000000000000000000000000000000000000000000000"}},"leader_proof":{"LeaderId":{"leader_id":"0000000000000000000000000000000000000000000000000000000000000000"}}},"last_view_timeout_qc":null,"last_committed_block":{"id":"0000000000000000000000000000000000000000000000000000000000000000","view":0,"parent_qc":{"Standard":{"view":0,"id":"0000000000000000000000000000000000000000000000000000000000000000"}},"leader_proof":{"LeaderId":{"leader_id":"0000000000000000000000000000000000000000000000000000000000000000"}}}}"#
+            r#"{"id":"0000000000000000000000000000000000000000000000000000000000000000","current_view":1,"highest_voted_view":-1,"local_high_qc":{"view":0,"id":"0000000000000000000000000000000000000000000000000000000000000000","signature":{"signers":[],"public_keys":[],"signature":[]}},"tip":{"id":"0000000000000000000000000000000000000000000000000000000000000000","view":0,"parent_qc":{"Standard":{"view":0,"id":"0000000000000000000000000000000000000000000000000000000000000000","signature":{"signers":[],"public_keys":[],"signature":[]}}},"leader_proof":{"LeaderId":{"leader_id":"0000000000000000000000000000000000000000000000000000000000000000"}}},"last_view_timeout_qc":null,"last_committed_block":{"id":"0000000000000000000000000000000000000000000000000000000000000000","view":0,"parent_qc":{"Standard":{"view":0,"id":"0000000000000000000000000000000000000000000000000000000000000000","signature":{"signers":[],"public_keys":[],"signature":[]}}},"leader_proof":{"LeaderId":{"leader_id":"0000000000000000000000000000000000000000000000000000000000000000"}}}}"#
         );
 
         let deserialized: CarnotInfo = serde_json::from_str(&serialized).unwrap();
@@ -1,31 +1,54 @@
 // std
 use std::collections::{BTreeMap, HashMap};
+use std::io;
 use std::ops::DerefMut;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 // crates
+use futures::AsyncWriteExt;
+use rand::seq::SliceRandom;
 use serde::{Deserialize, Serialize};
 use tokio::sync::broadcast::error::RecvError;
-use tokio::sync::mpsc::{error::TrySendError, Receiver, Sender};
-use tokio_stream::wrappers::ReceiverStream;
+use tokio::sync::mpsc::{self, error::TrySendError, Receiver, Sender};
+use tokio::sync::oneshot;
+use tokio_stream::{wrappers::ReceiverStream, StreamExt};
 // internal
-use crate::network::messages::{NewViewMsg, TimeoutMsg, TimeoutQcMsg};
+use crate::network::messages::{
+    NewViewMsg, SyncItem, SyncRequest, TimeoutMsg, TimeoutQcMsg, ViewSync,
+};
 use crate::network::{
     messages::{NetworkMessage, ProposalMsg, VoteMsg},
     BoxedStream, NetworkAdapter,
 };
+use crate::{Equivocation, NodeId};
 use carnot_engine::{Committee, CommitteeId, View};
-use nomos_core::{header::HeaderId, wire};
+use nomos_core::header::HeaderId;
+use nomos_core::wire::envelope::{self, message_type, WireMessage};
+use nomos_libp2p::{
+    libp2p::{Stream, StreamProtocol},
+    libp2p_stream::Control,
+    PeerId,
+};
 use nomos_network::{
-    backends::libp2p::{Command, Event, EventKind, Libp2p},
+    backends::libp2p::{Command, Event, EventKind, Libp2p, StreamControl},
     NetworkMsg, NetworkService,
 };
 use overwatch_rs::services::{relay::OutboundRelay, ServiceData};
 
 const TOPIC: &str = "/carnot/proto";
+// Sync protocol messages are sent in wire envelopes, see [`envelope`].
+// The end of a response is signaled by closing the stream.
+const SYNC_PROTOCOL: StreamProtocol = StreamProtocol::new("/carnot/sync/0.2.0");
+// Time to wait for a single peer to serve a sync request before trying the next one
+const SYNC_TIMEOUT: Duration = Duration::from_secs(10);
+// Number of sync requests that can wait to be served by the consensus service
+const SYNC_BUFFER_SIZE: usize = 64;
 // TODO: this could be tailored per message (e.g. we need to store only a few proposals per view but might need a lot of votes)
 const BUFFER_SIZE: usize = 500;
 
 type Relay<T> = OutboundRelay<<NetworkService<T> as ServiceData>::Message>;
+type SyncRequestWithReply = (SyncRequest, oneshot::Sender<Vec<SyncItem>>);
 
 /// Due to network effects, latencies, or other factors, it is possible that a node may receive messages
 /// out of order, or simply messages that are relevant to future views.
@@ -98,6 +121,36 @@ struct Messages {
     new_views: HashMap<CommitteeId, Spsc<NewViewMsg>>,
     timeouts: HashMap<CommitteeId, Spsc<TimeoutMsg>>,
     timeout_qcs: Spsc<TimeoutQcMsg>,
+    // first signed proposal and vote seen from each node, to detect equivocations
+    leader_proposals: HashMap<NodeId, ProposalMsg>,
+    voter_votes: HashMap<NodeId, VoteMsg>,
+}
+
+impl Messages {
+    fn check_proposal(&mut self, msg: &ProposalMsg) -> Option<Equivocation> {
+        match self.leader_proposals.get(&msg.leader) {
+            Some(first) => first.equivocation(msg),
+            None => {
+                self.leader_proposals.insert(msg.leader, msg.clone());
+                None
+            }
+        }
+    }
+
+    fn check_vote(&mut self, msg: &VoteMsg) -> Option<Equivocation> {
+        match self.voter_votes.get(&msg.voter) {
+            Some(first) => first.equivocation(msg),
+            None => {
+                // the qc is not part of the evidence
+                let vote = VoteMsg {
+                    qc: None,
+                    ..msg.clone()
+                };
+                self.voter_votes.insert(msg.voter, vote);
+                None
+            }
+        }
+    }
 }
 
 /// Requesting the same stream type multiple times will re-initialize it and new items will only be forwarded to the latest one.
@@ -106,6 +159,10 @@ struct Messages {
 pub struct Libp2pAdapter {
     network_relay: OutboundRelay<<NetworkService<Libp2p> as ServiceData>::Message>,
     message_cache: MessageCache,
+    invalid_signatures: Arc<AtomicU64>,
+    stream_control: Control,
+    equivocations: Arc<Mutex<Spsc<Equivocation>>>,
+    view_sync: Arc<Mutex<Spsc<ViewSync>>>,
 }
 
 impl MessageCache {
@@ -124,8 +181,18 @@ impl MessageCache {
 
     // treat view as the current view
     fn advance(mut cache: impl DerefMut<Target = BTreeMap<View, Messages>>, view: View) {
-        if cache.remove(&(view - 1.into())).is_some() {
-            cache.insert(view + Self::VIEW_SIZE_LIMIT - 1.into(), Messages::default());
+        if cache
+            .first_key_value()
+            .map_or(true, |(first, _)| *first >= view)
+        {
+            return;
+        }
+        // the view could have jumped by more than one, e.g. after syncing with other nodes
+        cache.retain(|cached, _| *cached >= view);
+        let mut next = view;
+        while next < view + Self::VIEW_SIZE_LIMIT {
+            cache.entry(next).or_default();
+            next = next.next();
         }
     }
 
@@ -194,11 +261,54 @@ struct GossipsubMessage {
 
 impl GossipsubMessage {
     pub fn as_bytes(&self) -> Box<[u8]> {
-        wire::serialize(self).unwrap().into_boxed_slice()
+        envelope::encode(self)
+            .expect("gossipsub messages do not exceed their size limit")
+            .into_boxed_slice()
     }
 }
 
+impl WireMessage for GossipsubMessage {
+    const MESSAGE_TYPE: u8 = message_type::CARNOT_GOSSIP;
+    const MAX_SIZE: u64 = ProposalMsg::MAX_SIZE + 1024;
+}
+
 impl Libp2pAdapter {
+    /// Number of votes, timeouts and new views dropped because of an invalid signature
+    pub fn invalid_signatures(&self) -> u64 {
+        self.invalid_signatures.load(Ordering::Relaxed)
+    }
+
+    fn report_equivocation(equivocations: &Mutex<Spsc<Equivocation>>, evidence: Equivocation) {
+        tracing::warn!(
+            "{:?} equivocated in view {}: {:?}",
+            evidence.offender,
+            evidence.view,
+            evidence.kind
+        );
+        equivocations.lock().unwrap().try_send(evidence);
+    }
+
+    // the first cached view is the current one, qcs for later views show the node is lagging behind
+    fn forward_view_sync(
+        view_sync: &Mutex<Spsc<ViewSync>>,
+        cache: &BTreeMap<View, Messages>,
+        msg: ViewSync,
+    ) {
+        if cache
+            .first_key_value()
+            .map_or(true, |(current, _)| msg.view() > *current)
+        {
+            view_sync.lock().unwrap().try_send(msg);
+        }
+    }
+
+    fn drop_invalid_signature(counter: &AtomicU64, kind: &str, voter: &NodeId) {
+        let dropped = counter.fetch_add(1, Ordering::Relaxed) + 1;
+        tracing::warn!(
+            "dropping {kind} with invalid signature from {voter:?} ({dropped} dropped so far)"
+        );
+    }
+
     async fn broadcast(&self, message: GossipsubMessage, topic: &str) {
         if let Err((e, message)) = self
             .network_relay
@@ -220,6 +330,71 @@ impl Libp2pAdapter {
             tracing::error!("error subscribing to {topic}: {e}");
         };
     }
+
+    async fn stream_control(relay: &Relay<Libp2p>) -> Control {
+        let (reply, control) = oneshot::channel();
+        if let Err((e, _)) = relay
+            .send(NetworkMsg::Process(Command::GetStreamControl { reply }))
+            .await
+        {
+            tracing::error!("error requesting stream control: {e}");
+        }
+        let StreamControl(control) = control
+            .await
+            .expect("Network backend should always provide a stream control");
+        control
+    }
+
+    async fn connected_peers(&self) -> Vec<PeerId> {
+        let (reply, peers) = oneshot::channel();
+        if let Err((e, _)) = self
+            .network_relay
+            .send(NetworkMsg::Process(Command::ConnectedPeers { reply }))
+            .await
+        {
+            tracing::error!("error requesting connected peers: {e}");
+            return vec![];
+        }
+        peers.await.unwrap_or_default()
+    }
+
+    async fn request_sync_from(
+        &self,
+        peer_id: PeerId,
+        request: SyncRequest,
+    ) -> io::Result<Vec<SyncItem>> {
+        let mut stream = self
+            .stream_control
+            .clone()
+            .open_stream(peer_id, SYNC_PROTOCOL)
+            .await
+            .map_err(io::Error::other)?;
+        envelope::write_message(&mut stream, &request).await?;
+        let mut items = Vec::new();
+        while let Some(item) = envelope::read_message(&mut stream).await? {
+            items.push(item);
+        }
+        Ok(items)
+    }
+
+    async fn serve_sync_request(
+        mut stream: Stream,
+        requests: mpsc::Sender<SyncRequestWithReply>,
+    ) -> io::Result<()> {
+        let Some(request) = envelope::read_message(&mut stream).await? else {
+            return Ok(());
+        };
+        let (reply, response) = oneshot::channel();
+        requests
+            .send((request, reply))
+            .await
+            .map_err(|_| io::Error::other("sync requests are not being served"))?;
+        let items = response.await.map_err(io::Error::other)?;
+        for item in items {
+            envelope::write_message(&mut stream, &item).await?;
+        }
+        stream.close().await
+    }
 }
 
 #[async_trait::async_trait]
@@ -229,6 +404,12 @@ impl NetworkAdapter for Libp2pAdapter {
     async fn new(network_relay: Relay<Self::Backend>) -> Self {
         let message_cache = MessageCache::new();
         let cache = message_cache.clone();
+        let invalid_signatures = Arc::new(AtomicU64::new(0));
+        let counter = invalid_signatures.clone();
+        let equivocations = Arc::new(Mutex::new(Spsc::default()));
+        let evidences = equivocations.clone();
+        let view_sync = Arc::new(Mutex::new(Spsc::default()));
+        let higher_views = view_sync.clone();
         let relay = network_relay.clone();
         Self::subscribe(&relay, TOPIC).await;
         tracing::debug!("Starting up...");
@@ -252,25 +433,54 @@ impl NetworkAdapter for Libp2pAdapter {
             let mut incoming_messages = receiver.await.unwrap();
             loop {
                 match incoming_messages.recv().await {
-                    Ok(Event::Message(message)) => {
-                        match nomos_core::wire::deserialize(&message.data) {
+                    Ok(Event::Message { message, .. }) => {
+                        match envelope::decode::<GossipsubMessage>(&message.data) {
                             Ok(GossipsubMessage { to, message }) => match message {
                                 NetworkMessage::Proposal(msg) => {
                                     tracing::debug!("received proposal chunk");
+                                    if !msg.verify() {
+                                        Self::drop_invalid_signature(
+                                            &counter,
+                                            "proposal",
+                                            &msg.leader,
+                                        );
+                                        continue;
+                                    }
                                     let mut cache = cache.cache.lock().unwrap();
                                     let view = msg.view;
                                     if let Some(messages) = cache.get_mut(&view) {
+                                        if let Some(evidence) = messages.check_proposal(&msg) {
+                                            Self::report_equivocation(&evidences, evidence);
+                                        }
                                         messages.proposal_chunks.try_send(msg);
                                     }
                                 }
                                 NetworkMessage::Vote(msg) => {
                                     tracing::debug!("received vote {:?}", msg);
+                                    if !msg.verify() {
+                                        Self::drop_invalid_signature(&counter, "vote", &msg.voter);
+                                        continue;
+                                    }
+                                    let Some(committee) = to else {
+                                        tracing::debug!("dropping vote without a committee");
+                                        continue;
+                                    };
                                     let mut cache = cache.cache.lock().unwrap();
+                                    if let Some(qc) = &msg.qc {
+                                        Self::forward_view_sync(
+                                            &higher_views,
+                                            &cache,
+                                            ViewSync::Qc(qc.clone()),
+                                        );
+                                    }
                                     let view = msg.vote.view;
                                     if let Some(messages) = cache.get_mut(&view) {
+                                        if let Some(evidence) = messages.check_vote(&msg) {
+                                            Self::report_equivocation(&evidences, evidence);
+                                        }
                                         messages
                                             .votes
-                                            .entry(to.unwrap())
+                                            .entry(committee)
                                             .or_default()
                                             .entry(msg.vote.block)
                                             .or_default()
@@ -279,12 +489,22 @@ impl NetworkAdapter for Libp2pAdapter {
                                 }
                                 NetworkMessage::Timeout(msg) => {
                                     tracing::debug!("received timeout");
+                                    if !msg.verify() {
+                                        Self::drop_invalid_signature(
+                                            &counter, "timeout", &msg.voter,
+                                        );
+                                        continue;
+                                    }
+                                    let Some(committee) = to else {
+                                        tracing::debug!("dropping timeout without a committee");
+                                        continue;
+                                    };
                                     let mut cache = cache.cache.lock().unwrap();
                                     let view = msg.vote.view;
                                     if let Some(messages) = cache.get_mut(&view) {
                                         messages
                                             .timeouts
-                                            .entry(to.unwrap())
+                                            .entry(committee)
                                             .or_default()
                                             .try_send(msg);
                                     }
@@ -292,6 +512,11 @@ impl NetworkAdapter for Libp2pAdapter {
                                 NetworkMessage::TimeoutQc(msg) => {
                                     tracing::debug!("received timeout_qc");
                                     let mut cache = cache.cache.lock().unwrap();
+                                    Self::forward_view_sync(
+                                        &higher_views,
+                                        &cache,
+                                        ViewSync::TimeoutQc(msg.qc.clone()),
+                                    );
                                     let view = msg.qc.view();
                                     if let Some(messages) = cache.get_mut(&view) {
                                         messages.timeout_qcs.try_send(msg);
@@ -299,18 +524,41 @@ impl NetworkAdapter for Libp2pAdapter {
                                 }
                                 NetworkMessage::NewView(msg) => {
                                     tracing::debug!("received new_view");
+                                    if !msg.verify() {
+                                        Self::drop_invalid_signature(
+                                            &counter, "new view", &msg.voter,
+                                        );
+                                        continue;
+                                    }
+                                    let Some(committee) = to else {
+                                        tracing::debug!("dropping new view without a committee");
+                                        continue;
+                                    };
                                     let mut cache = cache.cache.lock().unwrap();
+                                    Self::forward_view_sync(
+                                        &higher_views,
+                                        &cache,
+                                        ViewSync::TimeoutQc(msg.vote.timeout_qc.clone()),
+                                    );
                                     let view = msg.vote.view;
                                     if let Some(messages) = cache.get_mut(&view) {
                                         messages
                                             .new_views
-                                            .entry(to.unwrap())
+                                            .entry(committee)
                                             .or_default()
                                             .try_send(msg);
                                     }
                                 }
+                                NetworkMessage::Equivocation(evidence) => {
+                                    tracing::debug!("received equivocation evidence");
+                                    if evidence.verify() {
+                                        evidences.lock().unwrap().try_send(evidence);
+                                    } else {
+                                        tracing::debug!("dropping invalid equivocation evidence");
+                                    }
+                                }
                             },
-                            _ => tracing::debug!("unrecognized gossipsub message"),
+                            Err(e) => tracing::debug!("invalid gossipsub message: {e}"),
                         }
                     }
                     Err(RecvError::Lagged(n)) => {
@@ -320,9 +568,14 @@ impl NetworkAdapter for Libp2pAdapter {
                 }
             }
         });
+        let stream_control = Self::stream_control(&network_relay).await;
         Self {
             network_relay,
             message_cache,
+            invalid_signatures,
+            stream_control,
+            equivocations,
+            view_sync,
         }
     }
 
@@ -378,4 +631,49 @@ impl NetworkAdapter for Libp2pAdapter {
         };
         self.broadcast(message, TOPIC).await;
     }
+
+    async fn sync_requests_stream(&self) -> BoxedStream<SyncRequestWithReply> {
+        let (sender, receiver) = mpsc::channel(SYNC_BUFFER_SIZE);
+        match self.stream_control.clone().accept(SYNC_PROTOCOL) {
+            Ok(mut incoming_streams) => {
+                tokio::spawn(async move {
+                    while let Some((peer_id, stream)) = incoming_streams.next().await {
+                        let sender = sender.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = Self::serve_sync_request(stream, sender).await {
+                                tracing::debug!("failed to serve sync request from {peer_id}: {e}");
+                            }
+                        });
+                    }
+                });
+            }
+            Err(e) => tracing::error!("error accepting sync streams: {e}"),
+        }
+        Box::new(ReceiverStream::new(receiver))
+    }
+
+    async fn equivocations_stream(&self) -> BoxedStream<Equivocation> {
+        let receiver = self.equivocations.lock().unwrap().recv_or_restore();
+        Box::new(ReceiverStream::new(receiver))
+    }
+
+    async fn view_sync_stream(&self) -> BoxedStream<ViewSync> {
+        let receiver = self.view_sync.lock().unwrap().recv_or_restore();
+        Box::new(ReceiverStream::new(receiver))
+    }
+
+    async fn request_sync(&self, request: SyncRequest) -> Vec<SyncItem> {
+        let mut peers = self.connected_peers().await;
+        peers.shuffle(&mut rand::thread_rng());
+        for peer_id in peers {
+            match tokio::time::timeout(SYNC_TIMEOUT, self.request_sync_from(peer_id, request)).await
+            {
+                Ok(Ok(items)) if !items.is_empty() => return items,
+                Ok(Ok(_)) => tracing::debug!("peer {peer_id} could not serve {request:?}"),
+                Ok(Err(e)) => tracing::debug!("sync request {request:?} to {peer_id} failed: {e}"),
+                Err(_) => tracing::debug!("sync request {request:?} to {peer_id} timed out"),
+            }
+        }
+        vec![]
+    }
 }
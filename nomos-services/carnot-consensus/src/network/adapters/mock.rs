@@ -6,13 +6,17 @@ use nomos_network::{
     NetworkMsg, NetworkService,
 };
 use overwatch_rs::services::{relay::OutboundRelay, ServiceData};
+use tokio::sync::oneshot;
 use tokio_stream::wrappers::BroadcastStream;
 
-use crate::network::messages::{NetworkMessage, NewViewMsg, TimeoutMsg, TimeoutQcMsg};
+use crate::network::messages::{
+    NetworkMessage, NewViewMsg, SyncItem, SyncRequest, TimeoutMsg, TimeoutQcMsg, ViewSync,
+};
 use crate::network::{
     messages::{ProposalMsg, VoteMsg},
     BoxedStream, NetworkAdapter,
 };
+use crate::Equivocation;
 use consensus_engine::{BlockId, Committee, View};
 
 const MOCK_PUB_SUB_TOPIC: &str = "MockPubSubTopic";
@@ -72,7 +76,9 @@ impl NetworkAdapter for MockAdapter {
                                 == message.content_topic().content_topic_name
                             {
                                 let payload = message.payload();
-                                Some(ProposalMsg::from_bytes(payload.as_bytes()))
+                                ProposalMsg::from_bytes(payload.as_bytes())
+                                    .map_err(|e| tracing::debug!("invalid proposal: {e}"))
+                                    .ok()
                             } else {
                                 None
                             }
@@ -112,7 +118,9 @@ impl NetworkAdapter for MockAdapter {
                                 == message.content_topic().content_topic_name
                             {
                                 let payload = message.payload();
-                                Some(VoteMsg::from_bytes(payload.as_bytes()))
+                                VoteMsg::from_bytes(payload.as_bytes())
+                                    .map_err(|e| tracing::debug!("invalid vote: {e}"))
+                                    .ok()
                             } else {
                                 None
                             }
@@ -149,4 +157,23 @@ impl NetworkAdapter for MockAdapter {
             tracing::error!("Failed to forward approval: {:?}", e);
         };
     }
+
+    async fn sync_requests_stream(
+        &self,
+    ) -> BoxedStream<(SyncRequest, oneshot::Sender<Vec<SyncItem>>)> {
+        // the mock network does not support sync, so there are never requests to serve
+        Box::new(futures::stream::pending())
+    }
+
+    async fn equivocations_stream(&self) -> BoxedStream<Equivocation> {
+        Box::new(futures::stream::pending())
+    }
+
+    async fn view_sync_stream(&self) -> BoxedStream<ViewSync> {
+        Box::new(futures::stream::pending())
+    }
+
+    async fn request_sync(&self, _request: SyncRequest) -> Vec<SyncItem> {
+        vec![]
+    }
 }
@@ -1,58 +1,215 @@
 // std
 // crates
+use bls_signatures::PrivateKey;
 use serde::{Deserialize, Serialize};
 // internal
+use crate::crypto::NodeSignature;
 use crate::NodeId;
-use crate::{NewView, Qc, Timeout, TimeoutQc, Vote};
-use carnot_engine::View;
+use crate::{Equivocation, NewView, Qc, Timeout, TimeoutQc, Vote};
+use carnot_engine::{
+    new_view_message, proposal_message, timeout_message, vote_message, EquivocationKind,
+    SignedBlock, View,
+};
+use nomos_core::block::MAX_BLOCK_SIZE;
 use nomos_core::header::HeaderId;
-use nomos_core::wire;
+use nomos_core::wire::{
+    self,
+    envelope::{self, message_type, WireMessage},
+};
+
+// Room for the fields of a message besides the block it carries
+const PROPOSAL_OVERHEAD: u64 = 1024;
+// Votes, timeouts and qcs carry aggregated signatures, together with the bitmap and public keys
+// of the signers, so their size grows with the committees. This leaves room for thousands of
+// members per committee.
+const MAX_MESSAGE_SIZE: u64 = 1024 * 1024;
 
 #[derive(Clone, Serialize, Deserialize, Debug, Eq, PartialEq, Hash)]
 pub struct ProposalMsg {
     pub data: Box<[u8]>,
     pub proposal: HeaderId,
     pub view: View,
+    pub leader: NodeId,
+    /// Signature of the leader over the proposal. Proposals served by the sync protocol are
+    /// not signed, as they are checked against the qcs of the following blocks instead.
+    pub signature: Option<NodeSignature>,
 }
 
 impl ProposalMsg {
+    pub fn new(
+        data: Box<[u8]>,
+        proposal: HeaderId,
+        view: View,
+        leader: NodeId,
+        private_key: &PrivateKey,
+    ) -> Self {
+        let signature =
+            NodeSignature::sign(private_key, &proposal_message(leader, view, &proposal));
+        Self {
+            data,
+            proposal,
+            view,
+            leader,
+            signature: Some(signature),
+        }
+    }
+
+    /// Whether the message was signed by `leader`
+    pub fn verify(&self) -> bool {
+        self.signature.as_ref().is_some_and(|signature| {
+            signature.verify(
+                &self.leader,
+                &proposal_message(self.leader, self.view, &self.proposal),
+            )
+        })
+    }
+
+    /// Evidence that the leader signed both `self` and `other`, two different proposals for the
+    /// same view. Signatures are expected to be already verified.
+    pub fn equivocation(&self, other: &Self) -> Option<Equivocation> {
+        if self.leader != other.leader || self.view != other.view {
+            return None;
+        }
+        equivocation(
+            EquivocationKind::Proposal,
+            self.leader,
+            self.view,
+            (self.proposal, self.signature.as_ref()?),
+            (other.proposal, other.signature.as_ref()?),
+        )
+    }
+
+    /// # Panics
+    /// If the proposed block is larger than [`MAX_BLOCK_SIZE`]
     pub fn as_bytes(&self) -> Box<[u8]> {
-        wire::serialize(self).unwrap().into_boxed_slice()
+        envelope::encode(self)
+            .expect("proposals do not exceed their size limit")
+            .into_boxed_slice()
     }
 
-    pub fn from_bytes(data: &[u8]) -> Self {
-        wire::deserialize(data).unwrap()
+    pub fn from_bytes(data: &[u8]) -> Result<Self, envelope::Error> {
+        envelope::decode(data)
     }
 }
 
+impl WireMessage for ProposalMsg {
+    const MESSAGE_TYPE: u8 = message_type::CARNOT_PROPOSAL;
+    const MAX_SIZE: u64 = MAX_BLOCK_SIZE + PROPOSAL_OVERHEAD;
+}
+
 #[derive(Debug, Eq, PartialEq, Hash, Serialize, Deserialize, Clone)]
 pub struct VoteMsg {
     pub voter: NodeId,
     pub vote: Vote,
     pub qc: Option<Qc>,
+    pub signature: NodeSignature,
 }
 
 impl VoteMsg {
+    pub fn new(voter: NodeId, vote: Vote, qc: Option<Qc>, private_key: &PrivateKey) -> Self {
+        let signature = NodeSignature::sign(private_key, &Self::signed_bytes(&voter, &vote));
+        Self {
+            voter,
+            vote,
+            qc,
+            signature,
+        }
+    }
+
+    // Signatures are aggregated into the qc for the block, so they only cover what is
+    // included in the qc. The attached qc, if any, carries its own signatures.
+    fn signed_bytes(voter: &NodeId, vote: &Vote) -> Vec<u8> {
+        vote_message(*voter, vote.view, &vote.block)
+    }
+
+    /// Whether the message was signed by `voter`
+    pub fn verify(&self) -> bool {
+        self.signature
+            .verify(&self.voter, &Self::signed_bytes(&self.voter, &self.vote))
+    }
+
+    /// Evidence that the voter signed both `self` and `other`, votes for two different blocks in
+    /// the same view. Signatures are expected to be already verified.
+    pub fn equivocation(&self, other: &Self) -> Option<Equivocation> {
+        if self.voter != other.voter || self.vote.view != other.vote.view {
+            return None;
+        }
+        equivocation(
+            EquivocationKind::Vote,
+            self.voter,
+            self.vote.view,
+            (self.vote.block, &self.signature),
+            (other.vote.block, &other.signature),
+        )
+    }
+
     pub fn as_bytes(&self) -> Box<[u8]> {
-        wire::serialize(self).unwrap().into_boxed_slice()
+        wire::serialize_with_limit(self, MAX_MESSAGE_SIZE)
+            .expect("consensus messages do not exceed their size limit")
+            .into_boxed_slice()
     }
-    pub fn from_bytes(data: &[u8]) -> Self {
-        wire::deserialize(data).unwrap()
+    pub fn from_bytes(data: &[u8]) -> Result<Self, wire::Error> {
+        wire::deserialize_with_limit(data, MAX_MESSAGE_SIZE)
     }
 }
 
+fn equivocation(
+    kind: EquivocationKind,
+    offender: NodeId,
+    view: View,
+    (first, first_signature): (HeaderId, &NodeSignature),
+    (second, second_signature): (HeaderId, &NodeSignature),
+) -> Option<Equivocation> {
+    let signed = |block, signature: &NodeSignature| SignedBlock {
+        block,
+        public_key: signature.public_key().into(),
+        signature: signature.signature().into(),
+    };
+    (first != second).then(|| Equivocation {
+        kind,
+        offender,
+        view,
+        first: signed(first, first_signature),
+        second: signed(second, second_signature),
+    })
+}
+
 #[derive(Debug, Serialize, Deserialize, Hash, PartialEq, Eq, Clone)]
 pub struct NewViewMsg {
     pub voter: NodeId,
     pub vote: NewView,
+    pub signature: NodeSignature,
 }
 
 impl NewViewMsg {
+    pub fn new(voter: NodeId, vote: NewView, private_key: &PrivateKey) -> Self {
+        let signature = NodeSignature::sign(private_key, &Self::signed_bytes(&voter, &vote));
+        Self {
+            voter,
+            vote,
+            signature,
+        }
+    }
+
+    // Signatures are aggregated into the qc for the new view, so they only cover the view.
+    // The high qc carries its own signatures.
+    fn signed_bytes(voter: &NodeId, vote: &NewView) -> Vec<u8> {
+        new_view_message(*voter, vote.view)
+    }
+
+    /// Whether the message was signed by `voter`
+    pub fn verify(&self) -> bool {
+        self.signature
+            .verify(&self.voter, &Self::signed_bytes(&self.voter, &self.vote))
+    }
+
     pub fn as_bytes(&self) -> Box<[u8]> {
-        wire::serialize(self).unwrap().into_boxed_slice()
+        wire::serialize_with_limit(self, MAX_MESSAGE_SIZE)
+            .expect("consensus messages do not exceed their size limit")
+            .into_boxed_slice()
     }
-    pub fn from_bytes(data: &[u8]) -> Self {
-        wire::deserialize(data).unwrap()
+    pub fn from_bytes(data: &[u8]) -> Result<Self, wire::Error> {
+        wire::deserialize_with_limit(data, MAX_MESSAGE_SIZE)
     }
 }
 
@@ -60,14 +217,38 @@ impl NewViewMsg {
 pub struct TimeoutMsg {
     pub voter: NodeId,
     pub vote: Timeout,
+    pub signature: NodeSignature,
 }
 
 impl TimeoutMsg {
+    pub fn new(voter: NodeId, vote: Timeout, private_key: &PrivateKey) -> Self {
+        let signature = NodeSignature::sign(private_key, &Self::signed_bytes(&voter, &vote));
+        Self {
+            voter,
+            vote,
+            signature,
+        }
+    }
+
+    // Signatures are aggregated into the timeout qc, so they only cover the view.
+    // The high qc carries its own signatures.
+    fn signed_bytes(voter: &NodeId, vote: &Timeout) -> Vec<u8> {
+        timeout_message(*voter, vote.view)
+    }
+
+    /// Whether the message was signed by `voter`
+    pub fn verify(&self) -> bool {
+        self.signature
+            .verify(&self.voter, &Self::signed_bytes(&self.voter, &self.vote))
+    }
+
     pub fn as_bytes(&self) -> Box<[u8]> {
-        wire::serialize(self).unwrap().into_boxed_slice()
+        wire::serialize_with_limit(self, MAX_MESSAGE_SIZE)
+            .expect("consensus messages do not exceed their size limit")
+            .into_boxed_slice()
     }
-    pub fn from_bytes(data: &[u8]) -> Self {
-        wire::deserialize(data).unwrap()
+    pub fn from_bytes(data: &[u8]) -> Result<Self, wire::Error> {
+        wire::deserialize_with_limit(data, MAX_MESSAGE_SIZE)
     }
 }
 
@@ -79,10 +260,67 @@ pub struct TimeoutQcMsg {
 
 impl TimeoutQcMsg {
     pub fn as_bytes(&self) -> Box<[u8]> {
-        wire::serialize(self).unwrap().into_boxed_slice()
+        wire::serialize_with_limit(self, MAX_MESSAGE_SIZE)
+            .expect("consensus messages do not exceed their size limit")
+            .into_boxed_slice()
+    }
+    pub fn from_bytes(data: &[u8]) -> Result<Self, wire::Error> {
+        wire::deserialize_with_limit(data, MAX_MESSAGE_SIZE)
+    }
+}
+
+/// Requests served by the sync protocol, used by nodes lagging behind to catch up.
+/// Responses are a sequence of [`SyncItem`], ordered by view.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncRequest {
+    /// Only proposals and timeout qcs for views after this one are requested
+    pub from: View,
+    /// Last proposal to include. If `None` or not known by the peer, the peer tip is used.
+    pub to: Option<HeaderId>,
+    pub limit: u64,
+}
+
+impl WireMessage for SyncRequest {
+    const MESSAGE_TYPE: u8 = message_type::CARNOT_SYNC_REQUEST;
+    const MAX_SIZE: u64 = 1024;
+}
+
+/// What is needed to replay a view: either the proposal that was approved in it,
+/// or the timeout qc if the view timed out
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum SyncItem {
+    Proposal(ProposalMsg),
+    TimeoutQc(TimeoutQc),
+}
+
+impl SyncItem {
+    pub fn view(&self) -> View {
+        match self {
+            Self::Proposal(proposal) => proposal.view,
+            Self::TimeoutQc(timeout_qc) => timeout_qc.view(),
+        }
     }
-    pub fn from_bytes(data: &[u8]) -> Self {
-        wire::deserialize(data).unwrap()
+}
+
+impl WireMessage for SyncItem {
+    const MESSAGE_TYPE: u8 = message_type::CARNOT_SYNC_ITEM;
+    const MAX_SIZE: u64 = ProposalMsg::MAX_SIZE + PROPOSAL_OVERHEAD;
+}
+
+/// Qc or timeout qc carried by a message from another node, showing the view the network is in.
+/// Nodes lagging behind use them to catch up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ViewSync {
+    Qc(Qc),
+    TimeoutQc(TimeoutQc),
+}
+
+impl ViewSync {
+    pub fn view(&self) -> View {
+        match self {
+            Self::Qc(qc) => qc.view(),
+            Self::TimeoutQc(timeout_qc) => timeout_qc.view(),
+        }
     }
 }
 
@@ -93,13 +331,21 @@ pub enum NetworkMessage {
     Vote(VoteMsg),
     NewView(NewViewMsg),
     Proposal(ProposalMsg),
+    Equivocation(Equivocation),
 }
 
 impl NetworkMessage {
+    /// Proposals are the largest messages
+    pub const MAX_SIZE: u64 = ProposalMsg::MAX_SIZE + PROPOSAL_OVERHEAD;
+
+    /// # Panics
+    /// If the message is larger than [`NetworkMessage::MAX_SIZE`]
     pub fn as_bytes(&self) -> Box<[u8]> {
-        wire::serialize(self).unwrap().into_boxed_slice()
+        wire::serialize_with_limit(self, Self::MAX_SIZE)
+            .expect("consensus messages do not exceed their size limit")
+            .into_boxed_slice()
     }
-    pub fn from_bytes(data: &[u8]) -> Self {
-        wire::deserialize(data).unwrap()
+    pub fn from_bytes(data: &[u8]) -> Result<Self, wire::Error> {
+        wire::deserialize_with_limit(data, Self::MAX_SIZE)
     }
 }
@@ -7,13 +7,16 @@ use futures::Stream;
 use nomos_core::header::HeaderId;
 // internal
 use crate::network::messages::{
-    NetworkMessage, NewViewMsg, ProposalMsg, TimeoutMsg, TimeoutQcMsg, VoteMsg,
+    NetworkMessage, NewViewMsg, ProposalMsg, SyncItem, SyncRequest, TimeoutMsg, TimeoutQcMsg,
+    ViewSync, VoteMsg,
 };
+use crate::Equivocation;
 use carnot_engine::{Committee, View};
 use nomos_network::backends::NetworkBackend;
 use nomos_network::NetworkService;
 use overwatch_rs::services::relay::OutboundRelay;
 use overwatch_rs::services::ServiceData;
+use tokio::sync::oneshot;
 
 type BoxedStream<T> = Box<dyn Stream<Item = T> + Send + Sync + Unpin>;
 
@@ -38,4 +41,17 @@ pub trait NetworkAdapter {
     ) -> BoxedStream<VoteMsg>;
     async fn new_view_stream(&self, committee: &Committee, view: View) -> BoxedStream<NewViewMsg>;
     async fn send(&self, message: NetworkMessage, committee: &Committee);
+    /// Incoming sync requests from other peers, together with a channel to send back the response
+    async fn sync_requests_stream(
+        &self,
+    ) -> BoxedStream<(SyncRequest, oneshot::Sender<Vec<SyncItem>>)>;
+    /// Evidence of nodes signing conflicting proposals or votes, either detected locally or
+    /// received from other peers, who already verified it
+    async fn equivocations_stream(&self) -> BoxedStream<Equivocation>;
+    /// Qcs and timeout qcs for views after the current one, including the ones too far ahead to
+    /// be cached, so that a node lagging behind can notice it and catch up
+    async fn view_sync_stream(&self) -> BoxedStream<ViewSync>;
+    /// Request proposals and timeout qcs from connected peers.
+    /// An empty response means no peer could serve the request.
+    async fn request_sync(&self, request: SyncRequest) -> Vec<SyncItem>;
 }
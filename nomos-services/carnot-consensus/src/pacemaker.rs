@@ -0,0 +1,226 @@
+// std
+use std::hash::Hash;
+use std::time::Duration;
+// crates
+use serde::{Deserialize, Serialize};
+// internal
+use carnot_engine::{Carnot, Overlay, SignableBlockId, View};
+
+const DEFAULT_MULTIPLIER: f64 = 2.0;
+const DEFAULT_MAX_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How the view timeout grows while views keep ending with a timeout qc
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BackoffSettings {
+    /// Factor applied to the timeout after each consecutive timeout qc.
+    /// Values below 1 are treated as 1, i.e. no backoff.
+    pub multiplier: f64,
+    /// Upper bound for the timeout of a view
+    pub max_timeout: Duration,
+}
+
+impl Default for BackoffSettings {
+    fn default() -> Self {
+        Self {
+            multiplier: DEFAULT_MULTIPLIER,
+            max_timeout: DEFAULT_MAX_TIMEOUT,
+        }
+    }
+}
+
+/// Keeps track of the timeout of the current view and of the views other nodes are known to be in.
+///
+/// The timeout grows exponentially with the number of consecutive views that ended with a
+/// timeout qc, so that nodes stop timing out in lock-step during long outages, and goes back to
+/// the base timeout as soon as a new block is committed.
+#[derive(Clone, Debug)]
+pub struct Pacemaker {
+    base_timeout: Duration,
+    backoff: BackoffSettings,
+    consecutive_timeouts: u32,
+    latest_committed_view: View,
+    highest_seen_view: View,
+}
+
+impl Pacemaker {
+    pub fn new(base_timeout: Duration, backoff: BackoffSettings) -> Self {
+        Self {
+            base_timeout,
+            backoff,
+            consecutive_timeouts: 0,
+            latest_committed_view: View::new(0),
+            highest_seen_view: View::new(0),
+        }
+    }
+
+    /// Timeout of the current view
+    pub fn timeout(&self) -> Duration {
+        let exponent = i32::try_from(self.consecutive_timeouts).unwrap_or(i32::MAX);
+        let factor = self.backoff.multiplier.max(1.0).powi(exponent);
+        let secs = (self.base_timeout.as_secs_f64() * factor)
+            .min(self.backoff.max_timeout.as_secs_f64())
+            .max(self.base_timeout.as_secs_f64());
+        Duration::from_secs_f64(secs)
+    }
+
+    /// Number of views in a row that ended with a timeout qc since the last commit
+    pub fn consecutive_timeouts(&self) -> u32 {
+        self.consecutive_timeouts
+    }
+
+    /// Update the timeout once `carnot` moved past `prev_view`
+    pub fn on_view_change<O, Id>(&mut self, carnot: &Carnot<O, Id>, prev_view: View)
+    where
+        O: Overlay,
+        Id: Copy + Eq + Hash + core::fmt::Debug + SignableBlockId,
+    {
+        let latest_committed_view = carnot.latest_committed_view();
+        if latest_committed_view > self.latest_committed_view {
+            self.latest_committed_view = latest_committed_view;
+            self.consecutive_timeouts = 0;
+        } else if carnot
+            .last_view_timeout_qc()
+            .is_some_and(|timeout_qc| timeout_qc.view() >= prev_view)
+        {
+            self.consecutive_timeouts = self.consecutive_timeouts.saturating_add(1);
+        }
+    }
+
+    /// Whether a node in `current_view` is lagging behind, according to a valid qc or timeout qc
+    /// for `view`, and should catch up. Only the first sighting of a view triggers it, so that
+    /// nodes do not try to catch up once for every message of the view.
+    pub fn should_sync(&mut self, current_view: View, view: View) -> bool {
+        if view <= current_view || view <= self.highest_seen_view {
+            return false;
+        }
+        self.highest_seen_view = view;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use carnot_engine::overlay::{FlatOverlay, FlatOverlaySettings, FreezeMembership, RoundRobin};
+    use carnot_engine::{
+        AggregateQc, Block, LeaderProof, NodeId, Qc, QcSignature, StandardQc, TimeoutQc,
+    };
+    use nomos_core::header::HeaderId;
+
+    use super::*;
+
+    type Engine = Carnot<FlatOverlay<RoundRobin, FreezeMembership>, HeaderId>;
+
+    const NODE: NodeId = NodeId::new([0; 32]);
+
+    fn engine() -> Engine {
+        let genesis = Block {
+            id: [0; 32].into(),
+            view: View::new(0),
+            parent_qc: Qc::Standard(StandardQc::genesis([0; 32].into())),
+            leader_proof: LeaderProof::LeaderId { leader_id: NODE },
+        };
+        Carnot::from_genesis(
+            NODE,
+            genesis,
+            FlatOverlay::new(FlatOverlaySettings {
+                nodes: vec![NODE],
+                leader: RoundRobin::new(),
+                leader_super_majority_threshold: None,
+            }),
+        )
+    }
+
+    fn time_out(pacemaker: &mut Pacemaker, engine: Engine) -> Engine {
+        let prev_view = engine.current_view();
+        let engine = engine
+            .receive_timeout_qc(TimeoutQc::new(
+                prev_view,
+                engine.high_qc(),
+                NODE,
+                QcSignature::default(),
+            ))
+            .unwrap();
+        pacemaker.on_view_change(&engine, prev_view);
+        engine
+    }
+
+    fn receive_block(pacemaker: &mut Pacemaker, engine: Engine, id: u8) -> Engine {
+        let prev_view = engine.current_view();
+        let parent = engine.tip();
+        let parent_qc = match engine.last_view_timeout_qc() {
+            // the first block after a timeout builds on the new view qc
+            Some(timeout_qc) if timeout_qc.view().next() == prev_view => {
+                Qc::Aggregated(AggregateQc {
+                    high_qc: engine.high_qc(),
+                    view: prev_view,
+                    signature: Default::default(),
+                })
+            }
+            _ => Qc::Standard(StandardQc {
+                view: parent.view,
+                id: parent.id,
+                signature: Default::default(),
+            }),
+        };
+        let block = Block {
+            id: [id; 32].into(),
+            view: prev_view.next(),
+            parent_qc,
+            leader_proof: LeaderProof::LeaderId { leader_id: NODE },
+        };
+        let engine = engine.receive_block(block).unwrap();
+        pacemaker.on_view_change(&engine, prev_view);
+        engine
+    }
+
+    #[test]
+    fn timeout_grows_up_to_max() {
+        let backoff = BackoffSettings {
+            multiplier: 2.0,
+            max_timeout: Duration::from_secs(10),
+        };
+        let mut pacemaker = Pacemaker::new(Duration::from_secs(1), backoff);
+        let mut engine = engine();
+        let mut timeouts = vec![pacemaker.timeout()];
+        for _ in 0..5 {
+            engine = time_out(&mut pacemaker, engine);
+            timeouts.push(pacemaker.timeout());
+        }
+        assert_eq!(
+            timeouts,
+            [1, 2, 4, 8, 10, 10].map(Duration::from_secs).to_vec()
+        );
+        assert_eq!(pacemaker.consecutive_timeouts(), 5);
+    }
+
+    #[test]
+    fn timeout_resets_after_commit() {
+        let mut pacemaker = Pacemaker::new(Duration::from_secs(1), BackoffSettings::default());
+        let mut engine = engine();
+        engine = time_out(&mut pacemaker, engine);
+        engine = time_out(&mut pacemaker, engine);
+        assert_eq!(pacemaker.timeout(), Duration::from_secs(4));
+
+        // blocks without a commit keep the backoff
+        engine = receive_block(&mut pacemaker, engine, 1);
+        engine = receive_block(&mut pacemaker, engine, 2);
+        engine = receive_block(&mut pacemaker, engine, 3);
+        assert_eq!(engine.latest_committed_view(), View::new(0));
+        assert_eq!(pacemaker.timeout(), Duration::from_secs(4));
+
+        engine = receive_block(&mut pacemaker, engine, 4);
+        assert_eq!(engine.latest_committed_view(), View::new(4));
+        assert_eq!(pacemaker.consecutive_timeouts(), 0);
+        assert_eq!(pacemaker.timeout(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn sync_once_per_higher_view() {
+        let mut pacemaker = Pacemaker::new(Duration::from_secs(1), BackoffSettings::default());
+        assert!(!pacemaker.should_sync(View::new(5), View::new(5)));
+        assert!(pacemaker.should_sync(View::new(5), View::new(8)));
+        assert!(!pacemaker.should_sync(View::new(5), View::new(8)));
+        assert!(!pacemaker.should_sync(View::new(5), View::new(7)));
+        assert!(pacemaker.should_sync(View::new(6), View::new(9)));
+    }
+}
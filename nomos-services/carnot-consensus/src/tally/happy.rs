@@ -7,9 +7,10 @@ use futures::{Stream, StreamExt};
 use nomos_core::header::HeaderId;
 
 // internal
-use super::CarnotTallySettings;
+use super::{CarnotTallySettings, SubtreeSettings};
 use crate::network::messages::VoteMsg;
 use crate::{Qc, StandardQc, Vote};
+use carnot_engine::QcSignature;
 use nomos_core::crypto::PublicKey;
 use nomos_core::vote::Tally;
 
@@ -51,12 +52,14 @@ impl Tally for CarnotTally {
     ) -> Result<(Self::Qc, Self::Outcome), Self::TallyError> {
         let mut seen = HashSet::new();
         let mut outcome = HashSet::new();
+        let mut signatures = Vec::new();
         // return early for leaf nodes
         if self.settings.threshold == 0 {
             return Ok((
                 Qc::Standard(StandardQc {
                     view: block.view,
                     id: block.id,
+                    signature: QcSignature::default(),
                 }),
                 outcome,
             ));
@@ -72,13 +75,36 @@ impl Tally for CarnotTally {
                 continue;
             }
 
-            seen.insert(vote.voter);
+            // votes forwarded by the root of a subtree must carry the qc of the subtree
+            if let Some(subtree) = self
+                .settings
+                .subtrees
+                .iter()
+                .find(|subtree| subtree.members.contains(&vote.voter))
+            {
+                if !subtree_qc_is_valid(subtree, vote.qc.as_ref(), &block) {
+                    tracing::debug!("dropping vote from {:?} without a valid qc", vote.voter);
+                    continue;
+                }
+            }
+
+            if seen.insert(vote.voter) {
+                signatures.push((vote.voter, vote.signature.clone()));
+            }
             outcome.insert(vote.vote.clone());
             if seen.len() >= self.settings.threshold {
+                let signature = QcSignature::aggregate(
+                    &self.settings.participating_nodes,
+                    signatures.iter().map(|(voter, signature)| {
+                        (*voter, signature.public_key(), signature.signature())
+                    }),
+                )
+                .map_err(|e| CarnotTallyError::InvalidVote(e.to_string()))?;
                 return Ok((
                     Qc::Standard(StandardQc {
                         view: vote.vote.view,
                         id: vote.vote.block,
+                        signature,
                     }),
                     outcome,
                 ));
@@ -87,3 +113,154 @@ impl Tally for CarnotTally {
         Err(CarnotTallyError::StreamEnded)
     }
 }
+
+fn subtree_qc_is_valid(subtree: &SubtreeSettings, qc: Option<&Qc>, block: &Block) -> bool {
+    match qc {
+        Some(Qc::Standard(qc)) => {
+            qc.view == block.view
+                && qc.id == block.id
+                // leaf committees do not aggregate any vote
+                && (subtree.threshold == 0 || qc.verify(&subtree.children, subtree.threshold))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use bls_signatures::PrivateKey;
+    use carnot_engine::overlay::{FreezeMembership, RoundRobin, TreeOverlay, TreeOverlaySettings};
+    use carnot_engine::{Carnot, Committee, LeaderProof, NodeId, Overlay, View};
+    use futures::executor::block_on;
+
+    use super::*;
+    use crate::crypto::node_id;
+
+    struct Tree {
+        keys: HashMap<NodeId, PrivateKey>,
+        carnot: Carnot<TreeOverlay<RoundRobin, FreezeMembership>, HeaderId>,
+        block: Block,
+    }
+
+    // 7 committees of 2 nodes: the root, its two children and four leaves
+    fn tree() -> Tree {
+        let keys = (1..=14u8)
+            .map(|i| PrivateKey::new([i; 32]))
+            .map(|key| (node_id(&key.public_key()), key))
+            .collect::<HashMap<_, _>>();
+        let mut nodes = keys.keys().copied().collect::<Vec<_>>();
+        nodes.sort();
+        let overlay = TreeOverlay::new(TreeOverlaySettings {
+            nodes: nodes.clone(),
+            current_leader: nodes[0],
+            number_of_committees: 7,
+            leader: RoundRobin::default(),
+            committee_membership: FreezeMembership,
+            super_majority_threshold: None,
+        });
+        let genesis = Block {
+            id: [0; 32].into(),
+            view: View::new(0),
+            parent_qc: Qc::Standard(StandardQc::genesis([0; 32].into())),
+            leader_proof: LeaderProof::LeaderId {
+                leader_id: nodes[0],
+            },
+        };
+        let block = Block {
+            id: [1; 32].into(),
+            view: View::new(1),
+            parent_qc: Qc::Standard(StandardQc::genesis([0; 32].into())),
+            leader_proof: LeaderProof::LeaderId {
+                leader_id: nodes[0],
+            },
+        };
+        Tree {
+            keys,
+            carnot: Carnot::from_genesis(nodes[0], genesis, overlay),
+            block,
+        }
+    }
+
+    impl Tree {
+        fn votes(&self, committee: &Committee, qc: Option<Qc>) -> Vec<VoteMsg> {
+            committee
+                .iter()
+                .map(|voter| {
+                    let vote = Vote {
+                        view: self.block.view,
+                        block: self.block.id,
+                    };
+                    VoteMsg::new(*voter, vote, qc.clone(), &self.keys[voter])
+                })
+                .collect()
+        }
+
+        fn root_children(&self) -> Vec<Committee> {
+            let root_member = *self.carnot.root_committee().iter().next().unwrap();
+            self.carnot.overlay().child_committees(root_member)
+        }
+
+        // qc formed by a child of the root committee over the votes of its own children
+        fn subtree_qc(&self, child: &Committee) -> Qc {
+            let member = *child.iter().next().unwrap();
+            let overlay = self.carnot.overlay();
+            let children = overlay
+                .child_committees(member)
+                .into_iter()
+                .flatten()
+                .collect::<Committee>();
+            let tally = CarnotTally::new(CarnotTallySettings {
+                threshold: overlay.super_majority_threshold(member),
+                participating_nodes: children.clone(),
+                subtrees: Vec::new(),
+            });
+            let votes = futures::stream::iter(self.votes(&children, None));
+            block_on(tally.tally(self.block.clone(), votes)).unwrap().0
+        }
+    }
+
+    #[test]
+    fn leader_counts_votes_of_root_children() {
+        let tree = tree();
+        let settings = CarnotTallySettings::for_leader(&tree.carnot);
+        assert_eq!(settings.subtrees.len(), 2);
+
+        let mut votes = tree.votes(&tree.carnot.root_committee(), None);
+        for child in tree.root_children() {
+            let qc = tree.subtree_qc(&child);
+            votes.extend(tree.votes(&child, Some(qc)));
+        }
+        let (qc, _) = block_on(
+            CarnotTally::new(settings).tally(tree.block.clone(), futures::stream::iter(votes)),
+        )
+        .unwrap();
+
+        let Qc::Standard(qc) = qc else {
+            panic!("expected a standard qc");
+        };
+        assert!(qc.verify(
+            &tree.carnot.root_committee_with_children(),
+            tree.carnot.leader_super_majority_threshold()
+        ));
+    }
+
+    #[test]
+    fn votes_of_root_children_without_qc_are_dropped() {
+        let tree = tree();
+        let settings = CarnotTallySettings::for_leader(&tree.carnot);
+
+        let mut votes = tree.votes(&tree.carnot.root_committee(), None);
+        for child in tree.root_children() {
+            votes.extend(tree.votes(&child, None));
+        }
+        // the root committee alone is not enough to reach the leader threshold
+        assert!(matches!(
+            block_on(
+                CarnotTally::new(settings).tally(tree.block.clone(), futures::stream::iter(votes))
+            ),
+            Err(CarnotTallyError::StreamEnded)
+        ));
+    }
+}
@@ -8,11 +8,58 @@ pub mod unhappy;
 use serde::{Deserialize, Serialize};
 
 // internal
-use carnot_engine::Committee;
+use carnot_engine::{Carnot, Committee, Overlay};
+use nomos_core::header::HeaderId;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CarnotTallySettings {
     pub threshold: usize,
     // TODO: this probably should be dynamic and should change with the view (?)
     pub participating_nodes: Committee,
+    /// Participants whose votes are only counted if they carry the QC of the votes of their
+    /// own children. Only used when tallying votes for a block.
+    #[serde(default)]
+    pub subtrees: Vec<SubtreeSettings>,
+}
+
+/// A committee forwarding to the leader the votes it collected from its children
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SubtreeSettings {
+    pub members: Committee,
+    /// Nodes whose votes are aggregated in the forwarded QC
+    pub children: Committee,
+    pub threshold: usize,
+}
+
+impl CarnotTallySettings {
+    /// Settings for the next leader, who collects votes from the members of the root committee
+    /// and of its child committees
+    pub fn for_leader<O: Overlay>(carnot: &Carnot<O, HeaderId>) -> Self {
+        let overlay = carnot.overlay();
+        let subtrees = carnot
+            .root_committee()
+            .iter()
+            .next()
+            .map(|root_member| overlay.child_committees(*root_member))
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|members| {
+                let member = *members.iter().next()?;
+                Some(SubtreeSettings {
+                    children: overlay
+                        .child_committees(member)
+                        .into_iter()
+                        .flatten()
+                        .collect(),
+                    threshold: overlay.super_majority_threshold(member),
+                    members,
+                })
+            })
+            .collect();
+        Self {
+            threshold: carnot.leader_super_majority_threshold(),
+            participating_nodes: carnot.root_committee_with_children(),
+            subtrees,
+        }
+    }
 }
@@ -1,14 +1,22 @@
 // std
-use std::{collections::HashSet, convert::Infallible};
+use std::collections::HashSet;
 // crates
 use futures::{Stream, StreamExt};
 // internal
 use super::CarnotTallySettings;
 use crate::network::messages::TimeoutMsg;
 use crate::Timeout;
-use carnot_engine::View;
+use carnot_engine::{QcSignature, View};
 use nomos_core::vote::Tally;
 
+#[derive(thiserror::Error, Debug)]
+pub enum TimeoutTallyError {
+    #[error("Did not receive enough votes")]
+    InsufficientVotes,
+    #[error("Received invalid vote: {0}")]
+    InvalidVote(String),
+}
+
 #[derive(Clone, Debug)]
 pub struct TimeoutTally {
     settings: CarnotTallySettings,
@@ -17,10 +25,10 @@ pub struct TimeoutTally {
 #[async_trait::async_trait]
 impl Tally for TimeoutTally {
     type Vote = TimeoutMsg;
-    type Qc = ();
+    type Qc = QcSignature;
     type Subject = View;
     type Outcome = HashSet<Timeout>;
-    type TallyError = Infallible;
+    type TallyError = TimeoutTallyError;
     type Settings = CarnotTallySettings;
 
     fn new(settings: Self::Settings) -> Self {
@@ -34,6 +42,7 @@ impl Tally for TimeoutTally {
     ) -> Result<(Self::Qc, Self::Outcome), Self::TallyError> {
         let mut seen = HashSet::new();
         let mut outcome = HashSet::new();
+        let mut signatures = Vec::new();
         while let Some(vote) = vote_stream.next().await {
             // check timeout view is valid
             if vote.vote.view != view {
@@ -45,12 +54,21 @@ impl Tally for TimeoutTally {
                 continue;
             }
 
-            seen.insert(vote.voter);
+            if seen.insert(vote.voter) {
+                signatures.push((vote.voter, vote.signature.clone()));
+            }
             outcome.insert(vote.vote.clone());
             if seen.len() >= self.settings.threshold {
-                return Ok(((), outcome));
+                let signature = QcSignature::aggregate(
+                    &self.settings.participating_nodes,
+                    signatures.iter().map(|(voter, signature)| {
+                        (*voter, signature.public_key(), signature.signature())
+                    }),
+                )
+                .map_err(|e| TimeoutTallyError::InvalidVote(e.to_string()))?;
+                return Ok((signature, outcome));
             }
         }
-        unreachable!()
+        Err(TimeoutTallyError::InsufficientVotes)
     }
 }
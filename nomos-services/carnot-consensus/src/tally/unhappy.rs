@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 // internal
 use super::CarnotTallySettings;
 use crate::network::messages::NewViewMsg;
+use carnot_engine::QcSignature;
 use nomos_core::vote::Tally;
 
 use crate::{NewView, TimeoutQc};
@@ -14,6 +15,8 @@ use crate::{NewView, TimeoutQc};
 pub enum NewViewTallyError {
     #[error("Did not receive enough votes")]
     InsufficientVotes,
+    #[error("Received invalid vote: {0}")]
+    InvalidVote(String),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -24,7 +27,7 @@ pub struct NewViewTally {
 #[async_trait::async_trait]
 impl Tally for NewViewTally {
     type Vote = NewViewMsg;
-    type Qc = ();
+    type Qc = QcSignature;
     type Subject = TimeoutQc;
     type Outcome = HashSet<NewView>;
     type TallyError = NewViewTallyError;
@@ -41,9 +44,10 @@ impl Tally for NewViewTally {
     ) -> Result<(Self::Qc, Self::Outcome), Self::TallyError> {
         let mut seen = HashSet::new();
         let mut outcome = HashSet::new();
+        let mut signatures = Vec::new();
         // return early for leaf nodes
         if self.settings.threshold == 0 {
-            return Ok(((), outcome));
+            return Ok((QcSignature::default(), outcome));
         }
 
         while let Some(vote) = vote_stream.next().await {
@@ -56,10 +60,19 @@ impl Tally for NewViewTally {
             if !self.settings.participating_nodes.contains(&vote.voter) {
                 continue;
             }
-            seen.insert(vote.voter);
+            if seen.insert(vote.voter) {
+                signatures.push((vote.voter, vote.signature.clone()));
+            }
             outcome.insert(vote.vote.clone());
             if seen.len() >= self.settings.threshold {
-                return Ok(((), outcome));
+                let signature = QcSignature::aggregate(
+                    &self.settings.participating_nodes,
+                    signatures.iter().map(|(voter, signature)| {
+                        (*voter, signature.public_key(), signature.signature())
+                    }),
+                )
+                .map_err(|e| NewViewTallyError::InvalidVote(e.to_string()))?;
+                return Ok((signature, outcome));
             }
         }
         Err(NewViewTallyError::InsufficientVotes)
@@ -0,0 +1,154 @@
+use cryptarchia_engine::{Branch, Cryptarchia};
+use nomos_core::header::HeaderId;
+use serde::{Deserialize, Serialize};
+
+/// Number of events buffered for each subscriber before it starts lagging behind
+pub const EVENTS_BUFFER_SIZE: usize = 256;
+
+/// Changes to the local chain, in the order they happened
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChainEvent {
+    /// The local chain was extended by a block building on the previous tip
+    NewTip {
+        #[cfg_attr(feature = "openapi", schema(value_type = String))]
+        tip: HeaderId,
+    },
+    /// The fork choice rule switched to a chain not building on the previous tip
+    Reorg {
+        #[cfg_attr(feature = "openapi", schema(value_type = String))]
+        old_tip: HeaderId,
+        #[cfg_attr(feature = "openapi", schema(value_type = String))]
+        new_tip: HeaderId,
+        /// Last block shared by the old and the new chain
+        #[cfg_attr(feature = "openapi", schema(value_type = String))]
+        lca: HeaderId,
+        /// Blocks removed from the local chain, from the old tip back to the common ancestor
+        #[cfg_attr(feature = "openapi", schema(value_type = Vec<String>))]
+        rolled_back: Vec<HeaderId>,
+        /// Blocks added to the local chain, from the common ancestor up to the new tip
+        #[cfg_attr(feature = "openapi", schema(value_type = Vec<String>))]
+        applied: Vec<HeaderId>,
+    },
+    /// The block is now k blocks deep in the local chain and won't be reverted
+    Immutable {
+        #[cfg_attr(feature = "openapi", schema(value_type = String))]
+        id: HeaderId,
+    },
+}
+
+/// Events describing how the local chain changed from `old` to `new`.
+/// `new` must be derived from `old` and not pruned yet, so that blocks in both chains are known.
+pub fn chain_events(old: &Cryptarchia<HeaderId>, new: &Cryptarchia<HeaderId>) -> Vec<ChainEvent> {
+    let branches = new.branches();
+    let mut events = Vec::new();
+
+    let (old_tip, new_tip) = (old.tip(), new.tip());
+    if old_tip != new_tip {
+        let old_branch = branches.get(&old_tip).expect("old tip should be known");
+        let new_branch = branches.get(&new_tip).expect("new tip should be known");
+        if new_branch.parent() == old_tip {
+            events.push(ChainEvent::NewTip { tip: new_tip });
+        } else {
+            let lca = branches.lca(old_branch, new_branch);
+            let rolled_back = ancestors_until(new, old_branch, &lca);
+            let mut applied = ancestors_until(new, new_branch, &lca);
+            applied.reverse();
+            events.push(ChainEvent::Reorg {
+                old_tip,
+                new_tip,
+                lca: lca.id(),
+                rolled_back,
+                applied,
+            });
+        }
+    }
+
+    let (old_lib, new_lib) = (old.lib_branch(), new.lib_branch());
+    if old_lib.id() != new_lib.id() {
+        let mut immutable = ancestors_until(new, &new_lib, &old_lib);
+        immutable.reverse();
+        events.extend(immutable.into_iter().map(|id| ChainEvent::Immutable { id }));
+    }
+    events
+}
+
+// Blocks from `from` back to `until`, excluding the latter.
+// `until` is expected to be an ancestor of `from`, the walk stops at its height anyway.
+fn ancestors_until(
+    cryptarchia: &Cryptarchia<HeaderId>,
+    from: &Branch<HeaderId>,
+    until: &Branch<HeaderId>,
+) -> Vec<HeaderId> {
+    let mut ids = Vec::new();
+    let mut current = from;
+    while current.length() > until.length() {
+        ids.push(current.id());
+        let Some(parent) = cryptarchia.branches().get(&current.parent()) else {
+            break;
+        };
+        current = parent;
+    }
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cryptarchia_engine::{Config, Slot};
+
+    fn id(i: u8) -> HeaderId {
+        [i; 32].into()
+    }
+
+    #[test]
+    fn tip_updates_and_reorgs() {
+        let config = Config {
+            security_param: 2,
+            active_slot_coeff: 1.0,
+        };
+        let genesis = Cryptarchia::from_genesis(id(0), config);
+        let receive = |engine: &Cryptarchia<HeaderId>, block: u8, parent: u8| {
+            engine
+                .receive_block(id(block), id(parent), Slot::from(block as u64))
+                .unwrap()
+        };
+
+        let b1 = receive(&genesis, 1, 0);
+        assert_eq!(
+            chain_events(&genesis, &b1),
+            vec![ChainEvent::NewTip { tip: id(1) }]
+        );
+
+        // a fork of the same length does not change the local chain
+        let b2 = receive(&b1, 2, 1);
+        let fork = receive(&b2, 3, 1);
+        assert!(chain_events(&b2, &fork).is_empty());
+
+        // until it becomes the longest one, which also makes block 1 k blocks deep
+        let fork = receive(&fork, 4, 3);
+        assert_eq!(
+            chain_events(&b2, &fork),
+            vec![
+                ChainEvent::Reorg {
+                    old_tip: id(2),
+                    new_tip: id(4),
+                    lca: id(1),
+                    rolled_back: vec![id(2)],
+                    applied: vec![id(3), id(4)],
+                },
+                ChainEvent::Immutable { id: id(1) }
+            ]
+        );
+
+        let b5 = receive(&fork, 5, 4);
+        assert_eq!(
+            chain_events(&fork, &b5),
+            vec![
+                ChainEvent::NewTip { tip: id(5) },
+                ChainEvent::Immutable { id: id(3) }
+            ]
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+use cryptarchia_engine::Slot;
+use nomos_core::header::HeaderId;
+
+/// Maximum number of blocks held back until their slot starts
+pub const MAX_FUTURE_BLOCKS: usize = 256;
+/// Number of blocks too far in the future a peer can send before being penalised
+pub const MAX_OFFENCES: usize = 3;
+/// Time after which offences are forgotten
+pub const OFFENCES_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// Buffer of blocks received slightly before their slot started.
+///
+/// When the buffer is full, blocks for the furthest slots are evicted first.
+pub struct FutureBlocks<Block> {
+    by_slot: BTreeMap<Slot, Vec<(HeaderId, Block)>>,
+    ids: HashSet<HeaderId>,
+    capacity: usize,
+}
+
+impl<Block> FutureBlocks<Block> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            by_slot: BTreeMap::new(),
+            ids: HashSet::new(),
+            capacity,
+        }
+    }
+
+    pub fn insert(&mut self, id: HeaderId, slot: Slot, block: Block) {
+        if !self.ids.insert(id) {
+            return;
+        }
+        self.by_slot.entry(slot).or_default().push((id, block));
+
+        while self.ids.len() > self.capacity {
+            let Some(mut last) = self.by_slot.last_entry() else {
+                break;
+            };
+            if let Some((id, _)) = last.get_mut().pop() {
+                self.ids.remove(&id);
+            }
+            if last.get().is_empty() {
+                last.remove();
+            }
+        }
+    }
+
+    /// Remove and return the buffered blocks whose slot is not after `slot`, oldest first
+    pub fn take_until(&mut self, slot: Slot) -> Vec<Block> {
+        let later = self.by_slot.split_off(&(slot + 1));
+        std::mem::replace(&mut self.by_slot, later)
+            .into_values()
+            .flatten()
+            .map(|(id, block)| {
+                self.ids.remove(&id);
+                block
+            })
+            .collect()
+    }
+
+    pub fn contains(&self, id: &HeaderId) -> bool {
+        self.ids.contains(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+/// Count of invalid blocks sent by each peer within a time window
+pub struct Offences<Peer> {
+    // number of offences and time of the first one in the current window
+    counts: HashMap<Peer, (usize, Instant)>,
+    threshold: usize,
+    window: Duration,
+}
+
+impl<Peer: Eq + Hash> Offences<Peer> {
+    pub fn new(threshold: usize, window: Duration) -> Self {
+        Self {
+            counts: HashMap::new(),
+            threshold,
+            window,
+        }
+    }
+
+    /// Record an offence from `peer` at time `now`.
+    /// Returns `true` once the peer reaches the threshold within the window and should be
+    /// penalised, after which its count starts over.
+    pub fn report(&mut self, peer: Peer, now: Instant) -> bool {
+        // forget offences from previous windows
+        self.counts
+            .retain(|_, (_, start)| now.saturating_duration_since(*start) < self.window);
+        let (count, _) = self.counts.entry(peer).or_insert((0, now));
+        *count += 1;
+        if *count < self.threshold {
+            return false;
+        }
+        *count = 0;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(i: u8) -> HeaderId {
+        [i; 32].into()
+    }
+
+    #[test]
+    fn blocks_are_released_when_their_slot_starts() {
+        let mut blocks = FutureBlocks::new(10);
+        blocks.insert(id(1), Slot::from(3), 1);
+        blocks.insert(id(2), Slot::from(5), 2);
+        blocks.insert(id(3), Slot::from(4), 3);
+        // duplicates are ignored
+        blocks.insert(id(3), Slot::from(4), 3);
+        assert_eq!(blocks.len(), 3);
+
+        assert!(blocks.take_until(Slot::from(2)).is_empty());
+        assert_eq!(blocks.take_until(Slot::from(4)), vec![1, 3]);
+        assert!(!blocks.contains(&id(1)));
+        assert!(blocks.contains(&id(2)));
+        assert_eq!(blocks.take_until(Slot::from(10)), vec![2]);
+        assert!(blocks.is_empty());
+    }
+
+    #[test]
+    fn furthest_blocks_are_evicted() {
+        let mut blocks = FutureBlocks::new(2);
+        blocks.insert(id(1), Slot::from(3), 1);
+        blocks.insert(id(2), Slot::from(5), 2);
+        blocks.insert(id(3), Slot::from(4), 3);
+        assert_eq!(blocks.len(), 2);
+        assert!(!blocks.contains(&id(2)));
+        assert_eq!(blocks.take_until(Slot::from(5)), vec![1, 3]);
+    }
+
+    #[test]
+    fn peers_are_penalised_after_repeated_offences() {
+        let now = Instant::now();
+        let mut offences = Offences::new(2, Duration::from_secs(60));
+        assert!(!offences.report("alice", now));
+        assert!(!offences.report("bob", now));
+        assert!(offences.report("alice", now));
+        assert!(!offences.report("alice", now));
+        assert!(offences.report("bob", now));
+    }
+
+    #[test]
+    fn offences_are_forgotten_after_the_window() {
+        let now = Instant::now();
+        let window = Duration::from_secs(60);
+        let mut offences = Offences::new(2, window);
+        assert!(!offences.report("alice", now));
+        assert!(!offences.report("alice", now + window));
+        assert!(offences.report("alice", now + window + Duration::from_secs(1)));
+    }
+}
@@ -0,0 +1,103 @@
+use std::path::Path;
+
+use argon2::Argon2;
+use chacha20poly1305::{
+    aead::{Aead, KeyInit},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use cryptarchia_ledger::Coin;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid keystore format: {0}")]
+    Format(#[from] serde_json::Error),
+    #[error("Key derivation failed: {0}")]
+    KeyDerivation(argon2::Error),
+    #[error("Wrong password or corrupted keystore")]
+    Decryption,
+}
+
+/// Leader coins encrypted with a key derived from a password.
+///
+/// Keys are derived with Argon2id and coins are encrypted with ChaCha20-Poly1305,
+/// so a wrong password is detected when decrypting.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Keystore {
+    #[serde(with = "hex")]
+    salt: [u8; 16],
+    #[serde(with = "hex")]
+    nonce: [u8; 12],
+    #[serde(with = "hex")]
+    ciphertext: Vec<u8>,
+}
+
+impl Keystore {
+    pub fn encrypt(coins: &[Coin], password: &str) -> Result<Self, Error> {
+        let mut salt = [0; 16];
+        let mut nonce = [0; 12];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let plaintext = serde_json::to_vec(coins)?;
+        let ciphertext = cipher(password, &salt)?
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_ref())
+            .expect("encryption with a valid key and nonce should not fail");
+        Ok(Self {
+            salt,
+            nonce,
+            ciphertext,
+        })
+    }
+
+    pub fn decrypt(&self, password: &str) -> Result<Vec<Coin>, Error> {
+        let plaintext = cipher(password, &self.salt)?
+            .decrypt(Nonce::from_slice(&self.nonce), self.ciphertext.as_ref())
+            .map_err(|_| Error::Decryption)?;
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let file = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let file = std::fs::File::create(path)?;
+        Ok(serde_json::to_writer_pretty(file, self)?)
+    }
+}
+
+fn cipher(password: &str, salt: &[u8]) -> Result<ChaCha20Poly1305, Error> {
+    let mut key = [0; 32];
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(Error::KeyDerivation)?;
+    Ok(ChaCha20Poly1305::new(Key::from_slice(&key)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keystore_roundtrip() {
+        let coins = vec![
+            Coin::new([1; 32], [0; 32].into(), 10.into()),
+            Coin::new([2; 32], [1; 32].into(), 20.into()),
+        ];
+        let keystore = Keystore::encrypt(&coins, "password").unwrap();
+        let keystore: Keystore =
+            serde_json::from_str(&serde_json::to_string(&keystore).unwrap()).unwrap();
+
+        assert_eq!(keystore.decrypt("password").unwrap(), coins);
+        assert!(matches!(
+            keystore.decrypt("wrong password"),
+            Err(Error::Decryption)
+        ));
+    }
+}
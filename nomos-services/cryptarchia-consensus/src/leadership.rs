@@ -1,22 +1,48 @@
 use cryptarchia_engine::Slot;
-use cryptarchia_ledger::{Coin, Config, EpochState, LeaderProof};
+use cryptarchia_ledger::{Coin, Config, EpochState, LeaderProof, LedgerState};
+
+use crate::wallet::Wallet;
 
 pub struct Leader {
-    coins: Vec<Coin>,
+    wallet: Wallet,
     config: cryptarchia_ledger::Config,
 }
 
 impl Leader {
-    pub fn new(coins: Vec<Coin>, config: Config) -> Self {
-        Leader { coins, config }
+    pub fn new(wallet: Wallet, config: Config) -> Self {
+        Leader { wallet, config }
+    }
+
+    pub fn wallet(&self) -> &Wallet {
+        &self.wallet
+    }
+
+    pub fn wallet_mut(&mut self) -> &mut Wallet {
+        &mut self.wallet
+    }
+
+    /// Build a proof for the first coin that wins the slot lottery on the chain with
+    /// `ledger_state`. Coins are not evolved here: the proof nullifies the coin once
+    /// the block is applied, which is when the wallet moves on to its evolution.
+    pub fn build_proof_for(
+        &self,
+        ledger_state: &LedgerState,
+        epoch_state: &EpochState,
+        slot: Slot,
+    ) -> Option<LeaderProof> {
+        self.wallet
+            .coins(ledger_state)
+            .find(|coin| {
+                Self::can_lead(coin, ledger_state, epoch_state)
+                    && coin.is_slot_leader(epoch_state, slot, &self.config.consensus_config)
+            })
+            .map(|coin| coin.to_proof(epoch_state, slot))
     }
 
-    pub fn build_proof_for(&mut self, epoch_state: &EpochState, slot: Slot) -> Option<LeaderProof> {
-        for coin in &self.coins {
-            if coin.is_slot_leader(epoch_state, slot, &self.config.consensus_config) {
-                return Some(coin.to_proof(slot));
-            }
-        }
-        None
+    // Same checks as the ledger, so that we don't propose blocks that would be rejected
+    fn can_lead(coin: &Coin, ledger_state: &LedgerState, epoch_state: &EpochState) -> bool {
+        let commitment = coin.commitment();
+        !ledger_state.is_nullified(&coin.nullifier())
+            && (ledger_state.can_lead(&commitment) || epoch_state.is_eligible_leader(&commitment))
     }
 }
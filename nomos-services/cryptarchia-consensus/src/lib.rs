@@ -1,13 +1,26 @@
+pub mod events;
+mod future_blocks;
+pub mod keystore;
 mod leadership;
 pub mod network;
-mod time;
+mod recovery;
+pub mod sync;
+pub mod time;
+pub mod wallet;
 
+use crate::time::SlotTiming;
 use core::fmt::Debug;
-use cryptarchia_engine::Slot;
-use cryptarchia_ledger::{LeaderProof, LedgerState};
+use cryptarchia_engine::{Epoch, Slot};
+use cryptarchia_ledger::{EpochState, LeaderProof, LedgerState, Nullifier};
+use events::ChainEvent;
+use future_blocks::{FutureBlocks, Offences};
 use futures::StreamExt;
-use network::{messages::NetworkMessage, NetworkAdapter};
+use network::{
+    messages::{NetworkMessage, SyncRequest},
+    NetworkAdapter,
+};
 use nomos_core::da::certificate::{BlobCertificateSelect, Certificate};
+use nomos_core::execution::{State as AccountState, StateRoot};
 use nomos_core::header::{cryptarchia::Header, HeaderId};
 use nomos_core::tx::{Transaction, TxSelect};
 use nomos_core::{
@@ -27,13 +40,19 @@ use overwatch_rs::services::{
     state::{NoOperator, NoState},
     ServiceCore, ServiceData, ServiceId,
 };
+use recovery::Snapshot;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_with::serde_as;
+use std::borrow::Borrow;
+use std::collections::{HashSet, VecDeque};
 use std::hash::Hash;
+use std::time::Instant;
+use sync::{LocalChain, Orphans};
 use thiserror::Error;
-use tokio::sync::oneshot::Sender;
+use tokio::sync::{broadcast, mpsc, oneshot::Sender};
 use tokio_stream::wrappers::IntervalStream;
 use tracing::{error, instrument};
+use wallet::{Wallet, WalletInfo};
 
 #[derive(Debug, Clone, Error)]
 pub enum Error {
@@ -41,19 +60,42 @@ pub enum Error {
     Ledger(#[from] cryptarchia_ledger::LedgerError<HeaderId>),
     #[error("Consensus error: {0}")]
     Consensus(#[from] cryptarchia_engine::Error<HeaderId>),
+    #[error("Unexpected account state root: {0:?}")]
+    StateRoot(StateRoot),
 }
 
 struct Cryptarchia {
     ledger: cryptarchia_ledger::Ledger<HeaderId>,
     consensus: cryptarchia_engine::Cryptarchia<HeaderId>,
+    local_chain: LocalChain,
 }
 
 impl Cryptarchia {
+    fn new(
+        consensus: cryptarchia_engine::Cryptarchia<HeaderId>,
+        ledger: cryptarchia_ledger::Ledger<HeaderId>,
+    ) -> Self {
+        Self {
+            local_chain: LocalChain::new(&consensus),
+            ledger,
+            consensus,
+        }
+    }
+
     fn tip(&self) -> HeaderId {
         self.consensus.tip()
     }
 
-    fn try_apply_header(&self, header: &Header) -> Result<Self, Error> {
+    fn try_apply_block<'a>(
+        &self,
+        header: &Header,
+        transactions: impl IntoIterator<Item = &'a cryptarchia_ledger::Tx>,
+    ) -> Result<Self, Error> {
+        // cryptarchia transactions move notes and never touch the account state,
+        // so every block commits to the empty one
+        if header.state_root() != AccountState::default().root() {
+            return Err(Error::StateRoot(header.state_root()));
+        }
         let id = header.id();
         let parent = header.parent();
         let slot = header.slot();
@@ -66,23 +108,71 @@ impl Cryptarchia {
                 .orphaned_proofs()
                 .iter()
                 .map(|imported_header| (imported_header.id(), *imported_header.leader_proof())),
+            transactions,
         )?;
         let consensus = self.consensus.receive_block(id, parent, slot)?;
+        let local_chain = self.local_chain.follow(&consensus);
+
+        Ok(Self {
+            ledger,
+            consensus,
+            local_chain,
+        })
+    }
 
-        Ok(Self { ledger, consensus })
+    // Prune forks deeper than k from both the consensus engine and the ledger, keeping
+    // memory usage bounded for long-running nodes
+    fn prune_forks(&mut self) {
+        let k = self.ledger.config().consensus_config.security_param as u64;
+        for pruned in self.consensus.prune_forks(k) {
+            self.ledger.prune_state_at(&pruned);
+        }
     }
 
-    fn epoch_state_for_slot(&self, slot: Slot) -> Option<&cryptarchia_ledger::EpochState> {
-        let tip = self.tip();
-        let state = self.ledger.state(&tip).expect("no state for tip");
-        let requested_epoch = self.ledger.config().epoch(slot);
-        if state.epoch_state().epoch() == requested_epoch {
-            Some(state.epoch_state())
-        } else if requested_epoch == state.next_epoch_state().epoch() {
-            Some(state.next_epoch_state())
-        } else {
-            None
+    fn contains(&self, id: &HeaderId) -> bool {
+        self.consensus.branches().get(id).is_some()
+    }
+
+    // Ids of the block `id` and up to `limit - 1` of its ancestors, from the oldest to the newest.
+    // The genesis block is never included as it's not a real block.
+    fn ancestors(&self, id: HeaderId, limit: u64) -> Vec<HeaderId> {
+        let branches = self.consensus.branches();
+        let mut ids = Vec::new();
+        let mut current = branches.get(&id);
+        while let Some(branch) = current {
+            if ids.len() as u64 >= limit || branch.id() == self.consensus.genesis() {
+                break;
+            }
+            ids.push(branch.id());
+            current = branches.get(&branch.parent());
         }
+        ids.reverse();
+        ids
+    }
+
+    // Ids of up to `limit` blocks following `from` in the local chain, from the oldest to the newest.
+    // If `from` is not part of the local chain, no blocks are returned.
+    fn local_chain_after(&self, from: HeaderId, limit: u64) -> Vec<HeaderId> {
+        self.local_chain.after(&self.consensus, from, limit)
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let lib = self.consensus.lib_branch();
+        let lib_state = self
+            .ledger
+            .state(&lib.id())
+            .expect("states in the local chain are never pruned")
+            .clone();
+        Snapshot { lib, lib_state }
+    }
+
+    fn epoch_state_for_slot(&self, slot: Slot) -> Option<&EpochState> {
+        self.epoch_state(self.tip(), self.ledger.config().epoch(slot))
+    }
+
+    // Epoch state as seen by the branch ending with block `id`
+    fn epoch_state(&self, id: HeaderId, epoch: Epoch) -> Option<&EpochState> {
+        self.ledger.state(&id)?.epoch_state_for(epoch)
     }
 }
 
@@ -95,6 +185,10 @@ pub struct CryptarchiaSettings<Ts, Bs> {
     pub config: cryptarchia_ledger::Config,
     pub genesis_state: LedgerState,
     pub time: time::Config,
+    #[serde(default)]
+    pub sync: sync::Config,
+    #[serde(default)]
+    pub wallet: wallet::Config,
 }
 
 impl<Ts, Bs> CryptarchiaSettings<Ts, Bs> {
@@ -105,6 +199,8 @@ impl<Ts, Bs> CryptarchiaSettings<Ts, Bs> {
         config: cryptarchia_ledger::Config,
         genesis_state: LedgerState,
         time: time::Config,
+        sync: sync::Config,
+        wallet: wallet::Config,
     ) -> Self {
         Self {
             transaction_selector_settings,
@@ -112,6 +208,8 @@ impl<Ts, Bs> CryptarchiaSettings<Ts, Bs> {
             config,
             genesis_state,
             time,
+            sync,
+            wallet,
         }
     }
 }
@@ -179,6 +277,7 @@ where
     DaPool: MemPool<BlockId = HeaderId> + Send + Sync + 'static,
     DaPool::Settings: Send + Sync + 'static,
     ClPool::Item: Transaction<Hash = ClPool::Key>
+        + Borrow<cryptarchia_ledger::Tx>
         + Debug
         + Clone
         + Eq
@@ -253,42 +352,121 @@ where
             transaction_selector_settings,
             blob_selector_settings,
             time,
+            sync,
+            wallet,
         } = self.service_state.settings_reader.get_updated_settings();
 
-        let genesis_id = HeaderId::from([0; 32]);
-        let mut cryptarchia = Cryptarchia {
-            consensus: <cryptarchia_engine::Cryptarchia<_>>::from_genesis(
-                genesis_id,
-                config.consensus_config.clone(),
-            ),
-            ledger: <cryptarchia_ledger::Ledger<_>>::from_genesis(
-                genesis_id,
-                genesis_state,
-                config.clone(),
-            ),
-        };
+        let mut cryptarchia = Self::restore(genesis_state, config.clone(), &storage_relay).await;
         let adapter = A::new(network_relay).await;
         let tx_selector = TxS::new(transaction_selector_settings);
         let blob_selector = BS::new(blob_selector_settings);
 
-        let mut incoming_blocks = adapter.blocks_stream().await;
-        let mut leader = leadership::Leader::new(vec![], config);
         let timer = time::Timer::new(time);
+        let (events, _) = broadcast::channel(events::EVENTS_BUFFER_SIZE);
+
+        let mut incoming_blocks = adapter.blocks_stream().await;
+        let mut orphans = Orphans::new(sync.max_orphans);
+        let mut future_blocks = FutureBlocks::new(future_blocks::MAX_FUTURE_BLOCKS);
+        let mut offences =
+            Offences::new(future_blocks::MAX_OFFENCES, future_blocks::OFFENCES_WINDOW);
+        let (synced_blocks_sender, mut synced_blocks) = mpsc::channel(SYNC_BUFFER_SIZE);
+
+        if sync.initial_sync {
+            cryptarchia = Self::initial_sync(
+                cryptarchia,
+                &adapter,
+                &timer,
+                &events,
+                sync.batch_size,
+                storage_relay.clone(),
+                cl_mempool_relay.clone(),
+                da_mempool_relay.clone(),
+            )
+            .await;
+        }
+        let mut sync_requests = adapter.sync_requests_stream().await;
+        let request_parent = |parent| {
+            Self::request_ancestors(
+                adapter.clone(),
+                parent,
+                sync.batch_size,
+                synced_blocks_sender.clone(),
+            )
+        };
+
+        let evolutions = recovery::load(&storage_relay, recovery::EVOLUTIONS_KEY).await;
+        let wallet = Wallet::restore(wallet.load_coins()?, evolutions);
+        let mut leader = leadership::Leader::new(wallet, config);
 
         let mut slot_timer = IntervalStream::new(timer.slot_interval());
 
         let mut lifecycle_stream = self.service_state.lifecycle_handle.message_stream();
         loop {
             tokio::select! {
-                    Some(block) = incoming_blocks.next() => {
-                        cryptarchia = Self::process_block(
-                            cryptarchia,
-                            block,
-                            storage_relay.clone(),
-                            cl_mempool_relay.clone(),
-                            da_mempool_relay.clone(),
-                        )
-                        .await;
+                    Some((block, author)) = incoming_blocks.next() => {
+                        let id = block.header().id();
+                        let slot = block.header().cryptarchia().slot();
+                        match timer.slot_timing(slot) {
+                            SlotTiming::Current => {
+                                cryptarchia = Self::process_block(
+                                    cryptarchia,
+                                    block,
+                                    &mut orphans,
+                                    &request_parent,
+                                    &timer,
+                                    &events,
+                                    storage_relay.clone(),
+                                    cl_mempool_relay.clone(),
+                                    da_mempool_relay.clone(),
+                                )
+                                .await;
+                            }
+                            SlotTiming::NearFuture => {
+                                tracing::debug!(
+                                    "holding back block {id:?} until slot {} starts",
+                                    u64::from(slot)
+                                );
+                                future_blocks.insert(id, slot, block);
+                            }
+                            SlotTiming::FarFuture => {
+                                tracing::debug!(
+                                    "rejecting block {id:?} from future slot {}",
+                                    u64::from(slot)
+                                );
+                                if let Some(author) = author {
+                                    if offences.report(author.clone(), Instant::now()) {
+                                        adapter.penalize_peer(author).await;
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    Some((requested, blocks)) = synced_blocks.recv() => {
+                        if blocks.is_empty() {
+                            // no peer could provide the missing block, drop the blocks waiting for it
+                            // so that it can be requested again if more blocks building on it arrive
+                            tracing::debug!("could not fetch missing block {requested:?}");
+                            orphans.take_children(&requested);
+                        }
+                        for block in blocks {
+                            cryptarchia = Self::process_block(
+                                cryptarchia,
+                                block,
+                                &mut orphans,
+                                &request_parent,
+                                &timer,
+                                &events,
+                                storage_relay.clone(),
+                                cl_mempool_relay.clone(),
+                                da_mempool_relay.clone(),
+                            )
+                            .await;
+                        }
+                    }
+
+                    Some((request, reply)) = sync_requests.next() => {
+                        Self::serve_sync_request(&cryptarchia, request, sync.batch_size, reply, storage_relay.clone());
                     }
 
                     _ = slot_timer.next() => {
@@ -299,11 +477,24 @@ where
                             tracing::error!("trying to propose a block for slot {} but epoch state is not available", u64::from(slot));
                             continue;
                         };
-                        if let Some(proof) = leader.build_proof_for(epoch_state, slot) {
+                        let lib_state = cryptarchia.ledger.state(&cryptarchia.consensus.lib()).expect("no state for lib");
+                        if leader.wallet_mut().finalize(lib_state) {
+                            recovery::store(&storage_relay, recovery::EVOLUTIONS_KEY, leader.wallet().evolutions()).await;
+                        }
+                        let ledger_state = cryptarchia.ledger.state(&parent).expect("no state for tip");
+                        if let Some(proof) = leader.build_proof_for(ledger_state, epoch_state, slot) {
+                            let orphaned_proofs = Self::orphaned_proofs(
+                                &cryptarchia,
+                                parent,
+                                &proof,
+                                &storage_relay,
+                            ).await;
                             // TODO: spawn as a separate task?
                             let block = Self::propose_block(
                                 parent,
+                                ledger_state.clone(),
                                 proof,
+                                orphaned_proofs,
                                 tx_selector.clone(),
                                 blob_selector.clone(),
                                 cl_mempool_relay.clone(),
@@ -314,10 +505,25 @@ where
                                 let _ = adapter.broadcast(NetworkMessage::Block(block)).await;
                             }
                         }
+
+                        for block in future_blocks.take_until(slot) {
+                            cryptarchia = Self::process_block(
+                                cryptarchia,
+                                block,
+                                &mut orphans,
+                                &request_parent,
+                                &timer,
+                                &events,
+                                storage_relay.clone(),
+                                cl_mempool_relay.clone(),
+                                da_mempool_relay.clone(),
+                            )
+                            .await;
+                        }
                     }
 
                     Some(msg) = self.service_state.inbound_relay.next() => {
-                        Self::process_message(&cryptarchia, msg);
+                        Self::process_message(&cryptarchia, leader.wallet(), &events, msg);
                     }
                     Some(msg) = lifecycle_stream.next() => {
                         if Self::should_stop_service(msg).await {
@@ -339,6 +545,7 @@ where
     DaPool: MemPool<BlockId = HeaderId> + Send + Sync + 'static,
     DaPool::Settings: Send + Sync + 'static,
     ClPool::Item: Transaction<Hash = ClPool::Key>
+        + Borrow<cryptarchia_ledger::Tx>
         + Debug
         + Clone
         + Eq
@@ -381,74 +588,426 @@ where
         }
     }
 
-    fn process_message(cryptarchia: &Cryptarchia, msg: ConsensusMsg) {
+    fn process_message(
+        cryptarchia: &Cryptarchia,
+        wallet: &Wallet,
+        events: &broadcast::Sender<ChainEvent>,
+        msg: ConsensusMsg,
+    ) {
         match msg {
             ConsensusMsg::Info { tx } => {
                 let info = CryptarchiaInfo {
                     tip: cryptarchia.tip(),
+                    lib: cryptarchia.consensus.lib(),
                 };
                 tx.send(info).unwrap_or_else(|e| {
                     tracing::error!("Could not send consensus info through channel: {:?}", e)
                 });
             }
+            ConsensusMsg::GetHeaders { from, to, tx } => {
+                // default to the tip and the genesis block if not specified
+                let from = from.unwrap_or(cryptarchia.tip());
+                let to = to.unwrap_or(cryptarchia.consensus.genesis());
+                let branches = cryptarchia.consensus.branches();
+                let mut headers = Vec::new();
+                let mut current = from;
+                while let Some(branch) = branches.get(&current) {
+                    headers.push(branch.id());
+                    if current == to
+                        || current == cryptarchia.consensus.genesis()
+                        || headers.len() >= HEADERS_LIMIT
+                    {
+                        break;
+                    }
+                    current = branch.parent();
+                }
+                tx.send(headers).unwrap_or_else(|e| {
+                    tracing::error!("Could not send headers through channel: {:?}", e)
+                });
+            }
+            ConsensusMsg::Wallet { tx } => {
+                let state = cryptarchia
+                    .ledger
+                    .state(&cryptarchia.tip())
+                    .expect("no state for tip");
+                tx.send(wallet.info(state)).unwrap_or_else(|e| {
+                    tracing::error!("Could not send wallet info through channel: {:?}", e)
+                });
+            }
+            ConsensusMsg::SubscribeChainEvents { tx } => {
+                tx.send(events.subscribe()).unwrap_or_else(|_| {
+                    tracing::error!("Could not send chain events subscription through channel")
+                });
+            }
+            ConsensusMsg::EpochState { epoch, block, tx } => {
+                let block = block.unwrap_or(cryptarchia.tip());
+                let epoch_state = cryptarchia.epoch_state(block, epoch).cloned();
+                tx.send(epoch_state).unwrap_or_else(|e| {
+                    tracing::error!("Could not send epoch state through channel: {:?}", e)
+                });
+            }
+        }
+    }
+
+    /// Rebuild the consensus state from the last persisted snapshot, replaying the stored blocks
+    /// up to the last known tip. The genesis state is only used if no snapshot is found,
+    /// i.e. on the first boot.
+    async fn restore(
+        genesis_state: LedgerState,
+        config: cryptarchia_ledger::Config,
+        storage_relay: &OutboundRelay<StorageMsg<Storage>>,
+    ) -> Cryptarchia {
+        let genesis_id = HeaderId::from([0; 32]);
+        let Some(Snapshot { lib, lib_state }) =
+            recovery::load(storage_relay, recovery::SNAPSHOT_KEY).await
+        else {
+            tracing::info!("no consensus state found, starting from genesis");
+            let cryptarchia = Cryptarchia::new(
+                <cryptarchia_engine::Cryptarchia<_>>::from_genesis(
+                    genesis_id,
+                    config.consensus_config.clone(),
+                ),
+                <cryptarchia_ledger::Ledger<_>>::from_genesis(genesis_id, genesis_state, config),
+            );
+            recovery::store(
+                storage_relay,
+                recovery::SNAPSHOT_KEY,
+                cryptarchia.snapshot(),
+            )
+            .await;
+            return cryptarchia;
+        };
+
+        let lib_id = lib.id();
+        let mut cryptarchia = Cryptarchia::new(
+            <cryptarchia_engine::Cryptarchia<_>>::from_lib(
+                lib,
+                genesis_id,
+                config.consensus_config.clone(),
+            ),
+            <cryptarchia_ledger::Ledger<_>>::from_genesis(lib_id, lib_state, config),
+        );
+
+        // walk back from the last known tip to the snapshot
+        let mut blocks: Vec<Block<ClPool::Item, DaPool::Item>> = Vec::new();
+        let mut current = recovery::load(storage_relay, recovery::TIP_KEY)
+            .await
+            .unwrap_or(lib_id);
+        while current != lib_id {
+            let Some(block) = recovery::load::<_, Block<_, _>>(storage_relay, current).await else {
+                tracing::warn!(
+                    "block {current:?} not found, can't restore blocks after {lib_id:?}"
+                );
+                blocks.clear();
+                break;
+            };
+            current = block.header().parent();
+            blocks.push(block);
+        }
+
+        for block in blocks.into_iter().rev() {
+            match cryptarchia.try_apply_block(
+                block.header().cryptarchia(),
+                block.transactions().map(Borrow::borrow),
+            ) {
+                Ok(new_state) => cryptarchia = new_state,
+                Err(e) => {
+                    tracing::error!("could not restore block {:?}: {e}", block.header().id());
+                    break;
+                }
+            }
+        }
+        tracing::info!(
+            "restored consensus state from {lib_id:?}, tip: {:?}",
+            cryptarchia.tip()
+        );
+        cryptarchia
+    }
+
+    /// Persist the consensus state so that it can be restored after a restart
+    async fn persist(
+        old: &Cryptarchia,
+        new: &Cryptarchia,
+        storage_relay: &OutboundRelay<StorageMsg<Storage>>,
+    ) {
+        if old.tip() != new.tip() {
+            recovery::store(storage_relay, recovery::TIP_KEY, new.tip()).await;
+        }
+        let lib = new.consensus.lib_branch();
+        if old.consensus.lib() != lib.id() && lib.length() % recovery::SNAPSHOT_INTERVAL == 0 {
+            recovery::store(storage_relay, recovery::SNAPSHOT_KEY, new.snapshot()).await;
         }
     }
 
+    /// Apply a block and all the buffered blocks that were waiting for it.
+    /// Blocks with an unknown parent are buffered, and `request_parent` is called
+    /// the first time a missing parent is encountered.
     #[allow(clippy::type_complexity, clippy::too_many_arguments)]
     #[instrument(
         level = "debug",
-        skip(cryptarchia, storage_relay, cl_mempool_relay, da_mempool_relay)
+        skip(
+            cryptarchia,
+            orphans,
+            request_parent,
+            timer,
+            events,
+            storage_relay,
+            cl_mempool_relay,
+            da_mempool_relay
+        )
     )]
     async fn process_block(
         mut cryptarchia: Cryptarchia,
         block: Block<ClPool::Item, DaPool::Item>,
+        orphans: &mut Orphans<Block<ClPool::Item, DaPool::Item>>,
+        request_parent: impl Fn(HeaderId),
+        timer: &time::Timer,
+        events: &broadcast::Sender<ChainEvent>,
         storage_relay: OutboundRelay<StorageMsg<Storage>>,
         cl_mempool_relay: OutboundRelay<MempoolMsg<HeaderId, ClPool::Item, ClPool::Key>>,
         da_mempool_relay: OutboundRelay<MempoolMsg<HeaderId, DaPool::Item, DaPool::Key>>,
     ) -> Cryptarchia {
-        tracing::debug!("received proposal {:?}", block);
+        let mut blocks = VecDeque::from([block]);
+        while let Some(block) = blocks.pop_front() {
+            tracing::debug!("received proposal {:?}", block);
 
-        // TODO: filter on time?
+            let header = block.header();
+            let id = header.id();
+            if cryptarchia.contains(&id) || orphans.contains(&id) {
+                tracing::debug!("block {id:?} already known");
+                continue;
+            }
+            // blocks received through gossip are held back until their slot starts,
+            // anything else from the future is not worth keeping
+            if timer.slot_timing(header.cryptarchia().slot()) != SlotTiming::Current {
+                tracing::debug!("block {id:?} is from a future slot");
+                continue;
+            }
+            match cryptarchia.try_apply_block(
+                block.header().cryptarchia(),
+                block.transactions().map(Borrow::borrow),
+            ) {
+                Ok(new_state) => {
+                    // remove included content from mempool
+                    mark_in_block(
+                        cl_mempool_relay.clone(),
+                        block.transactions().map(Transaction::hash),
+                        id,
+                    )
+                    .await;
 
-        let header = block.header();
-        let id = header.id();
-        match cryptarchia.try_apply_header(block.header().cryptarchia()) {
-            Ok(new_state) => {
-                // remove included content from mempool
-                mark_in_block(
-                    cl_mempool_relay,
-                    block.transactions().map(Transaction::hash),
-                    id,
-                )
+                    mark_in_block(
+                        da_mempool_relay.clone(),
+                        block.blobs().map(Certificate::hash),
+                        id,
+                    )
+                    .await;
+
+                    // store block
+                    let msg = <StorageMsg<_>>::new_store_message(header.id(), block);
+                    if let Err((e, _msg)) = storage_relay.send(msg).await {
+                        tracing::error!("Could not send block to storage: {e}");
+                    }
+
+                    Self::persist(&cryptarchia, &new_state, &storage_relay).await;
+                    // an error only means that there are no subscribers
+                    for event in events::chain_events(&cryptarchia.consensus, &new_state.consensus)
+                    {
+                        let _ = events.send(event);
+                    }
+                    cryptarchia = new_state;
+                    cryptarchia.prune_forks();
+
+                    // blocks that were waiting for this one can now be applied
+                    blocks.extend(orphans.take_children(&id));
+                }
+                Err(Error::Consensus(cryptarchia_engine::Error::ParentMissing(parent))) => {
+                    tracing::debug!("missing parent {:?}", parent);
+                    if orphans.insert(id, parent, block) {
+                        request_parent(parent);
+                    }
+                }
+                // the block builds on a fork that was pruned or is older than the LIB, fetching
+                // its ancestors would only download the pruned blocks again
+                Err(Error::Consensus(cryptarchia_engine::Error::ParentPruned(parent))) => {
+                    tracing::debug!("block {id:?} builds on pruned block {parent:?}");
+                    // neither can the blocks that were waiting for this one
+                    let mut dropped = vec![id];
+                    while let Some(id) = dropped.pop() {
+                        dropped.extend(
+                            orphans
+                                .take_children(&id)
+                                .iter()
+                                .map(|block| block.header().id()),
+                        );
+                    }
+                }
+                Err(e) => tracing::debug!("invalid block {:?}: {e:?}", block),
+            }
+        }
+
+        cryptarchia
+    }
+
+    /// Fetch `id` and its ancestors from peers in the background.
+    /// The fetched blocks are sent through `synced_blocks`, together with the requested id.
+    fn request_ancestors(
+        adapter: A,
+        id: HeaderId,
+        limit: u64,
+        synced_blocks: mpsc::Sender<(HeaderId, Vec<Block<ClPool::Item, DaPool::Item>>)>,
+    ) where
+        A: NetworkAdapter<Tx = ClPool::Item, BlobCertificate = DaPool::Item>,
+    {
+        tokio::spawn(async move {
+            let blocks = adapter
+                .request_blocks(SyncRequest::Ancestors { id, limit })
                 .await;
+            if let Err(e) = synced_blocks.send((id, blocks)).await {
+                tracing::error!("Could not send synced blocks to consensus: {e}");
+            }
+        });
+    }
 
-                mark_in_block(da_mempool_relay, block.blobs().map(Certificate::hash), id).await;
+    /// Download the chain from peers, starting from the current tip, until no more blocks are available
+    async fn initial_sync(
+        mut cryptarchia: Cryptarchia,
+        adapter: &A,
+        timer: &time::Timer,
+        events: &broadcast::Sender<ChainEvent>,
+        batch_size: u64,
+        storage_relay: OutboundRelay<StorageMsg<Storage>>,
+        cl_mempool_relay: OutboundRelay<MempoolMsg<HeaderId, ClPool::Item, ClPool::Key>>,
+        da_mempool_relay: OutboundRelay<MempoolMsg<HeaderId, DaPool::Item, DaPool::Key>>,
+    ) -> Cryptarchia
+    where
+        A: NetworkAdapter<Tx = ClPool::Item, BlobCertificate = DaPool::Item>,
+    {
+        // blocks are requested following our local chain, so they should never be orphans
+        let mut orphans = Orphans::new(0);
+        loop {
+            let tip = cryptarchia.tip();
+            let blocks = adapter
+                .request_blocks(SyncRequest::Chain {
+                    from: tip,
+                    limit: batch_size,
+                })
+                .await;
+            if blocks.is_empty() {
+                break;
+            }
+            for block in blocks {
+                cryptarchia = Self::process_block(
+                    cryptarchia,
+                    block,
+                    &mut orphans,
+                    |_| {},
+                    timer,
+                    events,
+                    storage_relay.clone(),
+                    cl_mempool_relay.clone(),
+                    da_mempool_relay.clone(),
+                )
+                .await;
+            }
+            if cryptarchia.tip() == tip {
+                tracing::warn!("initial sync did not make progress from {tip:?}");
+                break;
+            }
+        }
+        tracing::info!("initial sync completed, tip: {:?}", cryptarchia.tip());
+        cryptarchia
+    }
 
-                // store block
-                let msg = <StorageMsg<_>>::new_store_message(header.id(), block);
-                if let Err((e, _msg)) = storage_relay.send(msg).await {
-                    tracing::error!("Could not send block to storage: {e}");
+    fn serve_sync_request(
+        cryptarchia: &Cryptarchia,
+        request: SyncRequest,
+        batch_size: u64,
+        reply: Sender<Vec<Block<ClPool::Item, DaPool::Item>>>,
+        storage_relay: OutboundRelay<StorageMsg<Storage>>,
+    ) {
+        // don't let peers make us walk and send the whole chain in a single response
+        let ids = match request {
+            SyncRequest::Ancestors { id, limit } => {
+                cryptarchia.ancestors(id, limit.min(batch_size))
+            }
+            SyncRequest::Chain { from, limit } => {
+                cryptarchia.local_chain_after(from, limit.min(batch_size))
+            }
+        };
+        tokio::spawn(async move {
+            let mut blocks = Vec::with_capacity(ids.len());
+            for id in ids {
+                let (msg, receiver) = <StorageMsg<_>>::new_load_message(id);
+                if let Err((e, _)) = storage_relay.send(msg).await {
+                    tracing::error!("Could not send load request to storage: {e}");
+                    break;
+                }
+                match receiver.recv().await {
+                    Ok(Some(block)) => blocks.push(block),
+                    // serve only a contiguous sequence of blocks
+                    _ => break,
                 }
+            }
+            if reply.send(blocks).is_err() {
+                tracing::debug!("Could not send sync response, requester is gone");
+            }
+        });
+    }
 
-                cryptarchia = new_state;
+    /// Headers of abandoned blocks whose leader proofs can be imported by a block building
+    /// on `parent`, so that coins used in forks can't be reused to grind the leader lottery
+    async fn orphaned_proofs(
+        cryptarchia: &Cryptarchia,
+        parent: HeaderId,
+        proof: &LeaderProof,
+        storage_relay: &OutboundRelay<StorageMsg<Storage>>,
+    ) -> Vec<Header> {
+        let slot = proof.slot();
+        let mut candidates = Vec::new();
+        for branch in cryptarchia.consensus.abandoned_blocks() {
+            // blocks are sorted by slot, only proofs from earlier slots are imported
+            if branch.slot() >= slot {
+                break;
             }
-            Err(Error::Consensus(cryptarchia_engine::Error::ParentMissing(parent))) => {
-                tracing::debug!("missing parent {:?}", parent);
-                // TODO: request parent block
+            let block: Option<Block<ClPool::Item, DaPool::Item>> =
+                recovery::load(storage_relay, branch.id()).await;
+            match block {
+                Some(block) => candidates.push(block.header().cryptarchia().clone()),
+                None => tracing::debug!("abandoned block {:?} not found in storage", branch.id()),
             }
-            Err(e) => tracing::debug!("invalid block {:?}: {e:?}", block),
         }
 
-        cryptarchia
+        let importable = cryptarchia.ledger.importable_orphan_proofs(
+            parent,
+            slot,
+            proof,
+            candidates
+                .iter()
+                .map(|header| (header.id(), *header.leader_proof())),
+        );
+        candidates
+            .into_iter()
+            .filter(|header| importable.contains(&header.id()))
+            .collect()
     }
 
     #[instrument(
         level = "debug",
-        skip(cl_mempool_relay, da_mempool_relay, tx_selector, blob_selector)
+        skip(
+            ledger_state,
+            orphaned_proofs,
+            cl_mempool_relay,
+            da_mempool_relay,
+            tx_selector,
+            blob_selector
+        )
     )]
     async fn propose_block(
         parent: HeaderId,
+        ledger_state: LedgerState,
         proof: LeaderProof,
+        orphaned_proofs: Vec<Header>,
         tx_selector: TxS,
         blob_selector: BS,
         cl_mempool_relay: OutboundRelay<MempoolMsg<HeaderId, ClPool::Item, ClPool::Key>>,
@@ -460,11 +1019,19 @@ where
 
         match futures::join!(cl_txs, da_certs) {
             (Ok(cl_txs), Ok(da_certs)) => {
+                // coins used in leader proofs are spent by the block itself
+                let leader_nullifiers = orphaned_proofs
+                    .iter()
+                    .map(|header| *header.leader_proof().nullifier())
+                    .chain([*proof.nullifier()])
+                    .collect();
                 let Ok(block) = BlockBuilder::new(tx_selector, blob_selector)
-                    .with_cryptarchia_builder(Builder::new(parent, proof))
-                    .with_transactions(cl_txs)
+                    .with_cryptarchia_builder(
+                        Builder::new(parent, proof).with_orphaned_proofs(orphaned_proofs),
+                    )
+                    .with_transactions(valid_transactions(ledger_state, leader_nullifiers, cl_txs))
                     .with_blobs_certificates(da_certs)
-                    .build()
+                    .build_without_state()
                 else {
                     panic!("Proposal block should always succeed to be built")
                 };
@@ -477,9 +1044,37 @@ where
     }
 }
 
+// Number of sync responses that can be waiting to be processed
+const SYNC_BUFFER_SIZE: usize = 16;
+// Maximum number of headers returned by a single `GetHeaders` request
+const HEADERS_LIMIT: usize = 512;
+
 #[derive(Debug)]
 pub enum ConsensusMsg {
-    Info { tx: Sender<CryptarchiaInfo> },
+    Info {
+        tx: Sender<CryptarchiaInfo>,
+    },
+    /// Ids of the blocks from `from` back to `to`, both included, newest first.
+    /// Default to the current tip and the genesis block.
+    GetHeaders {
+        from: Option<HeaderId>,
+        to: Option<HeaderId>,
+        tx: Sender<Vec<HeaderId>>,
+    },
+    Wallet {
+        tx: Sender<WalletInfo>,
+    },
+    /// Subscribe to changes to the local chain, see [`ChainEvent`]
+    SubscribeChainEvents {
+        tx: Sender<broadcast::Receiver<ChainEvent>>,
+    },
+    /// Snapshot of the state used to verify leader proofs in `epoch`, as seen by the branch
+    /// ending with `block`. Defaults to the current tip.
+    EpochState {
+        epoch: Epoch,
+        block: Option<HeaderId>,
+        tx: Sender<Option<EpochState>>,
+    },
 }
 
 impl RelayMessage for ConsensusMsg {}
@@ -489,6 +1084,8 @@ impl RelayMessage for ConsensusMsg {}
 #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct CryptarchiaInfo {
     pub tip: HeaderId,
+    /// Last immutable block, forks diverging before this block are pruned
+    pub lib: HeaderId,
 }
 
 async fn get_mempool_contents<Item, Key>(
@@ -507,6 +1104,34 @@ async fn get_mempool_contents<Item, Key>(
     rx.await
 }
 
+/// Keep only the transactions that can be applied on top of `state`, in order,
+/// so that the proposed block is valid
+fn valid_transactions<Item: Borrow<cryptarchia_ledger::Tx>>(
+    mut state: LedgerState,
+    leader_nullifiers: HashSet<Nullifier>,
+    txs: impl Iterator<Item = Item>,
+) -> impl Iterator<Item = Item> {
+    txs.filter(move |tx| {
+        let tx = tx.borrow();
+        if tx
+            .inputs()
+            .any(|input| leader_nullifiers.contains(&input.nullifier()))
+        {
+            return false;
+        }
+        match state.clone().try_apply_tx::<HeaderId>(tx) {
+            Ok(new_state) => {
+                state = new_state;
+                true
+            }
+            Err(e) => {
+                tracing::debug!("skipping invalid transaction {:?}: {e}", tx.digest());
+                false
+            }
+        }
+    })
+}
+
 async fn mark_in_block<Item, Key>(
     mempool: OutboundRelay<MempoolMsg<HeaderId, Item, Key>>,
     ids: impl Iterator<Item = Key>,
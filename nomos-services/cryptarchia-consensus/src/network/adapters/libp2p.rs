@@ -1,20 +1,40 @@
 // std
-use std::hash::Hash;
+use std::{hash::Hash, io, time::Duration};
 // crates
+use futures::AsyncWriteExt;
+use rand::seq::SliceRandom;
 use serde::{de::DeserializeOwned, Serialize};
-use tokio::sync::broadcast::error::RecvError;
-use tokio_stream::{wrappers::BroadcastStream, StreamExt};
+use tokio::sync::{broadcast::error::RecvError, mpsc, oneshot};
+use tokio_stream::{
+    wrappers::{BroadcastStream, ReceiverStream},
+    StreamExt,
+};
 // internal
-use crate::network::{messages::NetworkMessage, BoxedStream, NetworkAdapter};
-use nomos_core::{block::Block, wire};
+use crate::network::{
+    messages::{NetworkMessage, SyncRequest},
+    BoxedStream, NetworkAdapter,
+};
+use nomos_core::{block::Block, wire::envelope};
+use nomos_libp2p::{
+    libp2p::{Stream, StreamProtocol},
+    libp2p_stream::Control,
+    PeerId,
+};
 use nomos_network::{
-    backends::libp2p::{Command, Event, EventKind, Libp2p},
+    backends::libp2p::{Command, Event, EventKind, Libp2p, StreamControl},
     NetworkMsg, NetworkService,
 };
 use overwatch_rs::services::{relay::OutboundRelay, ServiceData};
 
 const TOPIC: &str = "/cryptarchia/proto";
+// Sync protocol messages are sent in wire envelopes, see [`envelope`].
+// The end of a response is signaled by closing the stream.
+const SYNC_PROTOCOL: StreamProtocol = StreamProtocol::new("/cryptarchia/sync/0.2.0");
+// Time to wait for a single peer to serve a sync request before trying the next one
+const SYNC_TIMEOUT: Duration = Duration::from_secs(10);
 const BUFFER_SIZE: usize = 64;
+// How long gossip from a misbehaving peer is ignored, on all topics
+const PENALTY_DURATION: Duration = Duration::from_secs(10 * 60);
 type Relay<T> = OutboundRelay<<NetworkService<T> as ServiceData>::Message>;
 
 #[derive(Clone)]
@@ -24,9 +44,12 @@ where
     BlobCert: Clone + Eq + Hash,
 {
     network_relay: OutboundRelay<<NetworkService<Libp2p> as ServiceData>::Message>,
-    blocks: tokio::sync::broadcast::Sender<Block<Tx, BlobCert>>,
+    blocks: tokio::sync::broadcast::Sender<(Block<Tx, BlobCert>, Option<PeerId>)>,
+    stream_control: Control,
 }
 
+type SyncRequestWithReply<Tx, BlobCert> = (SyncRequest, oneshot::Sender<Vec<Block<Tx, BlobCert>>>);
+
 impl<Tx, BlobCert> LibP2pAdapter<Tx, BlobCert>
 where
     Tx: Clone + Eq + Hash + Serialize,
@@ -40,6 +63,85 @@ where
             tracing::error!("error subscribing to {topic}: {e}");
         };
     }
+
+    async fn stream_control(relay: &Relay<Libp2p>) -> Control {
+        let (reply, control) = oneshot::channel();
+        if let Err((e, _)) = relay
+            .send(NetworkMsg::Process(Command::GetStreamControl { reply }))
+            .await
+        {
+            tracing::error!("error requesting stream control: {e}");
+        }
+        let StreamControl(control) = control
+            .await
+            .expect("Network backend should always provide a stream control");
+        control
+    }
+
+    async fn connected_peers(&self) -> Vec<PeerId> {
+        let (reply, peers) = oneshot::channel();
+        if let Err((e, _)) = self
+            .network_relay
+            .send(NetworkMsg::Process(Command::ConnectedPeers { reply }))
+            .await
+        {
+            tracing::error!("error requesting connected peers: {e}");
+            return vec![];
+        }
+        peers.await.unwrap_or_default()
+    }
+}
+
+impl<Tx, BlobCert> LibP2pAdapter<Tx, BlobCert>
+where
+    Tx: Serialize + DeserializeOwned + Clone + Eq + Hash + Send + 'static,
+    BlobCert: Serialize + DeserializeOwned + Clone + Eq + Hash + Send + 'static,
+{
+    async fn request_blocks_from(
+        &self,
+        peer_id: PeerId,
+        request: SyncRequest,
+    ) -> io::Result<Vec<Block<Tx, BlobCert>>> {
+        let mut stream = self
+            .stream_control
+            .clone()
+            .open_stream(peer_id, SYNC_PROTOCOL)
+            .await
+            .map_err(io::Error::other)?;
+        envelope::write_message(&mut stream, &request).await?;
+        let mut blocks = Vec::new();
+        while let Some(block) = envelope::read_message(&mut stream).await? {
+            // blocks are large, don't buffer more than what was asked for
+            if blocks.len() as u64 >= request.limit() {
+                self.penalize_peer(peer_id).await;
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("peer sent more than {} blocks", request.limit()),
+                ));
+            }
+            blocks.push(block);
+        }
+        Ok(blocks)
+    }
+
+    async fn serve_sync_request(
+        mut stream: Stream,
+        requests: mpsc::Sender<SyncRequestWithReply<Tx, BlobCert>>,
+    ) -> io::Result<()> {
+        let Some(request) = envelope::read_message(&mut stream).await? else {
+            return Ok(());
+        };
+        let (reply, response) = oneshot::channel();
+        requests
+            .send((request, reply))
+            .await
+            .map_err(|_| io::Error::other("sync requests are not being served"))?;
+        let blocks = response.await.map_err(io::Error::other)?;
+        for block in blocks {
+            envelope::write_message(&mut stream, &block).await?;
+        }
+        stream.close().await
+    }
 }
 
 #[async_trait::async_trait]
@@ -51,6 +153,7 @@ where
     type Backend = Libp2p;
     type Tx = Tx;
     type BlobCertificate = BlobCert;
+    type PeerId = PeerId;
 
     async fn new(network_relay: Relay<Libp2p>) -> Self {
         let relay = network_relay.clone();
@@ -78,19 +181,24 @@ where
             let mut incoming_messages = receiver.await.unwrap();
             loop {
                 match incoming_messages.recv().await {
-                    Ok(Event::Message(message)) => {
-                        match nomos_core::wire::deserialize(&message.data) {
-                            Ok(msg) => match msg {
-                                NetworkMessage::Block(block) => {
-                                    tracing::debug!("received block {:?}", block.header().id());
-                                    if let Err(err) = blocks_sender.send(block) {
-                                        tracing::error!("error sending block to consensus: {err}");
-                                    }
+                    Ok(Event::Message {
+                        message,
+                        propagation_source,
+                    }) => match envelope::decode::<NetworkMessage<Tx, BlobCert>>(&message.data) {
+                        Ok(msg) => match msg {
+                            NetworkMessage::Block(block) => {
+                                tracing::debug!("received block {:?}", block.header().id());
+                                // gossipsub forwards messages before they are validated, so only
+                                // the author can be held accountable for them
+                                let author = propagation_source
+                                    .filter(|peer| message.source.as_ref() == Some(peer));
+                                if let Err(err) = blocks_sender.send((block, author)) {
+                                    tracing::error!("error sending block to consensus: {err}");
                                 }
-                            },
-                            _ => tracing::debug!("unrecognized gossipsub message"),
-                        }
-                    }
+                            }
+                        },
+                        Err(e) => tracing::debug!("invalid gossipsub message: {e}"),
+                    },
                     Err(RecvError::Lagged(n)) => {
                         tracing::error!("lagged messages: {n}")
                     }
@@ -98,21 +206,32 @@ where
                 }
             }
         });
+        let stream_control = Self::stream_control(&network_relay).await;
         Self {
             network_relay,
             blocks,
+            stream_control,
         }
     }
 
-    async fn blocks_stream(&self) -> BoxedStream<Block<Self::Tx, Self::BlobCertificate>> {
+    async fn blocks_stream(
+        &self,
+    ) -> BoxedStream<(Block<Self::Tx, Self::BlobCertificate>, Option<PeerId>)> {
         Box::new(BroadcastStream::new(self.blocks.subscribe()).filter_map(Result::ok))
     }
 
     async fn broadcast(&self, message: NetworkMessage<Self::Tx, Self::BlobCertificate>) {
+        let data = match envelope::encode(&message) {
+            Ok(data) => data,
+            Err(e) => {
+                tracing::error!("error encoding message: {e}");
+                return;
+            }
+        };
         if let Err((e, message)) = self
             .network_relay
             .send(NetworkMsg::Process(Command::Broadcast {
-                message: wire::serialize(&message).unwrap().into_boxed_slice(),
+                message: data.into_boxed_slice(),
                 topic: TOPIC.into(),
             }))
             .await
@@ -120,4 +239,59 @@ where
             tracing::error!("error broadcasting {message:?}: {e}");
         };
     }
+
+    async fn sync_requests_stream(
+        &self,
+    ) -> BoxedStream<SyncRequestWithReply<Self::Tx, Self::BlobCertificate>> {
+        let (sender, receiver) = mpsc::channel(BUFFER_SIZE);
+        match self.stream_control.clone().accept(SYNC_PROTOCOL) {
+            Ok(mut incoming_streams) => {
+                tokio::spawn(async move {
+                    while let Some((peer_id, stream)) = incoming_streams.next().await {
+                        let sender = sender.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = Self::serve_sync_request(stream, sender).await {
+                                tracing::debug!("failed to serve sync request from {peer_id}: {e}");
+                            }
+                        });
+                    }
+                });
+            }
+            Err(e) => tracing::error!("error accepting sync streams: {e}"),
+        }
+        Box::new(ReceiverStream::new(receiver))
+    }
+
+    async fn request_blocks(
+        &self,
+        request: SyncRequest,
+    ) -> Vec<Block<Self::Tx, Self::BlobCertificate>> {
+        let mut peers = self.connected_peers().await;
+        peers.shuffle(&mut rand::thread_rng());
+        for peer_id in peers {
+            match tokio::time::timeout(SYNC_TIMEOUT, self.request_blocks_from(peer_id, request))
+                .await
+            {
+                Ok(Ok(blocks)) if !blocks.is_empty() => return blocks,
+                Ok(Ok(_)) => tracing::debug!("peer {peer_id} could not serve {request:?}"),
+                Ok(Err(e)) => tracing::debug!("sync request {request:?} to {peer_id} failed: {e}"),
+                Err(_) => tracing::debug!("sync request {request:?} to {peer_id} timed out"),
+            }
+        }
+        vec![]
+    }
+
+    async fn penalize_peer(&self, peer: PeerId) {
+        tracing::info!("blacklisting peer {peer} for {PENALTY_DURATION:?}");
+        if let Err((e, _)) = self
+            .network_relay
+            .send(NetworkMsg::Process(Command::BlacklistPeer {
+                peer_id: peer,
+                duration: PENALTY_DURATION,
+            }))
+            .await
+        {
+            tracing::error!("error blacklisting peer {peer}: {e}");
+        }
+    }
 }
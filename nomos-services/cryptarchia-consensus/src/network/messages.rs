@@ -1,9 +1,13 @@
 // std
 use std::hash::Hash;
 // crates
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 // internal
-use nomos_core::block::Block;
+use nomos_core::{
+    block::{Block, MAX_BLOCK_SIZE},
+    header::HeaderId,
+    wire::envelope::{message_type, WireMessage},
+};
 
 #[derive(Serialize, Deserialize)]
 pub enum NetworkMessage<Tx, Blob>
@@ -13,3 +17,36 @@ where
 {
     Block(Block<Tx, Blob>),
 }
+
+impl<Tx, Blob> WireMessage for NetworkMessage<Tx, Blob>
+where
+    Tx: Clone + Eq + Hash + Serialize + DeserializeOwned,
+    Blob: Clone + Eq + Hash + Serialize + DeserializeOwned,
+{
+    const MESSAGE_TYPE: u8 = message_type::CRYPTARCHIA_GOSSIP;
+    const MAX_SIZE: u64 = MAX_BLOCK_SIZE + 1024;
+}
+
+/// Requests served by the block sync protocol.
+/// Responses are a sequence of blocks, ordered from the oldest to the newest.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncRequest {
+    /// Request the block `id` and up to `limit - 1` of its ancestors.
+    Ancestors { id: HeaderId, limit: u64 },
+    /// Request up to `limit` blocks of the peer local chain following `from`.
+    Chain { from: HeaderId, limit: u64 },
+}
+
+impl SyncRequest {
+    /// Maximum number of blocks in the response
+    pub fn limit(&self) -> u64 {
+        match self {
+            Self::Ancestors { limit, .. } | Self::Chain { limit, .. } => *limit,
+        }
+    }
+}
+
+impl WireMessage for SyncRequest {
+    const MESSAGE_TYPE: u8 = message_type::CRYPTARCHIA_SYNC_REQUEST;
+    const MAX_SIZE: u64 = 1024;
+}
@@ -2,17 +2,19 @@ pub mod adapters;
 pub mod messages;
 
 // std
+use std::fmt::Debug;
 use std::hash::Hash;
 // crates
 use futures::Stream;
 use nomos_core::block::Block;
 // internal
-use crate::network::messages::NetworkMessage;
+use crate::network::messages::{NetworkMessage, SyncRequest};
 use nomos_network::backends::NetworkBackend;
 use nomos_network::NetworkService;
 use overwatch_rs::services::relay::OutboundRelay;
 use overwatch_rs::services::ServiceData;
 use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::oneshot;
 
 type BoxedStream<T> = Box<dyn Stream<Item = T> + Send + Sync + Unpin>;
 
@@ -21,9 +23,29 @@ pub trait NetworkAdapter {
     type Backend: NetworkBackend + 'static;
     type Tx: Serialize + DeserializeOwned + Clone + Eq + Hash + 'static;
     type BlobCertificate: Serialize + DeserializeOwned + Clone + Eq + Hash + 'static;
+    type PeerId: Clone + Eq + Hash + Debug + Send + Sync + 'static;
     async fn new(
         network_relay: OutboundRelay<<NetworkService<Self::Backend> as ServiceData>::Message>,
     ) -> Self;
-    async fn blocks_stream(&self) -> BoxedStream<Block<Self::Tx, Self::BlobCertificate>>;
+    /// Blocks broadcast by other peers, together with the peer which published them if it also
+    /// relayed them to us. Peers forwarding blocks they did not publish are not reported, as
+    /// they might not have validated them.
+    async fn blocks_stream(
+        &self,
+    ) -> BoxedStream<(Block<Self::Tx, Self::BlobCertificate>, Option<Self::PeerId>)>;
     async fn broadcast(&self, message: NetworkMessage<Self::Tx, Self::BlobCertificate>);
+    /// Incoming sync requests from other peers, together with a channel to send back the response
+    async fn sync_requests_stream(
+        &self,
+    ) -> BoxedStream<(
+        SyncRequest,
+        oneshot::Sender<Vec<Block<Self::Tx, Self::BlobCertificate>>>,
+    )>;
+    /// Request blocks from connected peers. An empty response means no peer could serve the request.
+    async fn request_blocks(
+        &self,
+        request: SyncRequest,
+    ) -> Vec<Block<Self::Tx, Self::BlobCertificate>>;
+    /// Temporarily ignore all gossip from a misbehaving peer, not only blocks
+    async fn penalize_peer(&self, peer: Self::PeerId);
 }
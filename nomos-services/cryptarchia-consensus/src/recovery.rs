@@ -0,0 +1,53 @@
+use cryptarchia_engine::Branch;
+use cryptarchia_ledger::LedgerState;
+use nomos_core::header::HeaderId;
+use nomos_storage::{backends::StorageBackend, StorageMsg};
+use overwatch_rs::services::relay::OutboundRelay;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+// Blocks are stored using their header id as key, so these can't clash with them
+pub(crate) const SNAPSHOT_KEY: &str = "cryptarchia/snapshot";
+pub(crate) const TIP_KEY: &str = "cryptarchia/tip";
+pub(crate) const EVOLUTIONS_KEY: &str = "cryptarchia/evolutions";
+/// A new snapshot is persisted every `SNAPSHOT_INTERVAL` immutable blocks
+pub(crate) const SNAPSHOT_INTERVAL: u64 = 100;
+
+/// State of the last immutable block, used to restore consensus after a restart
+/// without replaying the whole chain.
+/// Since forks deeper than k are never accepted, the block tree and the ledger
+/// can be rebuilt by applying only the stored blocks between this block and the
+/// last known tip.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct Snapshot {
+    pub lib: Branch<HeaderId>,
+    pub lib_state: LedgerState,
+}
+
+pub(crate) async fn load<Storage, T>(
+    storage_relay: &OutboundRelay<StorageMsg<Storage>>,
+    key: impl Serialize,
+) -> Option<T>
+where
+    Storage: StorageBackend + Send + Sync + 'static,
+    T: DeserializeOwned,
+{
+    let (msg, receiver) = <StorageMsg<Storage>>::new_load_message(key);
+    if let Err((e, _)) = storage_relay.send(msg).await {
+        tracing::error!("Could not send load request to storage: {e}");
+        return None;
+    }
+    receiver.recv().await.ok().flatten()
+}
+
+pub(crate) async fn store<Storage>(
+    storage_relay: &OutboundRelay<StorageMsg<Storage>>,
+    key: impl Serialize,
+    value: impl Serialize,
+) where
+    Storage: StorageBackend + Send + Sync + 'static,
+{
+    let msg = <StorageMsg<Storage>>::new_store_message(key, value);
+    if let Err((e, _)) = storage_relay.send(msg).await {
+        tracing::error!("Could not send consensus state to storage: {e}");
+    }
+}
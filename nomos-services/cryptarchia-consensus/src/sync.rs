@@ -0,0 +1,258 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use cryptarchia_engine::Cryptarchia;
+use nomos_core::header::HeaderId;
+use rpds::Vector;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+    /// Download the chain from peers on startup, before starting to propose blocks
+    pub initial_sync: bool,
+    /// Maximum number of blocks to request from a peer in a single sync request
+    pub batch_size: u64,
+    /// Maximum number of blocks with an unknown parent to keep around while their ancestors
+    /// are being fetched
+    pub max_orphans: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            initial_sync: true,
+            batch_size: 64,
+            max_orphans: 1024,
+        }
+    }
+}
+
+/// Buffer of blocks received before their parent.
+///
+/// Blocks are indexed by their (missing) parent so that they can be applied
+/// as soon as the parent is. When the buffer is full, blocks waiting for the
+/// parent that was first requested are evicted first.
+pub struct Orphans<Block> {
+    by_parent: HashMap<HeaderId, Vec<(HeaderId, Block)>>,
+    ids: HashSet<HeaderId>,
+    // missing parents, in the order they were first requested
+    missing: VecDeque<HeaderId>,
+    capacity: usize,
+}
+
+impl<Block> Orphans<Block> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            by_parent: HashMap::new(),
+            ids: HashSet::new(),
+            missing: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Buffer a block whose parent is not known yet.
+    /// Returns `true` if the parent was not already being waited for, in which case
+    /// it should be requested from peers.
+    pub fn insert(&mut self, id: HeaderId, parent: HeaderId, block: Block) -> bool {
+        if !self.ids.insert(id) {
+            return false;
+        }
+        let newly_missing = !self.by_parent.contains_key(&parent);
+        if newly_missing {
+            self.missing.push_back(parent);
+        }
+        self.by_parent.entry(parent).or_default().push((id, block));
+
+        while self.ids.len() > self.capacity {
+            let Some(evicted) = self.missing.pop_front() else {
+                break;
+            };
+            for (id, _) in self.by_parent.remove(&evicted).unwrap_or_default() {
+                self.ids.remove(&id);
+            }
+        }
+        newly_missing && self.by_parent.contains_key(&parent)
+    }
+
+    /// Remove and return all the buffered blocks that were waiting for `parent`
+    pub fn take_children(&mut self, parent: &HeaderId) -> Vec<Block> {
+        let Some(children) = self.by_parent.remove(parent) else {
+            return vec![];
+        };
+        self.missing.retain(|missing| missing != parent);
+        children
+            .into_iter()
+            .map(|(id, block)| {
+                self.ids.remove(&id);
+                block
+            })
+            .collect()
+    }
+
+    pub fn contains(&self, id: &HeaderId) -> bool {
+        self.ids.contains(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+/// Ids of the blocks in the local chain, indexed by height.
+///
+/// Sync requests can then be served without walking the chain back from the tip.
+/// The index is persistent, so that it's cheap to clone along with the consensus state.
+#[derive(Clone, Debug)]
+pub struct LocalChain {
+    // height of the first block, which is the genesis or the block the engine was restored from
+    root_length: u64,
+    ids: Vector<HeaderId>,
+}
+
+impl LocalChain {
+    /// Index the chain of an engine which was just created, i.e. whose tip is the root of its tree
+    pub fn new(consensus: &Cryptarchia<HeaderId>) -> Self {
+        let root = consensus.tip();
+        Self {
+            root_length: consensus
+                .branches()
+                .get(&root)
+                .expect("the root is a known block")
+                .length(),
+            ids: Vector::new().push_back(root),
+        }
+    }
+
+    /// Follow the local chain of `consensus`, which descends from the same root.
+    /// Only the blocks which are not indexed yet are visited, walking back from the new tip.
+    #[must_use]
+    pub fn follow(&self, consensus: &Cryptarchia<HeaderId>) -> Self {
+        let branches = consensus.branches();
+        let mut new_blocks = Vec::new();
+        let mut current = branches
+            .get(&consensus.tip())
+            .expect("the tip is a known block");
+        let height = loop {
+            let height = (current.length() - self.root_length) as usize;
+            if self.ids.get(height) == Some(&current.id()) {
+                break height;
+            }
+            new_blocks.push(current.id());
+            current = branches
+                .get(&current.parent())
+                .expect("blocks descend from the root");
+        };
+
+        let mut ids = self.ids.clone();
+        // drop the blocks of the old chain after the fork
+        while ids.len() > height + 1 {
+            ids.drop_last_mut();
+        }
+        for id in new_blocks.into_iter().rev() {
+            ids.push_back_mut(id);
+        }
+        Self {
+            root_length: self.root_length,
+            ids,
+        }
+    }
+
+    /// Ids of up to `limit` blocks following `from` in the chain, from the oldest to the newest.
+    /// If `from` is not part of the chain, no blocks are returned.
+    pub fn after(
+        &self,
+        consensus: &Cryptarchia<HeaderId>,
+        from: HeaderId,
+        limit: u64,
+    ) -> Vec<HeaderId> {
+        // the engine might have been restored from a block after `from`
+        let Some(branch) = consensus.branches().get(&from) else {
+            return vec![];
+        };
+        let height = (branch.length() - self.root_length) as usize;
+        if self.ids.get(height) != Some(&from) {
+            return vec![];
+        }
+        (height + 1..)
+            .map_while(|height| self.ids.get(height).copied())
+            .take(limit as usize)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LocalChain, Orphans};
+    use cryptarchia_engine::{Config, Cryptarchia};
+    use nomos_core::header::HeaderId;
+
+    fn id(i: u8) -> HeaderId {
+        [i; 32].into()
+    }
+
+    #[test]
+    fn children_are_released_with_parent() {
+        let mut orphans = Orphans::new(10);
+        assert!(orphans.insert(id(2), id(1), 2));
+        // the parent is already being waited for
+        assert!(!orphans.insert(id(3), id(1), 3));
+        // duplicates are ignored
+        assert!(!orphans.insert(id(3), id(1), 3));
+        assert!(orphans.insert(id(5), id(4), 5));
+        assert_eq!(orphans.len(), 3);
+
+        let mut children = orphans.take_children(&id(1));
+        children.sort();
+        assert_eq!(children, vec![2, 3]);
+        assert!(!orphans.contains(&id(2)));
+        assert!(orphans.take_children(&id(1)).is_empty());
+        assert_eq!(orphans.take_children(&id(4)), vec![5]);
+        assert!(orphans.is_empty());
+    }
+
+    #[test]
+    fn oldest_orphans_are_evicted() {
+        let mut orphans = Orphans::new(2);
+        orphans.insert(id(2), id(1), 2);
+        orphans.insert(id(4), id(3), 4);
+        orphans.insert(id(6), id(5), 6);
+        assert_eq!(orphans.len(), 2);
+        assert!(!orphans.contains(&id(2)));
+        assert!(orphans.contains(&id(4)));
+        assert!(orphans.contains(&id(6)));
+    }
+
+    #[test]
+    fn local_chain_follows_reorgs() {
+        let config = Config {
+            security_param: 10,
+            active_slot_coeff: 1.0,
+        };
+        let mut engine = Cryptarchia::from_genesis(id(0), config);
+        let mut chain = LocalChain::new(&engine);
+        for (block, parent) in [(1, 0), (2, 1), (3, 2)] {
+            engine = engine
+                .receive_block(id(block), id(parent), (block as u64).into())
+                .unwrap();
+            chain = chain.follow(&engine);
+        }
+        assert_eq!(chain.after(&engine, id(0), 10), vec![id(1), id(2), id(3)]);
+        assert_eq!(chain.after(&engine, id(1), 1), vec![id(2)]);
+        assert!(chain.after(&engine, id(3), 10).is_empty());
+
+        // a longer fork from block 1 becomes the local chain
+        for (block, parent) in [(4, 1), (5, 4), (6, 5)] {
+            engine = engine
+                .receive_block(id(block), id(parent), (block as u64).into())
+                .unwrap();
+            chain = chain.follow(&engine);
+        }
+        assert_eq!(engine.tip(), id(6));
+        assert_eq!(chain.after(&engine, id(1), 10), vec![id(4), id(5), id(6)]);
+        // blocks of the abandoned fork are not part of the chain anymore
+        assert!(chain.after(&engine, id(2), 10).is_empty());
+    }
+}
@@ -9,21 +9,66 @@ pub struct Config {
     pub slot_duration: Duration,
     /// Start of the first epoch
     pub chain_start_time: OffsetDateTime,
+    /// How far ahead of the local clock a block can be to be accepted.
+    /// Blocks within this tolerance are held back until their slot starts,
+    /// blocks further in the future are rejected.
+    #[serde(default = "default_max_clock_drift")]
+    pub max_clock_drift: Duration,
+}
+
+const fn default_max_clock_drift() -> Duration {
+    Duration::from_secs(1)
+}
+
+/// Source of the current time, so that slots can be computed against a mock clock in tests
+pub trait Clock {
+    fn now(&self) -> OffsetDateTime;
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> OffsetDateTime {
+        OffsetDateTime::now_utc()
+    }
+}
+
+/// Timing of a slot relative to the local clock
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotTiming {
+    /// The slot has already started
+    Current,
+    /// The slot starts within the allowed clock drift
+    NearFuture,
+    /// The slot starts beyond the allowed clock drift
+    FarFuture,
 }
 
 #[derive(Clone, Debug)]
-pub struct Timer {
+pub struct Timer<C = SystemClock> {
     config: Config,
+    clock: C,
 }
 
 impl Timer {
     pub fn new(config: Config) -> Self {
-        Timer { config }
+        Self::with_clock(config, SystemClock)
+    }
+}
+
+impl<C: Clock> Timer<C> {
+    pub fn with_clock(config: Config, clock: C) -> Self {
+        Timer { config, clock }
     }
 
     pub fn current_slot(&self) -> Slot {
+        self.slot_at(self.clock.now())
+    }
+
+    fn slot_at(&self, time: OffsetDateTime) -> Slot {
         // TODO: leap seconds / weird time stuff
-        let since_start = OffsetDateTime::now_utc() - self.config.chain_start_time;
+        let since_start = time - self.config.chain_start_time;
         if since_start.is_negative() {
             tracing::warn!("Current slot is before the start of the chain");
             Slot::genesis()
@@ -32,12 +77,40 @@ impl Timer {
         }
     }
 
+    /// Start of `slot`, or `None` if it can't be represented.
+    /// Slots come from headers sent by peers, so any value must be handled.
+    pub fn slot_start(&self, slot: Slot) -> Option<OffsetDateTime> {
+        let slot = u64::from(slot);
+        let slot_duration = self.config.slot_duration;
+        let secs = slot_duration.as_secs().checked_mul(slot)?;
+        let nanos = u64::from(slot_duration.subsec_nanos()).checked_mul(slot)?;
+        let since_start = Duration::from_secs(secs).checked_add(Duration::from_nanos(nanos))?;
+        self.config
+            .chain_start_time
+            .checked_add(time::Duration::try_from(since_start).ok()?)
+    }
+
+    /// Check whether a block for `slot` can be processed now, considering the allowed clock drift
+    pub fn slot_timing(&self, slot: Slot) -> SlotTiming {
+        let now = self.clock.now();
+        if slot <= self.slot_at(now) {
+            SlotTiming::Current
+        } else {
+            match self.slot_start(slot) {
+                Some(start) if start <= now + self.config.max_clock_drift => SlotTiming::NearFuture,
+                // slots too far to be represented are in the future as well
+                _ => SlotTiming::FarFuture,
+            }
+        }
+    }
+
     /// Ticks at the start of each slot, starting from the next slot
     pub fn slot_interval(&self) -> Interval {
         let slot_duration = self.config.slot_duration;
-        let now = OffsetDateTime::now_utc();
-        let next_slot_start = self.config.chain_start_time
-            + slot_duration * u64::from(self.current_slot() + 1) as u32;
+        let now = self.clock.now();
+        let next_slot_start = self
+            .slot_start(self.slot_at(now) + 1)
+            .expect("the next slot starts at a representable time");
         let delay = next_slot_start - now;
         let mut interval = tokio::time::interval_at(
             tokio::time::Instant::now()
@@ -48,3 +121,86 @@ impl Timer {
         interval
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    struct MockClock(Cell<OffsetDateTime>);
+
+    impl MockClock {
+        fn advance(&self, duration: Duration) {
+            self.0.set(self.0.get() + duration);
+        }
+    }
+
+    impl Clock for &MockClock {
+        fn now(&self) -> OffsetDateTime {
+            self.0.get()
+        }
+    }
+
+    fn config() -> Config {
+        Config {
+            slot_duration: Duration::from_secs(2),
+            chain_start_time: OffsetDateTime::UNIX_EPOCH,
+            max_clock_drift: Duration::from_millis(500),
+        }
+    }
+
+    #[test]
+    fn slots_follow_the_clock() {
+        let clock = MockClock(Cell::new(
+            OffsetDateTime::UNIX_EPOCH - Duration::from_secs(1),
+        ));
+        let timer = Timer::with_clock(config(), &clock);
+        assert_eq!(timer.current_slot(), Slot::genesis());
+
+        clock.advance(Duration::from_secs(6));
+        assert_eq!(timer.current_slot(), Slot::from(2));
+        assert_eq!(
+            timer.slot_start(Slot::from(3)),
+            Some(OffsetDateTime::UNIX_EPOCH + Duration::from_secs(6))
+        );
+    }
+
+    #[test]
+    fn future_slots_within_drift_are_tolerated() {
+        let clock = MockClock(Cell::new(
+            OffsetDateTime::UNIX_EPOCH + Duration::from_millis(3400),
+        ));
+        let timer = Timer::with_clock(config(), &clock);
+        assert_eq!(timer.slot_timing(Slot::from(0)), SlotTiming::Current);
+        assert_eq!(timer.slot_timing(Slot::from(1)), SlotTiming::Current);
+        // slot 2 starts in 600ms
+        assert_eq!(timer.slot_timing(Slot::from(2)), SlotTiming::FarFuture);
+
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(timer.slot_timing(Slot::from(2)), SlotTiming::NearFuture);
+        assert_eq!(timer.slot_timing(Slot::from(3)), SlotTiming::FarFuture);
+
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(timer.slot_timing(Slot::from(2)), SlotTiming::Current);
+    }
+
+    #[test]
+    fn slots_beyond_u32_are_not_truncated() {
+        let clock = MockClock(Cell::new(OffsetDateTime::UNIX_EPOCH));
+        let timer = Timer::with_clock(config(), &clock);
+        // would start at slot 0 if truncated to 32 bits
+        let slot = Slot::from(u64::from(u32::MAX) + 1);
+        assert_eq!(
+            timer.slot_start(slot),
+            Some(OffsetDateTime::UNIX_EPOCH + Duration::from_secs(2 * (u64::from(u32::MAX) + 1)))
+        );
+        assert_eq!(timer.slot_timing(slot), SlotTiming::FarFuture);
+
+        // overflowing slots are far in the future as well
+        assert_eq!(timer.slot_start(Slot::from(u64::MAX)), None);
+        assert_eq!(
+            timer.slot_timing(Slot::from(u64::MAX)),
+            SlotTiming::FarFuture
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use cryptarchia_ledger::{Coin, Commitment, LedgerState};
+use serde::{Deserialize, Serialize};
+
+use crate::keystore::{self, Keystore};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Config {
+    /// Leader coins, stored in plain text in the node configuration
+    #[serde(default)]
+    pub coins: Vec<Coin>,
+    /// Encrypted keystore holding additional leader coins
+    #[serde(default)]
+    pub keystore: Option<KeystoreConfig>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeystoreConfig {
+    pub path: PathBuf,
+    pub password: String,
+}
+
+impl Config {
+    /// Coins from both the configuration and the keystore
+    pub fn load_coins(&self) -> Result<Vec<Coin>, keystore::Error> {
+        let mut coins = self.coins.clone();
+        if let Some(KeystoreConfig { path, password }) = &self.keystore {
+            coins.extend(Keystore::load(path)?.decrypt(password)?);
+        }
+        Ok(coins)
+    }
+}
+
+/// Leader coins owned by this node.
+///
+/// Coins are evolved every time they are used to propose a block, so that the
+/// same nullifier is never revealed twice. A coin only advances once the block
+/// revealing its nullifier is part of a chain: on any chain, the current version
+/// of a coin is the first evolution whose nullifier is not in the ledger state.
+///
+/// Coins are indexed by the commitment they are configured with. Their secret keys
+/// only come from the configuration, while the number of evolutions which are final,
+/// i.e. nullified in the state of the last immutable block, can be persisted so that
+/// coins don't have to be evolved from scratch after a restart.
+#[derive(Clone, Debug, Default)]
+pub struct Wallet {
+    coins: HashMap<Commitment, Coin>,
+    evolutions: Evolutions,
+}
+
+/// Number of final evolutions of each coin, indexed by its configured commitment
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Evolutions(HashMap<Commitment, u64>);
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct WalletInfo {
+    /// Total value of the owned coins that can be spent at the current tip
+    pub balance: u64,
+    /// Commitments of the owned coins that can be spent at the current tip
+    #[cfg_attr(feature = "openapi", schema(value_type = Vec<String>))]
+    pub commitments: Vec<Commitment>,
+}
+
+impl Wallet {
+    /// Load the configured coins, resuming from the persisted evolutions if there are any
+    pub fn restore(coins: impl IntoIterator<Item = Coin>, persisted: Option<Evolutions>) -> Self {
+        Self {
+            coins: coins
+                .into_iter()
+                .map(|coin| (coin.commitment(), coin))
+                .collect(),
+            evolutions: persisted.unwrap_or_default(),
+        }
+    }
+
+    pub fn evolutions(&self) -> &Evolutions {
+        &self.evolutions
+    }
+
+    /// Current version of each coin on the chain with the given ledger state
+    pub fn coins<'a>(&'a self, state: &'a LedgerState) -> impl Iterator<Item = Coin> + 'a {
+        self.coins.iter().map(|(id, coin)| {
+            let final_evolutions = self.evolutions.0.get(id).copied().unwrap_or_default();
+            let coin = (0..final_evolutions).fold(*coin, |coin, _| coin.evolve());
+            Self::skip_nullified(coin, state).0
+        })
+    }
+
+    /// Record the evolutions nullified in `lib_state`, the state of the last immutable block,
+    /// which all the chains we may extend share. Returns whether any coin advanced.
+    pub fn finalize(&mut self, lib_state: &LedgerState) -> bool {
+        let mut advanced = false;
+        for (id, coin) in &self.coins {
+            let final_evolutions = self.evolutions.0.entry(*id).or_default();
+            let coin = (0..*final_evolutions).fold(*coin, |coin, _| coin.evolve());
+            let (_, skipped) = Self::skip_nullified(coin, lib_state);
+            if skipped > 0 {
+                *final_evolutions += skipped;
+                advanced = true;
+            }
+        }
+        advanced
+    }
+
+    // First evolution of `coin` which is not nullified in `state`, and how many were skipped
+    fn skip_nullified(mut coin: Coin, state: &LedgerState) -> (Coin, u64) {
+        let mut skipped = 0;
+        while state.is_nullified(&coin.nullifier()) {
+            coin = coin.evolve();
+            skipped += 1;
+        }
+        (coin, skipped)
+    }
+
+    pub fn info(&self, state: &LedgerState) -> WalletInfo {
+        let (balance, commitments) = self
+            .coins(state)
+            .filter(|coin| {
+                state.can_spend(&coin.commitment()) && !state.is_nullified(&coin.nullifier())
+            })
+            .fold((0, Vec::new()), |(balance, mut commitments), coin| {
+                commitments.push(coin.commitment());
+                (balance + u64::from(u32::from(coin.value())), commitments)
+            });
+        WalletInfo {
+            balance,
+            commitments,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cryptarchia_ledger::Tx;
+
+    // the ledger records the same nullifier whether a coin is spent or used to lead,
+    // and the output keeps the coin commitment chain going
+    fn nullify(state: LedgerState, coin: &Coin) -> LedgerState {
+        let tx = Tx::new(&[*coin], vec![coin.evolve().note()]);
+        state.try_apply_tx::<()>(&tx).unwrap()
+    }
+
+    fn sorted_coins(wallet: &Wallet, state: &LedgerState) -> Vec<Coin> {
+        let mut coins = wallet.coins(state).collect::<Vec<_>>();
+        coins.sort_by_key(|coin| u32::from(coin.value()));
+        coins
+    }
+
+    #[test]
+    fn coins_follow_the_nullifiers_on_chain() {
+        let coins = [
+            Coin::new([1; 32], [0; 32].into(), 10.into()),
+            Coin::new([2; 32], [0; 32].into(), 20.into()),
+        ];
+        let wallet = Wallet::restore(coins, None);
+        let state = LedgerState::from_notes(coins.iter().map(Coin::note));
+        assert_eq!(sorted_coins(&wallet, &state), coins.to_vec());
+
+        // the first coin is only evolved on the chain which contains its proof
+        let proved = nullify(state.clone(), &coins[0]);
+        assert_eq!(
+            sorted_coins(&wallet, &proved),
+            vec![coins[0].evolve(), coins[1]]
+        );
+        assert_eq!(sorted_coins(&wallet, &state), coins.to_vec());
+    }
+
+    #[test]
+    fn final_evolutions_survive_restarts() {
+        let coins = [
+            Coin::new([1; 32], [0; 32].into(), 10.into()),
+            Coin::new([2; 32], [0; 32].into(), 20.into()),
+        ];
+        let mut wallet = Wallet::restore(coins, None);
+        let lib_state = LedgerState::from_notes(coins.iter().map(Coin::note));
+        let lib_state = nullify(nullify(lib_state, &coins[0]), &coins[0].evolve());
+        assert!(wallet.finalize(&lib_state));
+        assert!(!wallet.finalize(&lib_state));
+
+        // only the number of evolutions is persisted, the coins come from the configuration
+        let persisted = wallet.evolutions().clone();
+
+        let new_coin = Coin::new([3; 32], [0; 32].into(), 30.into());
+        let restored = Wallet::restore(coins.into_iter().chain([new_coin]), Some(persisted));
+        // the evolutions are known even without the nullifiers of the final state
+        assert_eq!(
+            sorted_coins(&restored, &LedgerState::from_notes([])),
+            vec![coins[0].evolve().evolve(), coins[1], new_coin]
+        );
+    }
+
+    #[test]
+    fn balance_only_includes_unspent_coins() {
+        let coins = [
+            Coin::new([1; 32], [0; 32].into(), 10.into()),
+            Coin::new([2; 32], [0; 32].into(), 20.into()),
+        ];
+        let wallet = Wallet::restore(coins, None);
+        let state = LedgerState::from_notes([coins[0].note()]);
+
+        assert_eq!(
+            wallet.info(&state),
+            WalletInfo {
+                balance: 10,
+                commitments: vec![coins[0].commitment()],
+            }
+        );
+    }
+}
@@ -43,15 +43,16 @@ where
         let receiver = receiver.await.unwrap();
         Box::new(Box::pin(BroadcastStream::new(receiver).filter_map(
             move |msg| match msg {
-                Ok(Event::Message(Message { topic, data, .. })) if topic == topic_hash => {
-                    match wire::deserialize::<E>(&data) {
-                        Ok(msg) => Some(msg),
-                        Err(e) => {
-                            debug!("Unrecognized message: {e}");
-                            None
-                        }
+                Ok(Event::Message {
+                    message: Message { topic, data, .. },
+                    ..
+                }) if topic == topic_hash => match wire::deserialize::<E>(&data) {
+                    Ok(msg) => Some(msg),
+                    Err(e) => {
+                        debug!("Unrecognized message: {e}");
+                        None
                     }
-                }
+                },
                 _ => None,
             },
         )))
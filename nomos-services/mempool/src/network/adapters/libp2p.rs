@@ -58,15 +58,16 @@ where
         let receiver = receiver.await.unwrap();
         Box::new(Box::pin(BroadcastStream::new(receiver).filter_map(
             move |message| match message {
-                Ok(Event::Message(Message { data, topic, .. })) if topic == topic_hash => {
-                    match wire::deserialize::<Item>(&data) {
-                        Ok(item) => Some((id(&item), item)),
-                        Err(e) => {
-                            tracing::debug!("Unrecognized message: {e}");
-                            None
-                        }
+                Ok(Event::Message {
+                    message: Message { data, topic, .. },
+                    ..
+                }) if topic == topic_hash => match wire::deserialize::<Item>(&data) {
+                    Ok(item) => Some((id(&item), item)),
+                    Err(e) => {
+                        tracing::debug!("Unrecognized message: {e}");
+                        None
                     }
-                }
+                },
                 _ => None,
             },
         )))
@@ -1,4 +1,6 @@
-use nomos_libp2p::{libp2p::StreamProtocol, Multiaddr, PeerId};
+use std::time::Duration;
+
+use nomos_libp2p::{libp2p::StreamProtocol, libp2p_stream::Control, Multiaddr, PeerId};
 use serde::{Deserialize, Serialize};
 use tokio::sync::oneshot;
 
@@ -26,6 +28,22 @@ pub enum Command {
         protocol: StreamProtocol,
         data: Box<[u8]>,
     },
+    /// Get a stream control to open and accept streams for custom request/response protocols
+    GetStreamControl {
+        reply: oneshot::Sender<StreamControl>,
+    },
+    ConnectedPeers {
+        reply: oneshot::Sender<Vec<PeerId>>,
+    },
+    /// Ignore gossipsub messages authored or relayed by a peer for `duration`
+    BlacklistPeer {
+        peer_id: PeerId,
+        duration: Duration,
+    },
+    /// Accept gossipsub messages from a peer again, unless it was blacklisted for longer since
+    RemoveBlacklistedPeer {
+        peer_id: PeerId,
+    },
 }
 
 #[derive(Debug)]
@@ -37,6 +55,16 @@ pub struct Dial {
 
 pub type Topic = String;
 
+/// Wraps a [`Control`] so that it can be sent through the command channel
+#[derive(Clone)]
+pub struct StreamControl(pub Control);
+
+impl std::fmt::Debug for StreamControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("StreamControl")
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Libp2pInfo {
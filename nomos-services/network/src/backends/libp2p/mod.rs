@@ -5,7 +5,7 @@ pub mod mixnet;
 pub(crate) mod swarm;
 
 // std
-pub use self::command::{Command, Dial, Libp2pInfo, Topic};
+pub use self::command::{Command, Dial, Libp2pInfo, StreamControl, Topic};
 pub use self::config::Libp2pConfig;
 use self::swarm::SwarmHandler;
 
@@ -16,6 +16,7 @@ use crate::backends::libp2p::mixnet::{init_mixnet, MixnetMessage, STREAM_PROTOCO
 #[cfg(feature = "mixnet")]
 use ::mixnet::client::MessageQueue;
 pub use nomos_libp2p::libp2p::gossipsub::{Message, TopicHash};
+use nomos_libp2p::PeerId;
 // crates
 use overwatch_rs::{overwatch::handle::OverwatchHandle, services::state::NoState};
 use tokio::sync::{broadcast, mpsc};
@@ -35,7 +36,12 @@ pub enum EventKind {
 /// Events emitted from [`NomosLibp2p`], which users can subscribe
 #[derive(Debug, Clone)]
 pub enum Event {
-    Message(Message),
+    Message {
+        message: Message,
+        /// Peer which forwarded the message, `None` for messages published by this node.
+        /// Unlike the message source, this is authenticated by the connection.
+        propagation_source: Option<PeerId>,
+    },
 }
 
 const BUFFER_SIZE: usize = 64;
@@ -1,6 +1,6 @@
 use std::{
     collections::{hash_map::Entry, HashMap},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use futures::AsyncWriteExt;
@@ -15,7 +15,7 @@ use nomos_libp2p::{
 use tokio::sync::{broadcast, mpsc, oneshot};
 use tokio_stream::StreamExt;
 
-use crate::backends::libp2p::Libp2pInfo;
+use crate::backends::libp2p::{Libp2pInfo, StreamControl};
 
 use super::{
     command::{Command, Dial, Topic},
@@ -26,6 +26,8 @@ pub struct SwarmHandler {
     pub swarm: Swarm,
     stream_control: Control,
     streams: HashMap<PeerId, Stream>,
+    // when each blacklisted peer should be accepted again
+    blacklist: HashMap<PeerId, Instant>,
     pub pending_dials: HashMap<ConnectionId, Dial>,
     pub commands_tx: mpsc::Sender<Command>,
     pub commands_rx: mpsc::Receiver<Command>,
@@ -62,6 +64,7 @@ impl SwarmHandler {
             swarm,
             stream_control,
             streams: HashMap::new(),
+            blacklist: HashMap::new(),
             pending_dials,
             commands_tx,
             commands_rx,
@@ -100,7 +103,10 @@ impl SwarmHandler {
                 message,
             })) => {
                 tracing::debug!("Got message with id: {id} from peer: {peer_id}");
-                log_error!(self.events_tx.send(Event::Message(message)));
+                log_error!(self.events_tx.send(Event::Message {
+                    message,
+                    propagation_source: Some(peer_id),
+                }));
             }
             SwarmEvent::ConnectionEstablished {
                 peer_id,
@@ -191,6 +197,48 @@ impl SwarmHandler {
                     }
                 }
             }
+            Command::GetStreamControl { reply } => {
+                log_error!(reply.send(StreamControl(self.stream_control.clone())));
+            }
+            Command::ConnectedPeers { reply } => {
+                let peers = self.swarm.swarm().connected_peers().cloned().collect();
+                log_error!(reply.send(peers));
+            }
+            Command::BlacklistPeer { peer_id, duration } => {
+                let expiry = Instant::now() + duration;
+                match self.blacklist.entry(peer_id) {
+                    // already blacklisted for longer
+                    Entry::Occupied(entry) if *entry.get() >= expiry => return,
+                    Entry::Occupied(mut entry) => {
+                        entry.insert(expiry);
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(expiry);
+                    }
+                }
+                tracing::debug!("blacklisting peer {peer_id} for {duration:?}");
+                self.swarm.blacklist_peer(&peer_id);
+                let commands_tx = self.commands_tx.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(duration).await;
+                    commands_tx
+                        .send(Command::RemoveBlacklistedPeer { peer_id })
+                        .await
+                        .unwrap_or_else(|_| tracing::error!("could not schedule unblacklisting"));
+                });
+            }
+            Command::RemoveBlacklistedPeer { peer_id } => {
+                match self.blacklist.entry(peer_id) {
+                    // the peer was blacklisted again in the meantime, a later command will remove it
+                    Entry::Occupied(entry) if *entry.get() > Instant::now() => return,
+                    Entry::Occupied(entry) => {
+                        entry.remove();
+                    }
+                    Entry::Vacant(_) => return,
+                }
+                tracing::debug!("removing peer {peer_id} from the blacklist");
+                self.swarm.remove_blacklisted_peer(&peer_id);
+            }
         }
     }
 
@@ -257,12 +305,15 @@ impl SwarmHandler {
                 tracing::debug!("broadcasted message with id: {id} tp topic: {topic}");
                 // self-notification because libp2p doesn't do it
                 if self.swarm.is_subscribed(&topic) {
-                    log_error!(self.events_tx.send(Event::Message(gossipsub::Message {
-                        source: None,
-                        data: message.into(),
-                        sequence_number: None,
-                        topic: Swarm::topic_hash(&topic),
-                    })));
+                    log_error!(self.events_tx.send(Event::Message {
+                        message: gossipsub::Message {
+                            source: None,
+                            data: message.into(),
+                            sequence_number: None,
+                            topic: Swarm::topic_hash(&topic),
+                        },
+                        propagation_source: None,
+                    }));
                 }
             }
             Err(gossipsub::PublishError::InsufficientPeers) if retry_count < MAX_RETRY => {
@@ -58,6 +58,10 @@ impl<SerdeOp: StorageSerde + Send + Sync + 'static> StorageBackend for MockStora
         Ok(self.inner.remove(key))
     }
 
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
     async fn execute(&mut self, transaction: Self::Transaction) -> Result<(), Self::Error> {
         transaction(&mut self.inner);
         Ok(())
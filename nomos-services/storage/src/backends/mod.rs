@@ -46,6 +46,8 @@ pub trait StorageBackend: Sized {
     async fn store(&mut self, key: Bytes, value: Bytes) -> Result<(), Self::Error>;
     async fn load(&mut self, key: &[u8]) -> Result<Option<Bytes>, Self::Error>;
     async fn remove(&mut self, key: &[u8]) -> Result<Option<Bytes>, Self::Error>;
+    /// Make all previous operations durable
+    async fn flush(&mut self) -> Result<(), Self::Error>;
     /// Execute a transaction in the current backend
     async fn execute(
         &mut self,
@@ -127,6 +127,11 @@ impl<SerdeOp: StorageSerde + Send + Sync + 'static> StorageBackend for RocksBack
         })
     }
 
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        // writes are durable once they reach the write ahead log
+        self.rocks.flush_wal(true)
+    }
+
     async fn execute(
         &mut self,
         transaction: Self::Transaction,
@@ -79,6 +79,11 @@ impl<SerdeOp: StorageSerde + Send + Sync + 'static> StorageBackend for SledBacke
         Ok(self.sled.remove(key)?.map(|ivec| ivec.to_vec().into()))
     }
 
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.sled.flush_async().await?;
+        Ok(())
+    }
+
     async fn execute(
         &mut self,
         transaction: Self::Transaction,
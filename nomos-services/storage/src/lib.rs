@@ -38,6 +38,10 @@ pub enum StorageMsg<Backend: StorageBackend> {
         reply_channel:
             tokio::sync::oneshot::Sender<<Backend::Transaction as StorageTransaction>::Result>,
     },
+    /// Reply once all the messages received before are durably stored
+    Flush {
+        reply_channel: tokio::sync::oneshot::Sender<()>,
+    },
 }
 
 /// Reply channel for storage messages
@@ -130,6 +134,14 @@ impl<Backend: StorageBackend> StorageMsg<Backend> {
             StorageReplyReceiver::new(receiver),
         )
     }
+
+    pub fn new_flush_message() -> (StorageMsg<Backend>, StorageReplyReceiver<(), Backend>) {
+        let (reply_channel, receiver) = tokio::sync::oneshot::channel();
+        (
+            Self::Flush { reply_channel },
+            StorageReplyReceiver::new(receiver),
+        )
+    }
 }
 
 // Implement `Debug` manually to avoid constraining `Backend` to `Debug`
@@ -146,6 +158,7 @@ impl<Backend: StorageBackend> Debug for StorageMsg<Backend> {
                 write!(f, "Remove {{ {key:?} }}")
             }
             StorageMsg::Execute { .. } => write!(f, "Execute transaction"),
+            StorageMsg::Flush { .. } => write!(f, "Flush"),
         }
     }
 }
@@ -197,6 +210,7 @@ impl<Backend: StorageBackend + Send + Sync + 'static> StorageService<Backend> {
                 transaction,
                 reply_channel,
             } => Self::handle_execute(backend, transaction, reply_channel).await,
+            StorageMsg::Flush { reply_channel } => Self::handle_flush(backend, reply_channel).await,
         } {
             // TODO: add proper logging
             println!("{e}");
@@ -250,6 +264,24 @@ impl<Backend: StorageBackend + Send + Sync + 'static> StorageService<Backend> {
             .map_err(StorageServiceError::BackendError)
     }
 
+    /// Handle flush message
+    async fn handle_flush(
+        backend: &mut Backend,
+        reply_channel: tokio::sync::oneshot::Sender<()>,
+    ) -> Result<(), StorageServiceError<Backend>> {
+        // messages are handled in order, so every previous store has reached the backend
+        backend
+            .flush()
+            .await
+            .map_err(StorageServiceError::BackendError)?;
+        reply_channel
+            .send(())
+            .map_err(|_| StorageServiceError::ReplyError {
+                operation: "Flush".to_string(),
+                key: Bytes::new(),
+            })
+    }
+
     /// Handle execute message
     async fn handle_execute(
         backend: &mut Backend,
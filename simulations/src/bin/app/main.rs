@@ -10,6 +10,7 @@ use carnot_engine::{Block, LeaderProof, View};
 use clap::Parser;
 use crossbeam::channel;
 use nomos_core::block::builder::BlockBuilder;
+use nomos_core::execution::State as AccountState;
 use parking_lot::Mutex;
 use rand::rngs::SmallRng;
 use rand::seq::SliceRandom;
@@ -137,6 +138,7 @@ impl SimulationApp {
                     View::new(0),
                     Block::genesis([0; 32].into()).parent_qc,
                     LeaderProof::LeaderId { leader_id: leader },
+                    AccountState::default().root(),
                 );
                 let mut rng = SmallRng::seed_from_u64(seed);
                 overlay_node::to_overlay_node(
@@ -84,6 +84,7 @@ pub fn to_overlay_node<R: Rng>(
                     node_id,
                     CarnotSettings::new(
                         settings.node_settings.timeout,
+                        settings.node_settings.backoff(),
                         settings.record_settings.clone(),
                         fmt,
                     ),
@@ -108,6 +109,7 @@ pub fn to_overlay_node<R: Rng>(
                     node_id,
                     CarnotSettings::new(
                         settings.node_settings.timeout,
+                        settings.node_settings.backoff(),
                         settings.record_settings.clone(),
                         fmt,
                     ),
@@ -131,6 +133,7 @@ pub fn to_overlay_node<R: Rng>(
                     node_id,
                     CarnotSettings::new(
                         settings.node_settings.timeout,
+                        settings.node_settings.backoff(),
                         settings.record_settings.clone(),
                         fmt,
                     ),
@@ -37,6 +37,11 @@ impl EventBuilder {
         }
     }
 
+    /// Timeout for the views the node did not start yet
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout_handler.timeout = timeout;
+    }
+
     /// At some point we need to clear old votes or RAM just goes brrrrrr
     pub fn prune_by_view(&mut self, view: View) {
         self.vote_message.prune(view);
@@ -84,6 +89,7 @@ impl EventBuilder {
                 qc: Qc::Standard(StandardQc {
                     view: genesis.view,
                     id: genesis.id,
+                    signature: Default::default(),
                 }),
                 block: genesis,
                 votes: HashSet::new(),
@@ -93,7 +99,13 @@ impl EventBuilder {
         for message in messages {
             match message {
                 CarnotMessage::Proposal(msg) => {
-                    let block = Block::from_bytes(&msg.data);
+                    let block = match Block::from_bytes(&msg.data) {
+                        Ok(block) => block,
+                        Err(e) => {
+                            tracing::error!(node=%self.id, "malformed proposal: {e}");
+                            continue;
+                        }
+                    };
                     tracing::info!(
                         node=%self.id,
                         current_view = %engine.current_view(),
@@ -121,8 +133,12 @@ impl EventBuilder {
                     let is_next_view_leader = engine.is_next_leader();
                     let is_message_from_root_committee =
                         engine.overlay().is_member_of_root_committee(voter);
+                    // children of the root committee send their votes to the next leader too
+                    let is_message_for_leader = is_message_from_root_committee
+                        || (is_next_view_leader
+                            && engine.overlay().is_child_of_root_committee(voter));
 
-                    let tally = if is_message_from_root_committee {
+                    let tally = if is_message_for_leader {
                         &mut self.leader_vote_message
                     } else {
                         &mut self.vote_message
@@ -133,8 +149,8 @@ impl EventBuilder {
                         continue;
                     };
 
-                    // if the message comes from the root committee, then use the leader threshold, otherwise use the leaf threshold
-                    let threshold = if is_message_from_root_committee {
+                    // if the message is for the leader, then use the leader threshold, otherwise use the leaf threshold
+                    let threshold = if is_message_for_leader {
                         engine.leader_super_majority_threshold()
                     } else {
                         engine.super_majority_threshold()
@@ -156,11 +172,12 @@ impl EventBuilder {
                                 "approve block",
                             );
 
-                            if is_next_view_leader && is_message_from_root_committee {
+                            if is_next_view_leader && is_message_for_leader {
                                 events.push(Event::ProposeBlock {
                                     qc: Qc::Standard(StandardQc {
                                         view: block.view,
                                         id: block.id,
+                                        signature: Default::default(),
                                     }),
                                 });
                             } else {
@@ -192,27 +209,32 @@ impl EventBuilder {
                     let is_next_view_leader = engine.is_next_leader();
                     let is_message_from_root_committee =
                         engine.overlay().is_member_of_root_committee(voter);
+                    // children of the root committee send their votes to the next leader too
+                    let is_message_for_leader = is_message_from_root_committee
+                        || (is_next_view_leader
+                            && engine.overlay().is_child_of_root_committee(voter));
 
-                    let tally = if is_message_from_root_committee {
+                    let tally = if is_message_for_leader {
                         &mut self.leader_new_view_message
                     } else {
                         &mut self.new_view_message
                     };
 
-                    // if the message comes from the root committee, then use the leader threshold, otherwise use the leaf threshold
-                    let threshold = if is_message_from_root_committee {
+                    // if the message is for the leader, then use the leader threshold, otherwise use the leaf threshold
+                    let threshold = if is_message_for_leader {
                         engine.leader_super_majority_threshold()
                     } else {
                         engine.super_majority_threshold()
                     };
 
                     if let Some(votes) = tally.tally_by(msg_view, msg, threshold) {
-                        if is_next_view_leader && is_message_from_root_committee {
+                        if is_next_view_leader && is_message_for_leader {
                             let high_qc = engine.high_qc();
                             events.push(Event::ProposeBlock {
                                 qc: Qc::Aggregated(AggregateQc {
                                     high_qc,
                                     view: msg_view.next(),
+                                    signature: Default::default(),
                                 }),
                             });
                         } else {
@@ -5,7 +5,9 @@ mod message_cache;
 pub mod messages;
 mod state;
 use nomos_core::block::builder::BlockBuilder;
+use nomos_core::execution::State as AccountState;
 use nomos_core::header::HeaderId;
+use nomos_core::staking::StakingTransaction;
 pub use state::*;
 mod serde_util;
 mod tally;
@@ -33,16 +35,18 @@ use crate::streaming::SubscriberFormat;
 use crate::warding::SimulationState;
 use carnot_consensus::committee_membership::UpdateableCommitteeMembership;
 use carnot_consensus::network::messages::{ProposalMsg, TimeoutQcMsg};
+use carnot_consensus::pacemaker::{BackoffSettings, Pacemaker};
 use carnot_consensus::{
     leader_selection::UpdateableLeaderSelection,
     network::messages::{NewViewMsg, TimeoutMsg, VoteMsg},
 };
 use carnot_engine::overlay::RandomBeaconState;
-use carnot_engine::{Committee, LeaderProof, Overlay, View};
+use carnot_engine::{Committee, LeaderProof, Overlay, QcSignature, View};
 
 type Block = carnot_engine::Block<HeaderId>;
 type AggregateQc = carnot_engine::AggregateQc<HeaderId>;
 type Carnot<O> = carnot_engine::Carnot<O, HeaderId>;
+type Error = carnot_engine::Error<HeaderId>;
 type Payload = carnot_engine::Payload<HeaderId>;
 type TimeoutQc = carnot_engine::TimeoutQc<HeaderId>;
 type Vote = carnot_engine::Vote<HeaderId>;
@@ -56,6 +60,8 @@ static RECORD_SETTINGS: std::sync::OnceLock<BTreeMap<String, bool>> = std::sync:
 #[derive(Clone, Default, Deserialize)]
 pub struct CarnotSettings {
     timeout: Duration,
+    #[serde(default)]
+    backoff: BackoffSettings,
     record_settings: BTreeMap<String, bool>,
 
     #[serde(default)]
@@ -65,11 +71,13 @@ pub struct CarnotSettings {
 impl CarnotSettings {
     pub fn new(
         timeout: Duration,
+        backoff: BackoffSettings,
         record_settings: BTreeMap<String, bool>,
         format: SubscriberFormat,
     ) -> Self {
         Self {
             timeout,
+            backoff,
             record_settings,
             format,
         }
@@ -86,6 +94,7 @@ pub struct CarnotNode<O: Overlay> {
     network_interface: InMemoryNetworkInterface<CarnotMessage>,
     message_cache: MessageCache,
     event_builder: event_builder::EventBuilder,
+    pacemaker: Pacemaker,
     engine: Carnot<O>,
     random_beacon_pk: PrivateKey,
     step_duration: Duration,
@@ -109,6 +118,7 @@ impl<
         let engine = Carnot::from_genesis(id, genesis.header().carnot().to_carnot_block(), overlay);
         let state = CarnotState::from(&engine);
         let timeout = settings.timeout;
+        let pacemaker = Pacemaker::new(timeout, settings.backoff);
         RECORD_SETTINGS.get_or_init(|| settings.record_settings.clone());
         // pk is generated in an insecure way, but for simulation purpouses using a rng like smallrng is more useful
         let mut pk_buff = [0; 32];
@@ -121,6 +131,7 @@ impl<
             network_interface,
             message_cache: MessageCache::new(),
             event_builder: event_builder::EventBuilder::new(id, timeout),
+            pacemaker,
             engine,
             random_beacon_pk,
             step_duration: Duration::ZERO,
@@ -146,7 +157,10 @@ impl<
                             qc: Some(Qc::Standard(StandardQc {
                                 view: vote.view,
                                 id: vote.block,
+                                signature: Default::default(),
                             })),
+                            // signatures are not checked in simulations
+                            signature: Default::default(),
                         }),
                     );
                 }
@@ -161,6 +175,7 @@ impl<
                         CarnotMessage::NewView(NewViewMsg {
                             voter: node,
                             vote: new_view.clone(),
+                            signature: Default::default(),
                         }),
                     );
                 }
@@ -175,6 +190,7 @@ impl<
                         CarnotMessage::Timeout(TimeoutMsg {
                             voter: node,
                             vote: timeout.clone(),
+                            signature: Default::default(),
                         }),
                     );
                 }
@@ -192,6 +208,9 @@ impl<
                         data: proposal.as_bytes().to_vec().into(),
                         proposal: proposal.header().id(),
                         view: proposal.header().carnot().view(),
+                        leader: self.id,
+                        // simulated nodes do not sign their messages
+                        signature: None,
                     }))
             }
         }
@@ -221,11 +240,13 @@ impl<
                             self.engine = new;
                         }
                     }
-                    Err(_) => {
+                    Err(Error::DuplicateBlock(_)) => {}
+                    Err(e) => {
                         tracing::error!(
                             node = %self.id,
                             current_view = %self.engine.current_view(),
                             block_view = %block.header().carnot().view(), block = %block.header().id(),
+                            error = %e,
                             "receive block proposal, but is invalid",
                         );
                     }
@@ -240,13 +261,13 @@ impl<
                             "Parent committee of non root committee members should be present",
                         )
                     };
-                    output = Some(Output::Send(carnot_engine::Send {
+                    output = Some(Output::Send(self.with_next_leader(carnot_engine::Send {
                         to,
                         payload: Payload::Vote(Vote {
                             view: self.engine.current_view(),
                             block: block.header().id(),
                         }),
-                    }))
+                    })))
                 }
             }
             // This branch means we already get enough votes for this block
@@ -270,12 +291,18 @@ impl<
                             .view
                     }
                 } - View::new(3);
-                let (mut new, out) = self.engine.approve_block(block);
-                tracing::info!(vote=?out, node=%self.id);
-                // pruning old blocks older than the grandparent block needed to check validity
-                new.prune_older_blocks_by_view(block_grandparent_view);
-                output = Some(Output::Send(out));
-                self.engine = new;
+                match self.engine.approve_block(block) {
+                    Ok((mut new, out)) => {
+                        tracing::info!(vote=?out, node=%self.id);
+                        // pruning old blocks older than the grandparent block needed to check validity
+                        new.prune_older_blocks_by_view(block_grandparent_view);
+                        output = Some(Output::Send(self.with_next_leader(out)));
+                        self.engine = new;
+                    }
+                    Err(e) => {
+                        tracing::error!(node = %self.id, error = %e, "could not approve block");
+                    }
+                }
             }
             Event::ProposeBlock { qc } => {
                 output = Some(Output::BroadcastProposal {
@@ -286,6 +313,8 @@ impl<
                         LeaderProof::LeaderId {
                             leader_id: [0; 32].into(),
                         },
+                        // simulated blocks carry no transfers
+                        AccountState::default().root(),
                     ),
                 });
             }
@@ -303,9 +332,15 @@ impl<
                 );
                 // just process timeout if node have not already process it
                 if timeout_qc.view() == self.engine.current_view() {
-                    let (new, out) = self.engine.approve_new_view(timeout_qc, new_views);
-                    output = Some(Output::Send(out));
-                    self.engine = new;
+                    match self.engine.approve_new_view(timeout_qc, new_views) {
+                        Ok((new, out)) => {
+                            output = Some(Output::Send(self.with_next_leader(out)));
+                            self.engine = new;
+                        }
+                        Err(e) => {
+                            tracing::error!(node = %self.id, error = %e, "could not approve new view");
+                        }
+                    }
                 }
             }
             Event::TimeoutQc { timeout_qc } => {
@@ -315,8 +350,14 @@ impl<
                     timeout_view = %timeout_qc.view(),
                     "receive timeout qc message"
                 );
-                let new = self.engine.receive_timeout_qc(timeout_qc.clone());
-                self.engine = Self::update_overlay_with_timeout_qc(new, &timeout_qc);
+                match self.engine.receive_timeout_qc(timeout_qc.clone()) {
+                    Ok(new) => {
+                        self.engine = Self::update_overlay_with_timeout_qc(new, &timeout_qc);
+                    }
+                    Err(e) => {
+                        tracing::debug!(node = %self.id, error = %e, "ignoring timeout qc");
+                    }
+                }
             }
             Event::RootTimeout { timeouts } => {
                 tracing::debug!("root timeout {:?}", timeouts);
@@ -331,8 +372,12 @@ impl<
                         .max_by_key(|qc| qc.view)
                         .expect("empty root committee")
                         .clone();
-                    let timeout_qc =
-                        TimeoutQc::new(timeouts.iter().next().unwrap().view, high_qc, self.id);
+                    let timeout_qc = TimeoutQc::new(
+                        timeouts.iter().next().unwrap().view,
+                        high_qc,
+                        self.id,
+                        QcSignature::default(),
+                    );
                     output = Some(Output::BroadcastTimeoutQc { timeout_qc });
                 }
             }
@@ -356,7 +401,18 @@ impl<
         }
     }
 
-    fn update_overlay_with_block<Tx: Clone + Eq + Hash>(
+    /// Children of the root committee send their votes and new views to the next leader too
+    fn with_next_leader(
+        &self,
+        mut send: carnot_engine::Send<HeaderId>,
+    ) -> carnot_engine::Send<HeaderId> {
+        if self.engine.is_child_of_root_committee() {
+            send.to.insert(self.engine.overlay().next_leader());
+        }
+        send
+    }
+
+    fn update_overlay_with_block<Tx: Clone + Eq + Hash + StakingTransaction>(
         state: Carnot<O>,
         block: &nomos_core::block::Block<Tx, CarnotBlob>,
     ) -> Carnot<O> {
@@ -436,9 +492,14 @@ impl<
             .event_builder
             .step(current_view_messages, &self.engine, elapsed);
 
+        let prev_view = self.engine.current_view();
         for event in events {
             self.process_event(event);
         }
+        if self.engine.current_view() != prev_view {
+            self.pacemaker.on_view_change(&self.engine, prev_view);
+            self.event_builder.set_timeout(self.pacemaker.timeout());
+        }
 
         // update state
         self.state = CarnotState::new(
@@ -41,11 +41,40 @@ pub struct BranchSettings {
     pub branch_depth: usize,
 }
 
+/// Growth of the view timeout while views keep timing out
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BackoffSettings {
+    pub multiplier: f64,
+    #[serde(with = "humantime_serde")]
+    pub max_timeout: std::time::Duration,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct NodeSettings {
     pub network_capacity_kbps: Option<u32>,
     #[serde(with = "humantime_serde")]
     pub timeout: std::time::Duration,
+    /// Nodes keep the same timeout for every view if not set
+    #[serde(default)]
+    pub backoff: Option<BackoffSettings>,
+}
+
+impl NodeSettings {
+    pub fn backoff(&self) -> carnot_consensus::pacemaker::BackoffSettings {
+        match &self.backoff {
+            Some(BackoffSettings {
+                multiplier,
+                max_timeout,
+            }) => carnot_consensus::pacemaker::BackoffSettings {
+                multiplier: *multiplier,
+                max_timeout: *max_timeout,
+            },
+            None => carnot_consensus::pacemaker::BackoffSettings {
+                multiplier: 1.0,
+                max_timeout: self.timeout,
+            },
+        }
+    }
 }
 
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
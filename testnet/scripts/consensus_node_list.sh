@@ -4,8 +4,8 @@ set -e
 
 # This node id will be used to generate consensus node list.
 tmp_node_id=0
-# OVERLAY_NODES might be set in compose.yml.
-tmp_overlay_nodes=$OVERLAY_NODES
+# OVERLAY_NODE_KEYS might be set in compose.yml.
+tmp_overlay_nodes=$OVERLAY_NODE_KEYS
 
 # All spawned nodes should be added to consensus configuration.
 for i in $(seq 1 $LIBP2P_REPLICAS); do
@@ -5,16 +5,16 @@ set -e
 CONSENSUS_PRIV_KEY=$BOOTSTRAP_NODE_KEY
 DA_VOTER=$BOOTSTRAP_NODE_KEY
 NET_NODE_KEY=$BOOTSTRAP_NODE_KEY
-OVERLAY_NODES=$(/etc/nomos/scripts/consensus_node_list.sh)
+OVERLAY_NODE_KEYS=$(/etc/nomos/scripts/consensus_node_list.sh)
 
 export CONSENSUS_PRIV_KEY \
        DA_VOTER \
-       OVERLAY_NODES \
+       OVERLAY_NODE_KEYS \
        NET_NODE_KEY
 
 echo "I am a container ${HOSTNAME} node ${NET_NODE_KEY}"
 echo "CONSENSUS_PRIV_KEY: ${CONSENSUS_PRIV_KEY}"
 echo "DA_VOTER: ${DA_VOTER}"
-echo "OVERLAY_NODES: ${OVERLAY_NODES}"
+echo "OVERLAY_NODE_KEYS: ${OVERLAY_NODE_KEYS}"
 
 exec /usr/bin/nomos-node /etc/nomos/bootstrap_config.yaml --with-metrics
@@ -6,7 +6,7 @@ set -e
 NET_NODE_KEY=$(/etc/nomos/scripts/register_node.sh)
 CONSENSUS_PRIV_KEY=$NET_NODE_KEY
 DA_VOTER=$CONSENSUS_PRIV_KEY
-OVERLAY_NODES=$(/etc/nomos/scripts/consensus_node_list.sh)
+OVERLAY_NODE_KEYS=$(/etc/nomos/scripts/consensus_node_list.sh)
 
 node_ids=$(etcdctl get "/node/" --prefix --keys-only)
 for node_id in $node_ids; do
@@ -23,14 +23,14 @@ done
 
 export CONSENSUS_PRIV_KEY \
        DA_VOTER \
-       OVERLAY_NODES \
+       OVERLAY_NODE_KEYS \
        NET_NODE_KEY \
        NET_INITIAL_PEERS
 
 echo "I am a container ${HOSTNAME} node ${NET_NODE_KEY}"
 echo "CONSENSUS_PRIV_KEY: ${CONSENSUS_PRIV_KEY}"
 echo "DA_VOTER: ${DA_VOTER}"
-echo "OVERLAY_NODES: ${OVERLAY_NODES}"
+echo "OVERLAY_NODE_KEYS: ${OVERLAY_NODE_KEYS}"
 echo "NET_INITIAL_PEERS: ${NET_INITIAL_PEERS}"
 
 exec /usr/bin/nomos-node /etc/nomos/libp2p_config.yaml --with-metrics
@@ -0,0 +1,303 @@
+// std
+use std::net::SocketAddr;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+// internal
+#[cfg(feature = "mixnet")]
+use super::{build_mixnet_topology, create_mixnet_config, NUM_MIXNODE_CANDIDATES};
+use super::{create_tempdir, persist_tempdir, LOGS_PREFIX};
+use crate::{adjust_timeout, get_available_port};
+use cryptarchia_consensus::{
+    wallet::{self, WalletInfo},
+    CryptarchiaInfo, CryptarchiaSettings,
+};
+use cryptarchia_ledger::{Coin, LedgerState};
+use full_replication::Certificate;
+use nomos_core::{block::Block, header::HeaderId};
+use nomos_libp2p::{Multiaddr, Swarm};
+use nomos_log::{LoggerBackend, LoggerFormat};
+#[cfg(feature = "mixnet")]
+use nomos_network::backends::libp2p::mixnet::MixnetConfig;
+use nomos_network::{backends::libp2p::Libp2pConfig, NetworkConfig};
+use nomos_node::{api::AxumBackendSettings, CryptarchiaConfig, CryptarchiaTx};
+// crates
+use once_cell::sync::Lazy;
+use rand::{thread_rng, Rng};
+use reqwest::Client;
+use tempfile::NamedTempFile;
+use time::OffsetDateTime;
+
+static CLIENT: Lazy<Client> = Lazy::new(Client::new);
+const NOMOS_BIN: &str = "../target/debug/nomos-node";
+const CRYPTARCHIA_INFO_API: &str = "cryptarchia/info";
+const CRYPTARCHIA_HEADERS_API: &str = "cryptarchia/headers";
+const CRYPTARCHIA_WALLET_API: &str = "cryptarchia/wallet";
+const STORAGE_BLOCKS_API: &str = "storage/block";
+
+/// A nomos-node process running Cryptarchia
+pub struct CryptarchiaNode {
+    addr: SocketAddr,
+    _tempdir: tempfile::TempDir,
+    child: Child,
+    config: CryptarchiaConfig,
+}
+
+impl Drop for CryptarchiaNode {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            if let Err(e) = persist_tempdir(&mut self._tempdir, "nomos-node") {
+                println!("failed to persist tempdir: {e}");
+            }
+        }
+
+        if let Err(e) = self.child.kill() {
+            println!("failed to kill the child process: {e}");
+        }
+    }
+}
+
+impl CryptarchiaNode {
+    pub async fn spawn(mut config: CryptarchiaConfig) -> Self {
+        let dir = create_tempdir().unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        let config_path = file.path().to_owned();
+
+        // setup logging so that we can intercept it later in testing
+        config.log.backend = LoggerBackend::File {
+            directory: dir.path().to_owned(),
+            prefix: Some(LOGS_PREFIX.into()),
+        };
+        config.log.format = LoggerFormat::Json;
+
+        serde_yaml::to_writer(&mut file, &config).unwrap();
+        let child = Command::new(std::env::current_dir().unwrap().join(NOMOS_BIN))
+            .arg(&config_path)
+            .args(["--consensus", "cryptarchia"])
+            .current_dir(dir.path())
+            .stdout(Stdio::inherit())
+            .spawn()
+            .unwrap();
+        let node = Self {
+            addr: config.http.backend_settings.address,
+            child,
+            _tempdir: dir,
+            config,
+        };
+        tokio::time::timeout(adjust_timeout(Duration::from_secs(10)), async {
+            node.wait_online().await
+        })
+        .await
+        .unwrap();
+
+        node
+    }
+
+    /// Spawn `n_participants` nodes sequentially, each one connected to the previous one.
+    /// Every node owns a single coin and all coins have the same stake.
+    pub async fn spawn_nodes(n_participants: usize) -> Vec<Self> {
+        let mut nodes = Vec::new();
+        for conf in Self::node_configs(n_participants) {
+            nodes.push(Self::spawn(conf).await);
+        }
+        nodes
+    }
+
+    pub fn node_configs(n_participants: usize) -> Vec<CryptarchiaConfig> {
+        let mut ids = vec![[0; 32]; n_participants];
+        let mut coins = Vec::with_capacity(n_participants);
+        for id in &mut ids {
+            thread_rng().fill(id);
+            let mut sk = [0; 32];
+            thread_rng().fill(&mut sk);
+            coins.push(Coin::new(sk, [0; 32].into(), 1.into()));
+        }
+        let genesis_state = LedgerState::from_notes(coins.iter().map(Coin::note));
+        // leave some time to spawn all the nodes before the first slot
+        let chain_start_time =
+            OffsetDateTime::now_utc() + Duration::from_secs(n_participants as u64 * 2);
+
+        #[cfg(feature = "mixnet")]
+        let (mixclient_config, mixnode_configs) = create_mixnet_config(&ids);
+
+        let mut configs = ids
+            .iter()
+            .zip(coins)
+            .enumerate()
+            .map(|(_i, (id, coin))| {
+                create_node_config(
+                    *id,
+                    coin,
+                    genesis_state.clone(),
+                    chain_start_time,
+                    #[cfg(feature = "mixnet")]
+                    MixnetConfig {
+                        mixclient: mixclient_config.clone(),
+                        mixnode: mixnode_configs[_i].clone(),
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+
+        #[cfg(feature = "mixnet")]
+        {
+            let topology = build_mixnet_topology(
+                &configs
+                    .iter()
+                    .take(NUM_MIXNODE_CANDIDATES)
+                    .map(|config| &config.network.backend)
+                    .collect::<Vec<_>>(),
+            );
+            configs.iter_mut().for_each(|config| {
+                config.network.backend.mixnet.mixclient.topology = topology.clone();
+            });
+        }
+
+        // chain topology: every node is connected to the previous one
+        for i in 1..configs.len() {
+            let prev_node_addr = node_address(&configs[i - 1]);
+            configs[i]
+                .network
+                .backend
+                .initial_peers
+                .push(prev_node_addr);
+        }
+        configs
+    }
+
+    async fn get(&self, path: &str) -> reqwest::Result<reqwest::Response> {
+        CLIENT
+            .get(format!("http://{}/{}", self.addr, path))
+            .send()
+            .await
+    }
+
+    async fn wait_online(&self) {
+        loop {
+            let res = self.get(CRYPTARCHIA_INFO_API).await;
+            if res.is_ok() && res.unwrap().status().is_success() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    pub async fn consensus_info(&self) -> CryptarchiaInfo {
+        let res = self.get(CRYPTARCHIA_INFO_API).await;
+        res.unwrap().json().await.unwrap()
+    }
+
+    /// Ids of the blocks in the local chain, from `from` (default to the tip)
+    /// back to `to` (default to genesis)
+    pub async fn get_headers(&self, from: Option<HeaderId>, to: Option<HeaderId>) -> Vec<HeaderId> {
+        let mut req = CLIENT.get(format!("http://{}/{}", self.addr, CRYPTARCHIA_HEADERS_API));
+
+        if let Some(from) = from {
+            req = req.query(&[("from", from)]);
+        }
+
+        if let Some(to) = to {
+            req = req.query(&[("to", to)]);
+        }
+
+        req.send().await.unwrap().json().await.unwrap()
+    }
+
+    pub async fn get_wallet(&self) -> WalletInfo {
+        let res = self.get(CRYPTARCHIA_WALLET_API).await;
+        res.unwrap().json().await.unwrap()
+    }
+
+    pub async fn get_block(&self, id: HeaderId) -> Option<Block<CryptarchiaTx, Certificate>> {
+        CLIENT
+            .post(&format!("http://{}/{}", self.addr, STORAGE_BLOCKS_API))
+            .header("Content-Type", "application/json")
+            .body(serde_json::to_string(&id).unwrap())
+            .send()
+            .await
+            .unwrap()
+            .json::<Option<Block<CryptarchiaTx, Certificate>>>()
+            .await
+            .unwrap()
+    }
+
+    pub fn config(&self) -> &CryptarchiaConfig {
+        &self.config
+    }
+
+    pub fn stop(&mut self) {
+        self.child.kill().unwrap();
+    }
+}
+
+fn create_node_config(
+    id: [u8; 32],
+    coin: Coin,
+    genesis_state: LedgerState,
+    chain_start_time: OffsetDateTime,
+    #[cfg(feature = "mixnet")] mixnet_config: MixnetConfig,
+) -> CryptarchiaConfig {
+    let mut config = CryptarchiaConfig {
+        network: NetworkConfig {
+            backend: Libp2pConfig {
+                inner: Default::default(),
+                initial_peers: vec![],
+                #[cfg(feature = "mixnet")]
+                mixnet: mixnet_config,
+            },
+        },
+        consensus: CryptarchiaSettings::new(
+            (),
+            (),
+            cryptarchia_ledger::Config {
+                epoch_stake_distribution_stabilization: 3,
+                epoch_period_nonce_buffer: 3,
+                epoch_period_nonce_stabilization: 4,
+                consensus_config: cryptarchia_engine::Config {
+                    security_param: 10,
+                    active_slot_coeff: 0.9,
+                },
+            },
+            genesis_state,
+            cryptarchia_consensus::time::Config {
+                slot_duration: Duration::from_secs(1),
+                chain_start_time,
+                max_clock_drift: Duration::from_secs(1),
+            },
+            Default::default(),
+            wallet::Config {
+                coins: vec![coin],
+                keystore: None,
+            },
+        ),
+        log: Default::default(),
+        http: nomos_api::ApiServiceSettings {
+            backend_settings: AxumBackendSettings {
+                address: format!("127.0.0.1:{}", get_available_port())
+                    .parse()
+                    .unwrap(),
+                cors_origins: vec![],
+            },
+        },
+        da: nomos_da::Settings {
+            da_protocol: full_replication::Settings {
+                voter: id,
+                num_attestations: 1,
+            },
+            backend: nomos_da::backend::memory_cache::BlobCacheSettings {
+                max_capacity: usize::MAX,
+                evicting_period: Duration::from_secs(60 * 60 * 24), // 1 day
+            },
+        },
+    };
+
+    config.network.backend.inner.port = get_available_port();
+
+    config
+}
+
+fn node_address(config: &CryptarchiaConfig) -> Multiaddr {
+    Swarm::multiaddr(
+        std::net::Ipv4Addr::new(127, 0, 0, 1),
+        config.network.backend.inner.port,
+    )
+}
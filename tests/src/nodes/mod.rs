@@ -1,9 +1,29 @@
+pub mod cryptarchia;
 pub mod nomos;
 
+pub use cryptarchia::CryptarchiaNode;
 pub use nomos::NomosNode;
+
+#[cfg(feature = "mixnet")]
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    num::NonZeroU8,
+};
+
+#[cfg(feature = "mixnet")]
+use mixnet::{
+    address::NodeAddress,
+    client::MixClientConfig,
+    node::MixNodeConfig,
+    topology::{MixNodeInfo, MixnetTopology},
+};
+#[cfg(feature = "mixnet")]
+use nomos_network::backends::libp2p::Libp2pConfig;
 use tempfile::TempDir;
 
 const LOGS_PREFIX: &str = "__logs";
+#[cfg(feature = "mixnet")]
+const NUM_MIXNODE_CANDIDATES: usize = 2;
 
 fn create_tempdir() -> std::io::Result<TempDir> {
     // It's easier to use the current location instead of OS-default tempfile location
@@ -24,3 +44,46 @@ fn persist_tempdir(tempdir: &mut TempDir, label: &str) -> std::io::Result<()> {
     let _ = dir.into_path();
     Ok(())
 }
+
+#[cfg(feature = "mixnet")]
+fn create_mixnet_config(ids: &[[u8; 32]]) -> (MixClientConfig, Vec<MixNodeConfig>) {
+    let mixnode_configs: Vec<MixNodeConfig> = ids
+        .iter()
+        .map(|id| MixNodeConfig {
+            encryption_private_key: *id,
+            delay_rate_per_min: 100000000.0,
+        })
+        .collect();
+    // Build an empty topology because it will be constructed with meaningful node infos later
+    let topology = MixnetTopology::new(Vec::new(), 0, 0, [1u8; 32]).unwrap();
+
+    (
+        MixClientConfig {
+            topology,
+            emission_rate_per_min: 120.0,
+            redundancy: NonZeroU8::new(1).unwrap(),
+        },
+        mixnode_configs,
+    )
+}
+
+#[cfg(feature = "mixnet")]
+fn build_mixnet_topology(mixnode_candidates: &[&Libp2pConfig]) -> MixnetTopology {
+    use mixnet::crypto::public_key_from;
+
+    let candidates = mixnode_candidates
+        .iter()
+        .map(|config| {
+            MixNodeInfo::new(
+                NodeAddress::from(SocketAddr::new(
+                    IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
+                    config.inner.port,
+                )),
+                public_key_from(config.mixnet.mixnode.encryption_private_key),
+            )
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let num_layers = candidates.len();
+    MixnetTopology::new(candidates, num_layers, 1, [1u8; 32]).unwrap()
+}
@@ -2,25 +2,15 @@
 use std::net::SocketAddr;
 use std::process::{Child, Command, Stdio};
 use std::time::Duration;
-#[cfg(feature = "mixnet")]
-use std::{
-    net::{IpAddr, Ipv4Addr},
-    num::NonZeroU8,
-};
 // internal
+#[cfg(feature = "mixnet")]
+use super::{build_mixnet_topology, create_mixnet_config, NUM_MIXNODE_CANDIDATES};
 use super::{create_tempdir, persist_tempdir, LOGS_PREFIX};
 use crate::{adjust_timeout, get_available_port, ConsensusConfig, Node, SpawnConfig};
-use carnot_consensus::{CarnotInfo, CarnotSettings};
+use carnot_consensus::{crypto::node_id_from_private_key, CarnotInfo, CarnotSettings};
 use carnot_engine::overlay::{RandomBeaconState, RoundRobin, TreeOverlay, TreeOverlaySettings};
 use carnot_engine::{NodeId, Overlay};
 use full_replication::Certificate;
-#[cfg(feature = "mixnet")]
-use mixnet::{
-    address::NodeAddress,
-    client::MixClientConfig,
-    node::MixNodeConfig,
-    topology::{MixNodeInfo, MixnetTopology},
-};
 use nomos_core::{block::Block, header::HeaderId};
 use nomos_libp2p::{Multiaddr, Swarm};
 use nomos_log::{LoggerBackend, LoggerFormat};
@@ -65,7 +55,7 @@ impl Drop for NomosNode {
 
 impl NomosNode {
     pub fn id(&self) -> NodeId {
-        NodeId::from(self.config.consensus.private_key)
+        node_id_from_private_key(self.config.consensus.private_key)
     }
 
     pub async fn spawn(mut config: Config) -> Self {
@@ -265,9 +255,6 @@ impl Node for NomosNode {
     }
 }
 
-#[cfg(feature = "mixnet")]
-const NUM_MIXNODE_CANDIDATES: usize = 2;
-
 /// Returns the config of the next leader and all other nodes.
 ///
 /// Depending on the network topology, the next leader must be spawned first,
@@ -288,7 +275,7 @@ fn create_node_configs(consensus: ConsensusConfig) -> (Config, Vec<Config>) {
         .enumerate()
         .map(|(_i, id)| {
             create_node_config(
-                ids.iter().copied().map(NodeId::new).collect(),
+                ids.iter().copied().map(node_id_from_private_key).collect(),
                 *id,
                 consensus.threshold,
                 consensus.timeout,
@@ -305,7 +292,7 @@ fn create_node_configs(consensus: ConsensusConfig) -> (Config, Vec<Config>) {
     let next_leader = overlay.next_leader();
     let next_leader_idx = ids
         .iter()
-        .position(|&id| NodeId::from(id) == next_leader)
+        .position(|&id| node_id_from_private_key(id) == next_leader)
         .unwrap();
 
     #[cfg(not(feature = "mixnet"))]
@@ -318,11 +305,11 @@ fn create_node_configs(consensus: ConsensusConfig) -> (Config, Vec<Config>) {
         let mut next_leader_config = configs.swap_remove(next_leader_idx);
 
         // Build a topology using only a subset of nodes.
-        let mut mixnode_candidates = vec![&next_leader_config];
+        let mut mixnode_candidates = vec![&next_leader_config.network.backend];
         configs
             .iter()
             .take(NUM_MIXNODE_CANDIDATES - 1)
-            .for_each(|config| mixnode_candidates.push(config));
+            .for_each(|config| mixnode_candidates.push(&config.network.backend));
         let topology = build_mixnet_topology(&mixnode_candidates);
 
         // Set the topology to all configs
@@ -365,8 +352,10 @@ fn create_node_config(
                 super_majority_threshold: Some(threshold),
             },
             timeout,
+            backoff: Default::default(),
             transaction_selector_settings: (),
             blob_selector_settings: (),
+            genesis_accounts: Default::default(),
         },
         log: Default::default(),
         http: nomos_api::ApiServiceSettings {
@@ -394,49 +383,6 @@ fn create_node_config(
     config
 }
 
-#[cfg(feature = "mixnet")]
-fn create_mixnet_config(ids: &[[u8; 32]]) -> (MixClientConfig, Vec<MixNodeConfig>) {
-    let mixnode_configs: Vec<MixNodeConfig> = ids
-        .iter()
-        .map(|id| MixNodeConfig {
-            encryption_private_key: *id,
-            delay_rate_per_min: 100000000.0,
-        })
-        .collect();
-    // Build an empty topology because it will be constructed with meaningful node infos later
-    let topology = MixnetTopology::new(Vec::new(), 0, 0, [1u8; 32]).unwrap();
-
-    (
-        MixClientConfig {
-            topology,
-            emission_rate_per_min: 120.0,
-            redundancy: NonZeroU8::new(1).unwrap(),
-        },
-        mixnode_configs,
-    )
-}
-
-#[cfg(feature = "mixnet")]
-fn build_mixnet_topology(mixnode_candidates: &[&Config]) -> MixnetTopology {
-    use mixnet::crypto::public_key_from;
-
-    let candidates = mixnode_candidates
-        .iter()
-        .map(|config| {
-            MixNodeInfo::new(
-                NodeAddress::from(SocketAddr::new(
-                    IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
-                    config.network.backend.inner.port,
-                )),
-                public_key_from(config.network.backend.mixnet.mixnode.encryption_private_key),
-            )
-            .unwrap()
-        })
-        .collect::<Vec<_>>();
-    let num_layers = candidates.len();
-    MixnetTopology::new(candidates, num_layers, 1, [1u8; 32]).unwrap()
-}
-
 fn node_address(config: &Config) -> Multiaddr {
     Swarm::multiaddr(
         std::net::Ipv4Addr::new(127, 0, 0, 1),
@@ -0,0 +1,70 @@
+use futures::stream::{self, StreamExt};
+use std::collections::HashSet;
+use std::time::Duration;
+use tests::{adjust_timeout, CryptarchiaNode};
+
+// how many blocks past the security parameter the nodes need to produce
+const CHAIN_LENGTH_MULTIPLIER: u32 = 2;
+
+async fn happy_test(nodes: &[CryptarchiaNode]) {
+    let config = &nodes[0].config().consensus;
+    let security_param = config.config.consensus_config.security_param;
+    let n_blocks = security_param * CHAIN_LENGTH_MULTIPLIER;
+    // twice the expected time, to account for empty slots and forks
+    let timeout = Duration::from_secs_f64(
+        n_blocks as f64 / config.config.consensus_config.active_slot_coeff
+            * config.time.slot_duration.as_secs_f64()
+            * 2.0,
+    );
+    let timeout = tokio::time::sleep(adjust_timeout(timeout));
+    tokio::select! {
+        _ = timeout => panic!("timed out waiting for nodes to produce {} blocks", n_blocks),
+        _ = async { while stream::iter(nodes)
+            .any(|n| async move { (n.get_headers(None, None).await.len() as u32) < n_blocks })
+            .await
+        {
+            println!(
+                "waiting... {}",
+                stream::iter(nodes)
+                    .then(|n| async move { format!("{}", n.get_headers(None, None).await.len()) })
+                    .collect::<Vec<_>>()
+                    .await
+                    .join(" | ")
+            );
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+        } => {}
+    };
+
+    let chains = stream::iter(nodes)
+        .then(|n| async move { n.get_headers(None, None).await })
+        .collect::<Vec<_>>()
+        .await;
+    // blocks deeper than the security parameter must be the same on all nodes
+    let blocks = chains
+        .iter()
+        .map(|chain| chain.iter().rev().nth((n_blocks - security_param) as usize))
+        .collect::<HashSet<_>>();
+    assert_eq!(blocks.len(), 1);
+}
+
+#[tokio::test]
+async fn two_nodes_happy() {
+    let nodes = CryptarchiaNode::spawn_nodes(2).await;
+    happy_test(&nodes).await;
+}
+
+#[tokio::test]
+async fn test_get_block() {
+    let nodes = CryptarchiaNode::spawn_nodes(2).await;
+    happy_test(&nodes).await;
+    let id = nodes[0].consensus_info().await.lib;
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while nodes[0].get_block(id).await.is_none() {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            println!("trying...");
+        }
+    })
+    .await
+    .unwrap();
+}
@@ -1,5 +1,5 @@
 use carnot_consensus::CarnotInfo;
-use carnot_engine::{NodeId, View};
+use carnot_engine::{NodeId, QcSignature, View};
 use fraction::Fraction;
 use futures::stream::{self, StreamExt};
 use nomos_core::header::HeaderId;
@@ -92,12 +92,14 @@ fn assert_timeout_qc_consensus<'a>(
         .into_iter()
         .map(|i| {
             i.last_view_timeout_qc.clone().map(|timeout_qc| {
-                // Masking the `sender` field because we want timeout_qcs from different
-                // senders to be considered the same if all other fields are the same.
+                // Masking the `sender` and `signature` fields because we want timeout_qcs from
+                // different senders, aggregating different timeouts, to be considered the same
+                // if all other fields are the same.
                 TimeoutQc::new(
                     timeout_qc.view(),
                     timeout_qc.high_qc().clone(),
                     DUMMY_NODE_ID,
+                    QcSignature::default(),
                 )
             })
         })